@@ -2,7 +2,7 @@ use criterion::{
     black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput,
 };
 use poolshark::global::Pool;
-use poolshark::local::LPooled;
+use poolshark::local::{fast::LPooledFast, LPooled};
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
@@ -173,5 +173,43 @@ fn bench_string(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_vec, bench_hashmap, bench_string);
+// Benchmark: the thread local pool lookup LPooledFast skips on drop
+fn bench_local_fast(c: &mut Criterion) {
+    let mut group = c.benchmark_group("local_fast");
+    for size in SIZES.iter() {
+        group.throughput(Throughput::Elements(*size));
+
+        group.bench_with_input(
+            BenchmarkId::new("local_pooled", size),
+            size,
+            |b, &size| {
+                b.iter(|| {
+                    let mut v: LPooled<Vec<u64>> = LPooled::take();
+                    for i in 0..size {
+                        v.push(black_box(i));
+                    }
+                    black_box(&v);
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("local_pooled_fast", size),
+            size,
+            |b, &size| {
+                b.iter(|| {
+                    let mut v: LPooledFast<Vec<u64>> = LPooledFast::take();
+                    for i in 0..size {
+                        v.push(black_box(i));
+                    }
+                    black_box(&v);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_vec, bench_hashmap, bench_string, bench_local_fast);
 criterion_main!(benches);
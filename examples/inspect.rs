@@ -0,0 +1,43 @@
+// print the pool utilization of a running program.
+//
+// usage: inspect <socket or dump file>
+//
+// the program being inspected must call poolshark::global::inspect::serve,
+// or write a dump file with poolshark::global::inspect::write_dump. With no
+// arguments this example serves its own demo pools and inspects itself.
+use poolshark::global::{inspect, registry, Pool};
+use std::{env, fs::File, io::BufReader, path::PathBuf};
+
+fn main() -> std::io::Result<()> {
+    let pools = match env::args_os().nth(1).map(PathBuf::from) {
+        Some(path) if path.is_file() => {
+            inspect::read_dump(BufReader::new(File::open(path)?))?
+        }
+        Some(path) => inspect::connect(path)?,
+        None => {
+            let strings: Pool<String> = Pool::new(1024, 4096);
+            let bufs: Pool<Vec<u8>> = Pool::new(64, 65536);
+            registry::register("demo::strings", &strings);
+            registry::register("demo::bufs", &bufs);
+            let held: Vec<_> = (0..100)
+                .map(|i| {
+                    let mut s = strings.take();
+                    s.push_str(&i.to_string());
+                    s
+                })
+                .collect();
+            drop(held);
+            let mut b = bufs.take();
+            b.reserve(32768);
+            drop(b);
+            let path = env::temp_dir()
+                .join(format!("poolshark-inspect-{}.sock", std::process::id()));
+            inspect::serve(&path)?;
+            let pools = inspect::connect(&path)?;
+            let _ = std::fs::remove_file(&path);
+            pools
+        }
+    };
+    print!("{}", inspect::table(&pools));
+    Ok(())
+}
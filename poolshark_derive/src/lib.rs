@@ -1,4 +1,5 @@
 use proc_macro::{Span, TokenStream};
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use std::{
     cmp::max,
@@ -10,14 +11,18 @@ use std::{
 
 struct BuildEnv {
     out_dir: PathBuf,
+    dep_dir: PathBuf,
     crate_name: String,
 }
 
 impl BuildEnv {
     fn get() -> Self {
-        let mut t = Self { out_dir: PathBuf::new(), crate_name: String::new() };
+        let mut t = Self {
+            out_dir: PathBuf::new(),
+            dep_dir: PathBuf::new(),
+            crate_name: String::new(),
+        };
         let mut args = std::env::args();
-        let mut dep = PathBuf::new();
         while let Some(arg) = args.next() {
             if arg == "--out-dir" {
                 t.out_dir = PathBuf::from(args.next().expect("missing out dir"));
@@ -32,24 +37,15 @@ impl BuildEnv {
                 t.crate_name = name.to_owned();
             }
             if let Some(s) = arg.strip_prefix("dependency=") {
-                dep = PathBuf::from(s)
-            }
-        }
-        if !t.out_dir.is_dir() {
-            if dep.is_dir() {
-                t.out_dir = dep;
-            } else {
-                t.out_dir = PathBuf::from("target/debug/deps");
-                if !t.out_dir.is_dir() {
-                    panic!("could not find out dir")
-                }
+                t.dep_dir = PathBuf::from(s)
             }
         }
         if t.crate_name.is_empty() || t.crate_name.starts_with("-") {
             match std::env::var("CARGO_PKG_NAME") {
                 Ok(n) => t.crate_name = n,
                 Err(_) => {
-                    let dir = t.out_dir.parent().expect("could not find crate name");
+                    let out_dir = t.out_dir();
+                    let dir = out_dir.parent().expect("could not find crate name");
                     let dir = dir.parent().expect("could not find crate name");
                     let dir = dir.parent().expect("could not find crate name");
                     t.crate_name = String::from_utf8_lossy(
@@ -63,15 +59,79 @@ impl BuildEnv {
         }
         t
     }
+
+    // the compiler's out dir, only looked for when it's needed, since a
+    // hermetic build may not have one we can find
+    fn out_dir(&self) -> PathBuf {
+        if self.out_dir.is_dir() {
+            self.out_dir.clone()
+        } else if self.dep_dir.is_dir() {
+            self.dep_dir.clone()
+        } else {
+            let dir = PathBuf::from("target/debug/deps");
+            if !dir.is_dir() {
+                panic!("could not find out dir")
+            }
+            dir
+        }
+    }
+}
+
+const IDS_FILE: &str = ".poolshark_loc_ids";
+
+// where location ids come from, see the docs of location_id
+enum Strategy {
+    File(PathBuf),
+    Hash,
+}
+
+impl Strategy {
+    fn get(cfg: &BuildEnv) -> Self {
+        if std::env::var("POOLSHARK_LOC_IDS").is_ok_and(|s| s == "hash") {
+            return Self::Hash;
+        }
+        match std::env::var_os("POOLSHARK_LOC_IDS_DIR") {
+            Some(dir) => {
+                let dir = PathBuf::from(dir);
+                if let Err(e) = std::fs::create_dir_all(&dir) {
+                    panic!(
+                        "could not create POOLSHARK_LOC_IDS_DIR {}: {e}",
+                        dir.display()
+                    )
+                }
+                Self::File(dir.join(IDS_FILE))
+            }
+            None => Self::File(cfg.out_dir().join(IDS_FILE)),
+        }
+    }
+}
+
+// set in hashed ids, and never in allocated ones, so the pools know which
+// ids to check for collisions, keep in sync with LocationId::HASHED
+const HASHED: u16 = 0x8000;
+
+// fold a 64 bit FNV-1a hash of the key into a 15 bit id, marked as hashed,
+// the same as LocationId::from_key_hash
+fn hash_id(key: &str) -> u16 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in key.bytes() {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    let id = (h ^ (h >> 16) ^ (h >> 32) ^ (h >> 48)) as u16;
+    id | HASHED
 }
 
 fn allocate_id(path: &Path, key: String) -> u16 {
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)
-        .expect("could not open location ids file");
+    let file = match OpenOptions::new().create(true).read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) => panic!(
+            "could not open location ids file {}: {e}. If the build directory \
+             is read only set POOLSHARK_LOC_IDS_DIR to a writable directory \
+             shared by the whole build, or set POOLSHARK_LOC_IDS=hash",
+            path.display()
+        ),
+    };
     file.lock().expect("could not lock file");
     let mut reader = BufReader::new(file);
     let mut ids = BTreeMap::new();
@@ -97,7 +157,7 @@ fn allocate_id(path: &Path, key: String) -> u16 {
         Some(id) => *id,
         None => {
             let id = max_id + 1;
-            if id < max_id {
+            if id >= HASHED {
                 panic!("too many poolshark location ids")
             }
             ids.insert(key, id);
@@ -137,17 +197,234 @@ fn allocate_id(path: &Path, key: String) -> u16 {
 /// may conflict, however in our use case (poolshark) we don't care about this
 /// case because separately compiled code will have separate thread local
 /// structures and thus poolshark will still work fine
+///
+/// - The build directory is read only, e.g. in a Nix build or a Bazel remote
+/// execution sandbox. See below.
+///
+/// ### Hermetic Builds
+///
+/// Two environment variables, read when the macro is expanded, change where
+/// the ids come from. They must be set the same way for every crate in the
+/// build, and since cargo doesn't know the macro reads them, clean the build
+/// after changing them.
+///
+/// - `POOLSHARK_LOC_IDS_DIR=/some/dir` keeps the ids file in `/some/dir`
+/// instead of the out dir. The directory must be writable, and shared by all
+/// the compiler invocations of the build.
+///
+/// - `POOLSHARK_LOC_IDS=hash` doesn't keep a file at all, each id is a 15 bit
+/// fold of a hash of the key, and the out dir isn't needed. This works in
+/// any sandbox, and the ids are stable across builds, but unlike the file
+/// two keys can collide. Hashed ids are marked, and the pools check the
+/// container type of every object with a hashed id, in release builds as
+/// well, so if two [IsoPoolable] types with the same type parameter layouts
+/// get the same id, using the second one panics rather than sharing the
+/// first one's pools. Only use this when the build can't write anywhere,
+/// and with few location ids (a project with 25 ids has about a 1% chance
+/// of a collision somewhere).
+///
+/// [IsoPoolable]: https://docs.rs/poolshark/latest/poolshark/trait.IsoPoolable.html
 #[proc_macro]
 pub fn location_id(_input: TokenStream) -> TokenStream {
     let cfg = BuildEnv::get();
     let loc = Span::call_site();
     let key =
         format!("{}:{}:{}:{}", cfg.crate_name, loc.file(), loc.line(), loc.column());
-    let path = cfg.out_dir.join(".poolshark_loc_ids");
-    let id = allocate_id(&path, key);
+    let id = match Strategy::get(&cfg) {
+        Strategy::Hash => hash_id(&key),
+        Strategy::File(path) => allocate_id(&path, key),
+    };
     if cfg.crate_name == "poolshark" {
         quote!(crate::LocationId(#id)).into()
     } else {
         quote!(poolshark::LocationId(#id)).into()
     }
 }
+
+// build the pattern binding every field of a struct or variant, the
+// bindings, and an expression constructing it with every field empty
+fn destructure(
+    path: TokenStream2,
+    fields: &syn::Fields,
+) -> (TokenStream2, Vec<syn::Ident>, TokenStream2) {
+    use syn::Fields;
+    let binds: Vec<syn::Ident> =
+        (0..fields.len()).map(|i| quote::format_ident!("f{}", i)).collect();
+    match fields {
+        Fields::Unit => (quote!(#path), binds, quote!(#path)),
+        Fields::Unnamed(_) => {
+            let empty = binds.iter().map(|_| quote!(::poolshark::Poolable::empty()));
+            (quote!(#path(#(#binds),*)), binds.clone(), quote!(#path(#(#empty),*)))
+        }
+        Fields::Named(f) => {
+            let names: Vec<_> = f.named.iter().map(|f| f.ident.clone().unwrap()).collect();
+            (
+                quote!(#path { #(#names: #binds),* }),
+                binds,
+                quote!(#path { #(#names: ::poolshark::Poolable::empty()),* }),
+            )
+        }
+    }
+}
+
+fn has_empty_attr(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("poolable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("empty") {
+                found = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `empty`"))
+            }
+        })?;
+    }
+    Ok(found)
+}
+
+/// Derive `Poolable` for a struct or enum whose fields are all `Poolable`
+///
+/// - `empty` builds every field with `Poolable::empty`. An enum is created
+///   as the variant marked `#[poolable(empty)]`, which may be omitted if
+///   there is only one variant.
+/// - `reset` resets every field of a struct, or of the current variant of an
+///   enum. An enum keeps its current variant, so its allocations can be
+///   reused by the next value of the same variant, which means a taken enum
+///   with more than one variant may not be the empty one, so its `REUSE` is
+///   `ReusePolicy::Retained`.
+/// - `try_reset` tries to reset the same fields, and fails if any of them
+///   fails.
+/// - `capacity` and `allocated_bytes` are the sums over the same fields.
+///
+/// ```ignore
+/// #[derive(Poolable)]
+/// enum Message {
+///     #[poolable(empty)]
+///     Heartbeat,
+///     Batch(Vec<u64>),
+///     Update { key: String, values: Vec<u8> },
+/// }
+/// ```
+#[proc_macro_derive(Poolable, attributes(poolable))]
+pub fn derive_poolable(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match derive_poolable_inner(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn derive_poolable_inner(input: syn::DeriveInput) -> syn::Result<TokenStream2> {
+    use syn::Data;
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let mut field_types = vec![];
+    // one arm per variant, or one pattern for a struct, binding every field
+    let mut pats = vec![];
+    let mut bindings = vec![];
+    // reset keeps the current variant, so an enum that has another one
+    // isn't left empty
+    let mut retained = false;
+    let empty = match &input.data {
+        Data::Struct(s) => {
+            field_types.extend(s.fields.iter().map(|f| f.ty.clone()));
+            let (pat, binds, ctor) = destructure(quote!(#name), &s.fields);
+            pats.push(pat);
+            bindings.push(binds);
+            ctor
+        }
+        Data::Enum(e) => {
+            let mut empty = None;
+            for v in &e.variants {
+                if has_empty_attr(&v.attrs)? {
+                    if empty.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            v,
+                            "only one variant may be #[poolable(empty)]",
+                        ));
+                    }
+                    empty = Some(v);
+                }
+            }
+            let empty = match empty {
+                Some(v) => v,
+                None if e.variants.len() == 1 => &e.variants[0],
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "mark the variant to create empty values as with #[poolable(empty)]",
+                    ));
+                }
+            };
+            retained = e.variants.len() > 1;
+            let mut empty_ctor = None;
+            for v in &e.variants {
+                let vname = &v.ident;
+                field_types.extend(v.fields.iter().map(|f| f.ty.clone()));
+                let (pat, binds, ctor) = destructure(quote!(#name::#vname), &v.fields);
+                pats.push(pat);
+                bindings.push(binds);
+                if v.ident == empty.ident {
+                    empty_ctor = Some(ctor)
+                }
+            }
+            empty_ctor.unwrap()
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new_spanned(
+                u.union_token,
+                "Poolable can't be derived for unions",
+            ));
+        }
+    };
+    let where_clause = generics.make_where_clause();
+    for ty in &field_types {
+        where_clause.predicates.push(syn::parse_quote!(#ty: ::poolshark::Poolable));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let reset = pats.iter().zip(&bindings).map(|(pat, binds)| {
+        quote!(#pat => { #(::poolshark::Poolable::reset(#binds);)* })
+    });
+    let try_reset = pats.iter().zip(&bindings).map(|(pat, binds)| {
+        quote!(#pat => { #(::poolshark::Poolable::try_reset(#binds)?;)* })
+    });
+    let capacity = pats.iter().zip(&bindings).map(|(pat, binds)| {
+        quote!(#pat => 0usize #(+ ::poolshark::Poolable::capacity(#binds))*)
+    });
+    let bytes = pats.iter().zip(&bindings).map(|(pat, binds)| {
+        quote!(#pat => 0usize #(+ ::poolshark::Poolable::allocated_bytes(#binds))*)
+    });
+    let reuse = retained.then(|| {
+        quote!(const REUSE: ::poolshark::ReusePolicy = ::poolshark::ReusePolicy::Retained;)
+    });
+    Ok(quote! {
+        impl #impl_generics ::poolshark::Poolable for #name #ty_generics #where_clause {
+            #reuse
+
+            fn empty() -> Self {
+                #empty
+            }
+
+            #[allow(unused_variables)]
+            fn reset(&mut self) {
+                match self { #(#reset)* }
+            }
+
+            #[allow(unused_variables)]
+            fn try_reset(&mut self) -> ::std::result::Result<(), ::poolshark::ResetError> {
+                match self { #(#try_reset)* }
+                Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn capacity(&self) -> usize {
+                match self { #(#capacity,)* }
+            }
+
+            #[allow(unused_variables)]
+            fn allocated_bytes(&self) -> usize {
+                match self { #(#bytes,)* }
+            }
+        }
+    })
+}
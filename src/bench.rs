@@ -0,0 +1,206 @@
+//! Helpers for fair pooled vs unpooled benchmarks.
+//!
+//! A benchmark of pooled objects that starts with empty pools mostly
+//! measures the allocator, and one that doesn't check for allocations can't
+//! tell whether pooling is working at all. The `warm_*` functions fill a
+//! pool with objects already grown to their steady state size, and
+//! [CountingAlloc] with [count_allocs] reports how many allocator calls each
+//! iteration really makes. Neither depends on a benchmark harness, so they
+//! work with criterion, divan, or a plain loop.
+//!
+//! # Example
+//!
+//! ```rust,standalone_crate
+//! use poolshark::{bench::{self, CountingAlloc}, local::LPooled};
+//!
+//! #[global_allocator]
+//! static ALLOC: CountingAlloc = CountingAlloc::system();
+//!
+//! fn main() {
+//!     bench::warm_local::<Vec<u64>, _>(4, |v| v.reserve(100));
+//!     let ((), counts) = bench::count_allocs(|| {
+//!         for _ in 0..1000 {
+//!             let mut v: LPooled<Vec<u64>> = LPooled::take();
+//!             v.extend(0..100);
+//!         }
+//!     });
+//!     assert_eq!(counts.allocs, 0);
+//! }
+//! ```
+use crate::{
+    global::{self, Pool},
+    local::LPooled,
+    IsoPoolable, Poolable,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    ops::Sub,
+};
+
+/// Take `n` objects from this thread's local pool of `T`s, call `grow` on
+/// each, e.g. to reserve its steady state capacity, and return them all.
+///
+/// Objects grown past the pool's maximum element capacity are dropped, see
+/// [local::set_size](crate::local::set_size).
+pub fn warm_local<T: IsoPoolable, F: FnMut(&mut T)>(n: usize, mut grow: F) {
+    let batch: Vec<LPooled<T>> = (0..n)
+        .map(|_| {
+            let mut t = LPooled::take();
+            grow(&mut t);
+            t
+        })
+        .collect();
+    drop(batch)
+}
+
+/// Like [warm_local], but for this thread's global pool of `T`s, see
+/// [global::take]
+pub fn warm_global<T: IsoPoolable, F: FnMut(&mut T)>(n: usize, mut grow: F) {
+    let batch: Vec<_> = (0..n)
+        .map(|_| {
+            let mut t = global::take::<T>();
+            grow(&mut t);
+            t
+        })
+        .collect();
+    drop(batch)
+}
+
+/// Like [warm_local], but for `pool`
+pub fn warm_pool<T: Poolable, F: FnMut(&mut T)>(pool: &Pool<T>, n: usize, mut grow: F) {
+    let batch: Vec<_> = (0..n)
+        .map(|_| {
+            let mut t = pool.take();
+            grow(&mut t);
+            t
+        })
+        .collect();
+    drop(batch)
+}
+
+thread_local! {
+    static COUNTS: Cell<AllocCounts> = const { Cell::new(AllocCounts::ZERO) };
+}
+
+fn record<F: FnOnce(&mut AllocCounts)>(f: F) {
+    // the thread local may be gone during thread exit
+    let _ = COUNTS.try_with(|c| {
+        let mut counts = c.get();
+        f(&mut counts);
+        c.set(counts)
+    });
+}
+
+/// Allocator calls made by one thread, see [CountingAlloc]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocCounts {
+    /// Calls to `alloc` and `alloc_zeroed`
+    pub allocs: u64,
+    /// Calls to `realloc`
+    pub reallocs: u64,
+    /// Calls to `dealloc`
+    pub deallocs: u64,
+    /// Bytes requested by `alloc`, `alloc_zeroed` and growing `realloc`s
+    pub bytes: u64,
+}
+
+impl AllocCounts {
+    const ZERO: Self = Self { allocs: 0, reallocs: 0, deallocs: 0, bytes: 0 };
+
+    /// The calls this thread has made so far. Always zero unless a
+    /// [CountingAlloc] is the global allocator.
+    pub fn current() -> Self {
+        COUNTS.try_with(|c| c.get()).unwrap_or_default()
+    }
+
+    /// The calls this thread has made since `self` was current
+    pub fn elapsed(&self) -> Self {
+        Self::current() - *self
+    }
+
+    /// The total calls, of every kind, that allocate or free memory
+    pub fn calls(&self) -> u64 {
+        self.allocs + self.reallocs + self.deallocs
+    }
+
+    /// The average [calls](Self::calls) per iteration of a benchmark that
+    /// ran `iters` times
+    pub fn calls_per_iter(&self, iters: u64) -> f64 {
+        self.calls() as f64 / iters.max(1) as f64
+    }
+}
+
+impl Sub for AllocCounts {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            allocs: self.allocs - rhs.allocs,
+            reallocs: self.reallocs - rhs.reallocs,
+            deallocs: self.deallocs - rhs.deallocs,
+            bytes: self.bytes - rhs.bytes,
+        }
+    }
+}
+
+/// Run `f` and return what it returned along with the allocator calls it
+/// made on this thread
+pub fn count_allocs<R, F: FnOnce() -> R>(f: F) -> (R, AllocCounts) {
+    let start = AllocCounts::current();
+    let r = f();
+    (r, start.elapsed())
+}
+
+/// A global allocator that counts the calls each thread makes to `A`.
+///
+/// Install it in a benchmark binary with `#[global_allocator]` and read
+/// the counts with [AllocCounts::current] or [count_allocs]. Counting costs
+/// a thread local access per call.
+#[derive(Debug, Default)]
+pub struct CountingAlloc<A = System>(A);
+
+impl CountingAlloc<System> {
+    /// Count the calls made to the system allocator
+    pub const fn system() -> Self {
+        Self(System)
+    }
+}
+
+impl<A> CountingAlloc<A> {
+    /// Count the calls made to `alloc`
+    pub const fn new(alloc: A) -> Self {
+        Self(alloc)
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(|c| {
+            c.allocs += 1;
+            c.bytes += layout.size() as u64
+        });
+        unsafe { self.0.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(|c| {
+            c.allocs += 1;
+            c.bytes += layout.size() as u64
+        });
+        unsafe { self.0.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(|c| c.deallocs += 1);
+        unsafe { self.0.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(|c| {
+            c.reallocs += 1;
+            c.bytes += new_size.saturating_sub(layout.size()) as u64
+        });
+        unsafe { self.0.realloc(ptr, layout, new_size) }
+    }
+}
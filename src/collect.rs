@@ -0,0 +1,74 @@
+//! Collect iterators directly into pooled containers.
+//!
+//! `FromIterator` for [LPooled] already takes from the local pool, but it
+//! can't right-size the container and it can't target a specific global pool.
+//! [PoolCollect] does both, reserving the iterator's lower size hint up front
+//! so a recycled container that is too small only grows once.
+//!
+//! ```
+//! use poolshark::{collect::PoolCollect, global::Pool, local::LPooled};
+//! use std::collections::HashMap;
+//!
+//! let pool: Pool<Vec<u64>> = Pool::new(64, 1024);
+//! let squares = (0..10u64).map(|i| i * i).collect_into_pool(&pool);
+//! assert_eq!(squares[3], 9);
+//!
+//! let map: LPooled<HashMap<u64, u64>> = (0..10u64).map(|i| (i, i)).collect_local();
+//! assert_eq!(map[&3], 3);
+//! ```
+use crate::{
+    global::{self, GPooled, Pool},
+    local::{self, LPooled},
+    IsoPoolable, Poolable,
+};
+
+fn fill<T, I>(t: &mut T, iter: I)
+where
+    T: Poolable + Extend<I::Item>,
+    I: Iterator,
+{
+    let (lower, _) = iter.size_hint();
+    if lower > t.capacity() {
+        // this is just an optimization, if the allocation fails here then
+        // extend will report it
+        let _ = t.try_reserve(lower);
+    }
+    t.extend(iter)
+}
+
+/// Extension trait to collect any iterator into a pooled container
+pub trait PoolCollect: Iterator + Sized {
+    /// Collect into a `T` taken from `pool`.
+    ///
+    /// The result will be returned to `pool` when it is dropped.
+    fn collect_into_pool<T>(self, pool: &Pool<T>) -> GPooled<T>
+    where
+        T: Poolable + Extend<Self::Item>,
+    {
+        let mut t = pool.take();
+        fill(&mut *t, self);
+        t
+    }
+
+    /// Collect into a `T` taken from the thread local global pool.
+    fn collect_global<T>(self) -> GPooled<T>
+    where
+        T: IsoPoolable + Extend<Self::Item>,
+    {
+        let mut t = global::take::<T>();
+        fill(&mut *t, self);
+        t
+    }
+
+    /// Collect into a `T` taken from the local pool.
+    fn collect_local<T>(self) -> LPooled<T>
+    where
+        T: IsoPoolable + Extend<Self::Item>,
+    {
+        let mut t = LPooled::from(local::take::<T>());
+        fill(&mut *t, self);
+        t
+    }
+}
+
+impl<I: Iterator> PoolCollect for I {}
@@ -0,0 +1,206 @@
+//! The API of the `lifeguard` crate.
+//!
+//! [Pool] has the methods of lifeguard's `Pool`, and hands out [Recycled]
+//! values that go back to it when they are dropped. It is backed by a
+//! global [Pool](crate::global::Pool), so unlike lifeguard's it can be
+//! shared between threads. lifeguard's `RcRecycled`, which doesn't borrow
+//! the pool, is just a [GPooled].
+//!
+//! Differences from `lifeguard`:
+//!
+//! - pooled types implement [Poolable] instead of `Recycleable`, `empty`
+//!   is its `new` and `reset` is the same
+//! - pools can't grow without limit, [with_size](Pool::with_size) retains
+//!   at most the larger of the starting size and 1024 objects
+//! - objects with no capacity, e.g. an empty `String`, aren't retained
+//! - there is no builder, use [with_size_and_max](Pool::with_size_and_max)
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::compat::lifeguard::Pool;
+//!
+//! let pool: Pool<String> = Pool::with_size(16);
+//! {
+//!     let s = pool.new_from("cat");
+//!     assert_eq!(&*s, "cat");
+//! } // cleared and returned to the pool
+//! assert_eq!(pool.size(), 16);
+//! ```
+use crate::{
+    global::{self, GPooled},
+    Poolable,
+};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+const DEFAULT_MAX_SIZE: usize = 1024;
+
+/// Initialize a pooled value from `T`, for [Pool::new_from].
+pub trait InitializeWith<T> {
+    /// set the value from `source`, it starts out empty
+    fn initialize_with(&mut self, source: T);
+}
+
+impl InitializeWith<&str> for String {
+    fn initialize_with(&mut self, source: &str) {
+        self.push_str(source)
+    }
+}
+
+impl InitializeWith<String> for String {
+    fn initialize_with(&mut self, source: String) {
+        *self = source
+    }
+}
+
+impl<T: Clone> InitializeWith<&[T]> for Vec<T> {
+    fn initialize_with(&mut self, source: &[T]) {
+        self.extend_from_slice(source)
+    }
+}
+
+/// lifeguard's `RcRecycled`, a value that returns to its pool when it is
+/// dropped without borrowing the pool
+pub type RcRecycled<T> = GPooled<T>;
+
+/// A pool of `T`s, see the [module level documentation](self).
+pub struct Pool<T: Poolable>(global::Pool<T>);
+
+impl<T: Poolable> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool").field("size", &self.size()).finish()
+    }
+}
+
+impl<T: Poolable> Pool<T> {
+    /// Create a pool holding `size` new values
+    pub fn with_size(size: usize) -> Pool<T> {
+        Self::with_size_and_max(size, size.max(DEFAULT_MAX_SIZE))
+    }
+
+    /// Create a pool holding `starting_size` new values, that retains at
+    /// most `max_size`, or one if `max_size` is 0
+    pub fn with_size_and_max(starting_size: usize, max_size: usize) -> Pool<T> {
+        let max_size = max_size.max(1);
+        let pool = Pool(global::Pool::new(max_size, usize::MAX));
+        // lifeguard's values start out allocated, ours start out empty and
+        // wouldn't be retained, so give them room for one element
+        for _ in 0..starting_size.min(max_size) {
+            let mut t = T::empty();
+            let _ = t.try_reserve(1);
+            pool.attach(t)
+        }
+        pool
+    }
+
+    /// The number of values in the pool
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The maximum number of values the pool retains
+    pub fn max_size(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Take a value from the pool, or create one if it is empty
+    #[allow(clippy::new_ret_no_self, clippy::should_implement_trait)]
+    pub fn new(&self) -> Recycled<'_, T> {
+        Recycled { t: self.0.take(), _pool: self }
+    }
+
+    /// Take a value from the pool and initialize it with `source`
+    pub fn new_from<A>(&self, source: A) -> Recycled<'_, T>
+    where
+        T: InitializeWith<A>,
+    {
+        let mut r = self.new();
+        r.initialize_with(source);
+        r
+    }
+
+    /// Take a value that doesn't borrow the pool
+    pub fn new_rc(&self) -> RcRecycled<T> {
+        self.0.take()
+    }
+
+    /// Take a value that doesn't borrow the pool and initialize it with
+    /// `source`
+    pub fn new_rc_from<A>(&self, source: A) -> RcRecycled<T>
+    where
+        T: InitializeWith<A>,
+    {
+        let mut t = self.new_rc();
+        t.initialize_with(source);
+        t
+    }
+
+    /// Put `value` in the pool
+    pub fn attach(&self, value: T) {
+        self.0.insert(GPooled::with_weak(self.0.downgrade(), value))
+    }
+
+    /// Take a value out of the pool for good, or `None` if it is empty
+    pub fn detach(&self) -> Option<T> {
+        self.0.try_take().map(GPooled::detach)
+    }
+
+    /// Return the underlying poolshark pool
+    pub fn inner(&self) -> &global::Pool<T> {
+        &self.0
+    }
+}
+
+/// A value that returns to its [Pool] when it is dropped.
+pub struct Recycled<'a, T: Poolable> {
+    t: GPooled<T>,
+    _pool: &'a Pool<T>,
+}
+
+impl<T: Poolable + fmt::Debug> fmt::Debug for Recycled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &*self.t)
+    }
+}
+
+impl<T: Poolable + fmt::Display> fmt::Display for Recycled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &*self.t)
+    }
+}
+
+impl<T: Poolable> Recycled<'_, T> {
+    /// Take the value out, so it doesn't go back to the pool
+    pub fn detach(self) -> T {
+        self.t.detach()
+    }
+}
+
+impl<T: Poolable> AsRef<T> for Recycled<'_, T> {
+    fn as_ref(&self) -> &T {
+        &self.t
+    }
+}
+
+impl<T: Poolable> AsMut<T> for Recycled<'_, T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.t
+    }
+}
+
+impl<T: Poolable> Deref for Recycled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.t
+    }
+}
+
+impl<T: Poolable> DerefMut for Recycled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.t
+    }
+}
@@ -0,0 +1,16 @@
+//! Adapters with the APIs of other pool crates.
+//!
+//! Moving a large codebase from another pool crate to poolshark one call
+//! site at a time is tedious. These modules mimic the types and methods of
+//! popular pool crates, backed by poolshark pools, so the migration starts
+//! with changing imports, and call sites can move to the native API later.
+//!
+//! - [object_pool] mimics the `object-pool` crate's `Pool::pull`
+//! - [lifeguard] mimics the `lifeguard` crate's recycling pool
+//!
+//! The adapters keep the names and signatures, not every behaviour. In
+//! particular pooled types must be [Poolable](crate::Poolable), and objects
+//! are reset when they go back to the pool, which neither crate does for
+//! you. Each module lists its differences.
+pub mod lifeguard;
+pub mod object_pool;
@@ -0,0 +1,140 @@
+//! The API of the `object-pool` crate.
+//!
+//! [Pool] and [Reusable] have the methods of their `object-pool`
+//! namesakes, backed by a global [Pool](crate::global::Pool), so a pool can
+//! be shared between threads and its objects dropped anywhere.
+//!
+//! Differences from `object-pool`:
+//!
+//! - pooled types must be [Poolable], and objects are reset when they are
+//!   returned, so code that cleared them after a pull can stop
+//! - a pool retains at most the number of objects it was created with,
+//!   extra objects are dropped when they are returned
+//! - objects with no capacity, e.g. an empty `Vec`, aren't retained
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::compat::object_pool::Pool;
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(32, || Vec::with_capacity(4096));
+//! let mut reusable_buff = pool.pull(|| Vec::with_capacity(4096));
+//! reusable_buff.extend_from_slice(b"hello");
+//! drop(reusable_buff); // cleared and returned to the pool
+//! assert_eq!(pool.len(), 32);
+//! ```
+use crate::{
+    global::{self, GPooled},
+    Poolable,
+};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// A pool of `T`s, see the [module level documentation](self).
+pub struct Pool<T: Poolable>(global::Pool<T>);
+
+impl<T: Poolable> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool").field("len", &self.len()).finish()
+    }
+}
+
+impl<T: Poolable> Pool<T> {
+    /// Create a pool holding `cap` objects made by `init`. The pool has
+    /// room for at least one object, even if `cap` is 0.
+    pub fn new<F: Fn() -> T>(cap: usize, init: F) -> Pool<T> {
+        let pool = Pool(global::Pool::new(cap.max(1), usize::MAX));
+        for _ in 0..cap {
+            pool.attach(init())
+        }
+        pool
+    }
+
+    /// Create a pool holding the objects in `v`, with room for at least
+    /// one object if `v` is empty
+    pub fn from_vec(v: Vec<T>) -> Pool<T> {
+        let pool = Pool(global::Pool::new(v.len().max(1), usize::MAX));
+        for t in v {
+            pool.attach(t)
+        }
+        pool
+    }
+
+    /// The number of objects in the pool
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return true if there are no objects in the pool
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Take an object from the pool, or `None` if it is empty
+    pub fn try_pull(&self) -> Option<Reusable<'_, T>> {
+        self.0.try_take().map(|t| Reusable { pool: self, t })
+    }
+
+    /// Take an object from the pool, or make one with `fallback` if it is
+    /// empty. Either way it returns to the pool when it is dropped.
+    pub fn pull<F: Fn() -> T>(&self, fallback: F) -> Reusable<'_, T> {
+        self.try_pull().unwrap_or_else(|| Reusable::new(self, fallback()))
+    }
+
+    /// Put `t` in the pool
+    pub fn attach(&self, t: T) {
+        self.0.insert(GPooled::with_weak(self.0.downgrade(), t))
+    }
+
+    /// Return the underlying poolshark pool
+    pub fn inner(&self) -> &global::Pool<T> {
+        &self.0
+    }
+}
+
+/// An object that returns to its [Pool] when it is dropped.
+pub struct Reusable<'a, T: Poolable> {
+    pool: &'a Pool<T>,
+    t: GPooled<T>,
+}
+
+impl<T: Poolable + fmt::Debug> fmt::Debug for Reusable<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &*self.t)
+    }
+}
+
+impl<'a, T: Poolable> Reusable<'a, T> {
+    /// Wrap `t`, which will go to `pool` when it is dropped
+    pub fn new(pool: &'a Pool<T>, t: T) -> Self {
+        Self { pool, t: GPooled::with_weak(pool.0.downgrade(), t) }
+    }
+
+    /// Take the object out, so it doesn't go back to the pool. The pool is
+    /// returned too, so the object can be [attached](Pool::attach) again.
+    pub fn detach(self) -> (&'a Pool<T>, T) {
+        (self.pool, self.t.detach())
+    }
+
+    /// Convert into the poolshark equivalent, which isn't tied to the
+    /// pool's lifetime
+    pub fn into_gpooled(self) -> GPooled<T> {
+        self.t
+    }
+}
+
+impl<T: Poolable> Deref for Reusable<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.t
+    }
+}
+
+impl<T: Poolable> DerefMut for Reusable<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.t
+    }
+}
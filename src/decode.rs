@@ -0,0 +1,99 @@
+//! Pooled building blocks for hand written binary decoders.
+//!
+//! Decoding a message usually produces a few strings, byte buffers and
+//! vecs of records, and every decoder ends up wiring the same pooled types
+//! together. The functions here decode straight into local pooled
+//! containers, and [DecodeScratch] bundles the working buffers a decoder
+//! needs, so they are taken from one pool, and returned to it, as a unit.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::{decode::{self, DecodeScratch}, local::LPooled};
+//! use std::str::Utf8Error;
+//!
+//! // a length prefixed utf8 string followed by length prefixed u16s
+//! fn decode_record(mut buf: &[u8]) -> Result<(LPooled<String>, LPooled<Vec<u16>>), Utf8Error> {
+//!     let mut scratch = DecodeScratch::<u16>::take();
+//!     let n = buf[0] as usize;
+//!     let name = decode::string(&buf[1..1 + n])?;
+//!     buf = &buf[1 + n..];
+//!     let n = buf[0] as usize;
+//!     scratch.items.extend(buf[1..].chunks(2).take(n).map(|b| u16::from_le_bytes([b[0], b[1]])));
+//!     Ok((name, decode::vec_from_slice(&scratch.items)))
+//! }
+//!
+//! let (name, values) = decode_record(b"\x02hi\x02\x01\x00\x02\x00").unwrap();
+//! assert_eq!(&*name, "hi");
+//! assert_eq!(&*values, &[1, 2]);
+//! ```
+use crate::{local::LPooled, location_id, Discriminant, IsoPoolable};
+use std::str::{self, Utf8Error};
+
+/// The working buffers of a decoder, pooled as a unit.
+///
+/// Taking a `DecodeScratch` from the local pool gets all three buffers at
+/// once, with the capacity they grew to the last time they were used, and
+/// dropping it returns them all.
+#[derive(Debug, crate::Poolable)]
+pub struct DecodeScratch<T> {
+    /// Raw bytes, e.g. a frame being reassembled
+    pub bytes: Vec<u8>,
+    /// Text, e.g. a string being unescaped
+    pub text: String,
+    /// Decoded records
+    pub items: Vec<T>,
+}
+
+unsafe impl<T> IsoPoolable for DecodeScratch<T> {
+    const DISCRIMINANT: Option<Discriminant> = Discriminant::new_p1::<T>(location_id!());
+}
+
+impl<T> DecodeScratch<T> {
+    /// Take a scratch bundle from the local pool
+    pub fn take() -> LPooled<Self> {
+        LPooled::take()
+    }
+
+    /// Replace the text buffer with the utf8 in `bytes`, and return it
+    pub fn text_from(&mut self, bytes: &[u8]) -> Result<&str, Utf8Error> {
+        self.text.clear();
+        self.text.push_str(str::from_utf8(bytes)?);
+        Ok(&self.text)
+    }
+}
+
+/// Decode the utf8 in `bytes` into a pooled string
+pub fn string(bytes: &[u8]) -> Result<LPooled<String>, Utf8Error> {
+    let s = str::from_utf8(bytes)?;
+    let mut res: LPooled<String> = LPooled::take();
+    res.push_str(s);
+    Ok(res)
+}
+
+/// Copy `bytes` into a pooled byte buffer
+pub fn bytes(bytes: &[u8]) -> LPooled<Vec<u8>> {
+    vec_from_slice(bytes)
+}
+
+/// Copy `items` into a pooled vec
+pub fn vec_from_slice<T: Clone>(items: &[T]) -> LPooled<Vec<T>> {
+    let mut res: LPooled<Vec<T>> = LPooled::take();
+    res.extend_from_slice(items);
+    res
+}
+
+/// Decode `n` records by calling `f` `n` times, collecting them into a
+/// pooled vec, and stopping at the first error
+pub fn vec<T, E, F: FnMut() -> Result<T, E>>(
+    n: usize,
+    mut f: F,
+) -> Result<LPooled<Vec<T>>, E> {
+    let mut res: LPooled<Vec<T>> = LPooled::take();
+    // n usually comes off the wire, so don't trust it for the allocation
+    res.reserve(n.min(1024));
+    for _ in 0..n {
+        res.push(f()?)
+    }
+    Ok(res)
+}
@@ -0,0 +1,44 @@
+//! Encoding bytes as text into pooled strings.
+//!
+//! Base64 and hex encoding sit on the hot path of a lot of code, request
+//! ids, digests, tokens, binary fields in json, and each encoding usually
+//! allocates a fresh `String` that is dropped moments later. These
+//! helpers encode into a local pooled `String` instead, so steady state
+//! encoding doesn't allocate.
+//!
+//! Base64 is enabled by the `base64` feature and hex by the `hex` feature.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(all(feature = "base64", feature = "hex"))] {
+//! use poolshark::encode::{base64_encode_pooled, hex_encode_pooled};
+//!
+//! assert_eq!(&*base64_encode_pooled(b"poolshark"), "cG9vbHNoYXJr");
+//! assert_eq!(&*hex_encode_pooled([0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+//! # }
+//! ```
+#[cfg(any(feature = "base64", feature = "hex"))]
+use crate::local::LPooled;
+
+/// Encode `input` as standard, padded, base64 into a pooled string
+#[cfg(feature = "base64")]
+pub fn base64_encode_pooled<T: AsRef<[u8]>>(input: T) -> LPooled<String> {
+    use base64::Engine;
+    let mut buf: LPooled<String> = LPooled::take();
+    base64::engine::general_purpose::STANDARD.encode_string(input, &mut buf);
+    buf
+}
+
+/// Encode `input` as lower case hex into a pooled string
+#[cfg(feature = "hex")]
+pub fn hex_encode_pooled<T: AsRef<[u8]>>(input: T) -> LPooled<String> {
+    let input = input.as_ref();
+    let mut buf: LPooled<String> = LPooled::take();
+    // safe because the buffer starts empty, and is entirely overwritten
+    // with hex digits, which are ascii
+    let v = unsafe { buf.as_mut_vec() };
+    v.resize(input.len() * 2, 0);
+    hex::encode_to_slice(input, v).expect("the output is exactly twice the input");
+    buf
+}
@@ -0,0 +1,167 @@
+//! Formatting timestamps into pooled strings.
+//!
+//! Log formatting allocates a string per record, and the timestamp is
+//! often the first thing formatted. A [FormatBuffer] is a local pooled
+//! `String` that timestamps, and anything else, can be written into, so
+//! formatting a record reuses a buffer from the pool instead.
+//!
+//! Support for `chrono` and `time` timestamps is enabled by the features of
+//! the same name.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "chrono")] {
+//! use poolshark::format::FormatBuffer;
+//! use std::fmt::Write;
+//!
+//! let ts = chrono::DateTime::from_timestamp(0, 0).unwrap();
+//! let mut buf = FormatBuffer::new();
+//! buf.push_chrono(&ts, "%Y-%m-%d %H:%M:%S");
+//! write!(buf, " INFO started").unwrap();
+//! assert_eq!(&*buf, "1970-01-01 00:00:00 INFO started");
+//! # }
+//! ```
+use crate::local::LPooled;
+use std::{fmt, ops::Deref};
+
+/// A pooled string buffer for formatting, see the
+/// [module level documentation](self).
+///
+/// The buffer is returned to the local `String` pool when dropped.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatBuffer(LPooled<String>);
+
+impl FormatBuffer {
+    /// Take an empty buffer from the local pool
+    pub fn new() -> Self {
+        Self(LPooled::take())
+    }
+
+    /// Empty the buffer, keeping its allocation
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// The formatted string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Return the underlying pooled string
+    pub fn into_inner(self) -> LPooled<String> {
+        self.0
+    }
+
+    /// Append `ts` formatted with the chrono `strftime` style format `fmt`.
+    ///
+    /// # Panics
+    ///
+    /// If `fmt` is invalid, like `to_string` on chrono's `DelayedFormat`.
+    #[cfg(feature = "chrono")]
+    pub fn push_chrono<Tz>(&mut self, ts: &chrono::DateTime<Tz>, fmt: &str)
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: fmt::Display,
+    {
+        use std::fmt::Write;
+        write!(self.0, "{}", ts.format(fmt)).expect("invalid chrono format string")
+    }
+
+    /// Append `ts` in RFC 3339 format
+    #[cfg(feature = "chrono")]
+    pub fn push_chrono_rfc3339<Tz>(&mut self, ts: &chrono::DateTime<Tz>)
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: fmt::Display,
+    {
+        self.push_chrono(ts, "%+")
+    }
+
+    /// Append `ts` formatted with the `time` format description `fmt`
+    #[cfg(feature = "time")]
+    pub fn push_time<F>(
+        &mut self,
+        ts: &time::OffsetDateTime,
+        fmt: &F,
+    ) -> Result<(), time::error::Format>
+    where
+        F: time::formatting::Formattable + ?Sized,
+    {
+        let mut w = Utf8Writer(&mut self.0);
+        ts.format_into(&mut w, fmt).map(|_| ())
+    }
+}
+
+impl Deref for FormatBuffer {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for FormatBuffer {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Write for FormatBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Display for FormatBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// time formats into an io::Write, it only ever writes whole strings
+#[cfg(feature = "time")]
+struct Utf8Writer<'a>(&'a mut String);
+
+#[cfg(feature = "time")]
+impl std::io::Write for Utf8Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let s = std::str::from_utf8(buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.0.push_str(s);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Format `ts` with the chrono `strftime` style format `fmt` into a pooled
+/// string
+#[cfg(feature = "chrono")]
+pub fn format_chrono<Tz>(ts: &chrono::DateTime<Tz>, fmt: &str) -> LPooled<String>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    let mut buf = FormatBuffer::new();
+    buf.push_chrono(ts, fmt);
+    buf.into_inner()
+}
+
+/// Format `ts` with the `time` format description `fmt` into a pooled
+/// string
+#[cfg(feature = "time")]
+pub fn format_time<F>(
+    ts: &time::OffsetDateTime,
+    fmt: &F,
+) -> Result<LPooled<String>, time::error::Format>
+where
+    F: time::formatting::Formattable + ?Sized,
+{
+    let mut buf = FormatBuffer::new();
+    buf.push_time(ts, fmt)?;
+    Ok(buf.into_inner())
+}
@@ -17,9 +17,16 @@
 //! drop(arc2);  // Returns to pool when last reference is dropped
 //! ```
 
-use super::{Poolable, RawPool, RawPoolable, WeakPool};
+use super::{GPooled, Poolable, RawPool, RawPoolable, WeakPool};
 use core::fmt;
-use std::{cmp::Eq, fmt::Debug, hash::Hash, mem::ManuallyDrop, ops::Deref, ptr};
+use std::{
+    cmp::Eq,
+    fmt::Debug,
+    hash::Hash,
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    ptr,
+};
 
 macro_rules! impl_arc {
     ($name:ident, $inner:ident, $uniq:expr, $doc:expr) => {
@@ -44,10 +51,32 @@ macro_rules! impl_arc {
                 $inner::get_mut(&mut self.inner).unwrap().1.reset()
             }
 
+            fn try_reset(&mut self) -> Result<(), crate::ResetError> {
+                $inner::get_mut(&mut self.inner).unwrap().1.try_reset()
+            }
+
             fn really_drop(self) {
                 let mut t = ManuallyDrop::new(self);
                 unsafe { ManuallyDrop::drop(&mut t.inner) }
             }
+
+            fn allocated_bytes(&self) -> usize {
+                std::mem::size_of::<(WeakPool<Self>, T)>() + self.inner.1.allocated_bytes()
+            }
+
+            fn try_reserve(&mut self, additional: usize) -> Result<(), crate::AllocError> {
+                match $inner::get_mut(&mut self.inner) {
+                    Some((_, t)) => t.try_reserve(additional),
+                    None => Ok(()),
+                }
+            }
+
+            fn weak_pool(
+                &mut self,
+                _: crate::sealed::Token,
+            ) -> Option<&mut WeakPool<Self>> {
+                $inner::get_mut(&mut self.inner).map(|(p, _)| p)
+            }
         }
 
         impl<T: Poolable> Drop for $name<T> {
@@ -114,6 +143,18 @@ macro_rules! impl_arc {
                 t
             }
 
+            /// Move the value out of `t` into an arc from `pool`.
+            ///
+            /// The arc's previous, already reset, value is swapped into
+            /// `t`, which then goes back to its own pool, so the value's
+            /// storage and the arc's allocation both stay pooled.
+            pub fn from_pooled(pool: &RawPool<Self>, mut t: GPooled<T>) -> Self {
+                let mut a = pool.take();
+                // values in the pool are guaranteed to be unique
+                mem::swap(Self::get_mut(&mut a).unwrap(), &mut *t);
+                a
+            }
+
             /// Get a mutable reference to the inner value if the Arc is unique.
             ///
             /// Returns `None` if the Arc is not unique (strong_count > 1).
@@ -0,0 +1,143 @@
+//! Request scoped recycling.
+//!
+//! Handling a request usually needs a handful of scratch buffers that all
+//! die when the request is done. A [RequestArena] takes them from the
+//! global pools, hands out plain `&mut T`s, and returns every object it
+//! took to its pool at once when it is dropped or cleared. That is the
+//! ergonomics of an arena, nothing to drop one by one, with the reuse of
+//! pooling underneath.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::arena::RequestArena;
+//! use std::fmt::Write;
+//!
+//! fn handle(arena: &RequestArena, name: &str) -> usize {
+//!     let path = arena.take::<String>();
+//!     let lens = arena.take::<Vec<usize>>();
+//!     write!(path, "/users/{name}/posts").unwrap();
+//!     lens.extend(path.split('/').filter(|p| !p.is_empty()).map(str::len));
+//!     lens.len()
+//! }
+//!
+//! let arena = RequestArena::new();
+//! assert_eq!(handle(&arena, "alice"), 3);
+//! drop(arena); // the string and the vec go back to their pools
+//! ```
+use super::{take, GPooled, Pool};
+use crate::{IsoPoolable, Poolable};
+use fxhash::FxHashMap;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    fmt,
+};
+
+// the objects of one type, in chunks that are never reallocated, so
+// references handed out stay valid while more objects are added
+struct Chunks<T: Poolable>(Vec<Vec<GPooled<T>>>);
+
+impl<T: Poolable> Chunks<T> {
+    fn push(&mut self, t: GPooled<T>) -> *mut T {
+        let cap = match self.0.last() {
+            Some(c) if c.len() < c.capacity() => None,
+            Some(c) => Some(c.capacity() * 2),
+            None => Some(8),
+        };
+        if let Some(cap) = cap {
+            self.0.push(Vec::with_capacity(cap))
+        }
+        let chunk = self.0.last_mut().unwrap();
+        chunk.push(t);
+        // point at the new object without borrowing the whole chunk, which
+        // would invalidate the references already handed out to the others
+        unsafe { &mut **chunk.as_mut_ptr().add(chunk.len() - 1) }
+    }
+}
+
+trait Held: Send {
+    fn as_any(&mut self) -> &mut dyn Any;
+    fn len(&self) -> usize;
+}
+
+impl<T: Poolable + Send + 'static> Held for Chunks<T> {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|c| c.len()).sum()
+    }
+}
+
+/// Pooled objects that are all returned together, see the
+/// [module level documentation](self).
+///
+/// The arena can be sent between threads, e.g. held across an await in a
+/// multi threaded runtime, but not shared by them. Objects always go back
+/// to the pool they came from.
+#[derive(Default)]
+pub struct RequestArena(RefCell<FxHashMap<TypeId, Box<dyn Held>>>);
+
+impl fmt::Debug for RequestArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestArena").field("len", &self.len()).finish()
+    }
+}
+
+impl RequestArena {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a `T` from the thread local global pool, see [take], and hold
+    /// it until the arena is dropped or cleared
+    #[cfg_attr(feature = "leaks", track_caller)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn take<T: IsoPoolable + Send + 'static>(&self) -> &mut T {
+        self.hold(take())
+    }
+
+    /// Take a `T` from `pool`, and hold it until the arena is dropped or
+    /// cleared
+    #[cfg_attr(feature = "leaks", track_caller)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn take_from<T: Poolable + Send + 'static>(&self, pool: &Pool<T>) -> &mut T {
+        self.hold(pool.take())
+    }
+
+    /// Hold `t`, which was taken some other way, until the arena is
+    /// dropped or cleared
+    #[allow(clippy::mut_from_ref)]
+    pub fn hold<T: Poolable + Send + 'static>(&self, t: GPooled<T>) -> &mut T {
+        let mut held = self.0.borrow_mut();
+        let chunks = held
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Chunks::<T>(Vec::new())))
+            .as_any()
+            .downcast_mut::<Chunks<T>>()
+            .unwrap();
+        // the object lives in a chunk that is never reallocated, and is only
+        // dropped by drop or clear, which need &mut self, so the reference
+        // can't outlive it. Each object is handed out exactly once.
+        unsafe { &mut *chunks.push(t) }
+    }
+
+    /// The number of objects the arena holds
+    pub fn len(&self) -> usize {
+        self.0.borrow().values().map(|h| h.len()).sum()
+    }
+
+    /// Return true if the arena holds no objects
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return every object to its pool now, so the arena can be reused for
+    /// the next request
+    pub fn clear(&mut self) {
+        self.0.get_mut().clear()
+    }
+}
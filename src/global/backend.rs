@@ -0,0 +1,162 @@
+//! Pluggable storage for global pools.
+//!
+//! By default a [RawPool](super::RawPool) keeps its objects in a lock-free
+//! crossbeam `ArrayQueue`, which is a good fit for most contention profiles.
+//! A pool can be built on any other bounded container that implements
+//! [PoolBackend] with [RawPool::with_backend](super::RawPool::with_backend),
+//! e.g. a treiber stack, a sharded ring, or [MutexStack], which hands back
+//! the most recently returned, and so most likely cache hot, object first.
+//!
+//! Pools with the default backend pay one well predicted branch per push
+//! and pop for this, a pool with a custom backend pays a dynamic call.
+//!
+//! The default queue hands objects back in the order they were returned,
+//! so a take gets the coldest object. [RawPool::new_lifo] builds a pool on
+//! a [MutexStack] instead, and [RawPool::order] reports which [TakeOrder]
+//! a pool's backend follows.
+//!
+//! [RawPool::new_lifo]: super::RawPool::new_lifo
+//! [RawPool::order]: super::RawPool::order
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{backend::MutexStack, GPooled, PoolControl, RawPool};
+//!
+//! let pool: RawPool<GPooled<Vec<u8>>> = RawPool::with_backend(MutexStack::new(64), 4096);
+//! let mut v = pool.take();
+//! v.extend_from_slice(b"hello");
+//! drop(v);
+//! assert_eq!(pool.len(), 1);
+//! ```
+use crossbeam_queue::ArrayQueue;
+use std::{fmt, sync::Mutex};
+
+/// The order in which a backend hands out its objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TakeOrder {
+    /// The least recently returned object first
+    Fifo,
+    /// The most recently returned, and so most likely cache hot, object first
+    Lifo,
+    /// No particular order
+    Unspecified,
+}
+
+/// Bounded storage for the objects in a pool.
+///
+/// Implementations must be safe to use from many threads at once. `push`
+/// must refuse objects, by giving them back, once `capacity` objects are
+/// stored.
+pub trait PoolBackend<T>: Send + Sync {
+    /// Store `t`, or give it back if the backend is full
+    fn push(&self, t: T) -> Result<(), T>;
+
+    /// Remove and return a stored object, if there are any
+    fn pop(&self) -> Option<T>;
+
+    /// The number of stored objects
+    fn len(&self) -> usize;
+
+    /// Return true if no objects are stored
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of objects the backend will store
+    fn capacity(&self) -> usize;
+
+    /// The order `pop` returns objects in. The default is
+    /// [Unspecified](TakeOrder::Unspecified).
+    fn order(&self) -> TakeOrder {
+        TakeOrder::Unspecified
+    }
+
+    /// Create a new empty backend with the same capacity and order, for
+    /// [RawPool::clone_config](super::RawPool::clone_config). The default
+    /// returns `None`, and the new pool uses the default backend instead.
+    fn clone_empty(&self) -> Option<Box<dyn PoolBackend<T>>> {
+        None
+    }
+}
+
+impl<T: Send + 'static> PoolBackend<T> for ArrayQueue<T> {
+    fn push(&self, t: T) -> Result<(), T> {
+        ArrayQueue::push(self, t)
+    }
+
+    fn pop(&self) -> Option<T> {
+        ArrayQueue::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        ArrayQueue::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        ArrayQueue::capacity(self)
+    }
+
+    fn order(&self) -> TakeOrder {
+        TakeOrder::Fifo
+    }
+
+    fn clone_empty(&self) -> Option<Box<dyn PoolBackend<T>>> {
+        Some(Box::new(ArrayQueue::new(self.capacity())))
+    }
+}
+
+/// A mutex protected LIFO stack.
+///
+/// Under low contention this is about as fast as the default queue, and
+/// reusing the most recently returned object first keeps the working set
+/// small and warm.
+pub struct MutexStack<T> {
+    cap: usize,
+    inner: Mutex<Vec<T>>,
+}
+
+impl<T> fmt::Debug for MutexStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutexStack").field("cap", &self.cap).finish_non_exhaustive()
+    }
+}
+
+impl<T> MutexStack<T> {
+    /// Create an empty stack that will store up to `cap` objects
+    pub fn new(cap: usize) -> Self {
+        Self { cap, inner: Mutex::new(Vec::new()) }
+    }
+}
+
+impl<T: Send + 'static> PoolBackend<T> for MutexStack<T> {
+    fn push(&self, t: T) -> Result<(), T> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.len() >= self.cap {
+            Err(t)
+        } else {
+            inner.push(t);
+            Ok(())
+        }
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.inner.lock().unwrap().pop()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    fn order(&self) -> TakeOrder {
+        TakeOrder::Lifo
+    }
+
+    fn clone_empty(&self) -> Option<Box<dyn PoolBackend<T>>> {
+        Some(Box::new(MutexStack::new(self.cap)))
+    }
+}
@@ -0,0 +1,195 @@
+//! Batching channel senders with pooled batches.
+//!
+//! Sending items across a channel one at a time costs a wakeup per item, so
+//! producers usually gather them into batches, as in the producer consumer
+//! example in the crate documentation. A [BatchSender] packages that
+//! pattern. Items accumulate in a `Vec` taken from a pool, and the batch is
+//! sent when it is full or when its oldest item has waited long enough. The
+//! receiver drops batches when it's done with them, which returns them to
+//! the pool.
+//!
+//! This needs the `tokio` feature, and a tokio runtime, since the deadline
+//! is enforced by a background task.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{batch::BatchSender, Pool};
+//! use std::time::Duration;
+//! use tokio::sync::mpsc;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let batches: Pool<Vec<u64>> = Pool::new(64, 1024);
+//! let (tx, mut rx) = mpsc::channel(8);
+//! let tx = BatchSender::new(tx, batches, 100, Duration::from_millis(10));
+//! for i in 0..250 {
+//!     tx.send(i).await.unwrap();
+//! }
+//! // two full batches are sent right away, the rest after 10ms
+//! let mut n = 0;
+//! while n < 250 {
+//!     n += rx.recv().await.unwrap().len();
+//! }
+//! # }
+//! ```
+use super::{GPooled, Pool};
+use std::{
+    fmt, mem,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, Mutex},
+    task,
+    time::{self, Instant},
+};
+
+/// A pooled batch of items
+pub type Batch<T> = GPooled<Vec<T>>;
+
+/// The error returned when the receiver is gone, holding the batch that
+/// couldn't be delivered
+pub type SendError<T> = mpsc::error::SendError<Batch<T>>;
+
+struct Pending<T: Send + 'static> {
+    batch: Batch<T>,
+    // when the first item of the batch was added
+    since: Instant,
+}
+
+struct Inner<T: Send + 'static> {
+    tx: mpsc::Sender<Batch<T>>,
+    pool: Pool<Vec<T>>,
+    max_len: usize,
+    max_delay: Duration,
+    pending: Mutex<Pending<T>>,
+}
+
+impl<T: Send + 'static> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let batch =
+            mem::replace(&mut self.pending.get_mut().batch, GPooled::orphan_default());
+        if !batch.is_empty() {
+            let _ = self.tx.try_send(batch);
+        }
+    }
+}
+
+impl<T: Send + 'static> Inner<T> {
+    // send the pending batch, replacing it with a new one from the pool
+    async fn flush(&self, due: Option<Instant>) -> Result<(), SendError<T>> {
+        let mut pending = self.pending.lock().await;
+        if pending.batch.is_empty() {
+            return Ok(());
+        }
+        if let Some(now) = due
+            && now < pending.since + self.max_delay
+        {
+            return Ok(());
+        }
+        let batch = mem::replace(&mut pending.batch, self.pool.take());
+        // the lock is held while sending so batches are delivered in order
+        self.tx.send(batch).await
+    }
+}
+
+/// A sender that gathers items into pooled batches, see the
+/// [module level documentation](self).
+///
+/// Cloned senders add to the same batch.
+pub struct BatchSender<T: Send + 'static>(Arc<Inner<T>>);
+
+impl<T: Send + 'static> Clone for BatchSender<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Send + 'static> fmt::Debug for BatchSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchSender")
+            .field("max_len", &self.0.max_len)
+            .field("max_delay", &self.0.max_delay)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + 'static> BatchSender<T> {
+    /// Send batches of up to `max_len` items, taken from `pool`, to `tx`.
+    ///
+    /// A batch is sent as soon as it is full, or `max_delay` after its first
+    /// item was added, whichever comes first.
+    ///
+    /// # Panics
+    ///
+    /// If called outside a tokio runtime, or if `max_len` is 0.
+    pub fn new(
+        tx: mpsc::Sender<Batch<T>>,
+        pool: Pool<Vec<T>>,
+        max_len: usize,
+        max_delay: Duration,
+    ) -> Self {
+        assert!(max_len > 0, "a batch must hold at least one item");
+        let batch = pool.take();
+        let inner = Arc::new(Inner {
+            tx,
+            pool,
+            max_len,
+            max_delay,
+            pending: Mutex::new(Pending { batch, since: Instant::now() }),
+        });
+        task::spawn(flush_on_deadline(Arc::downgrade(&inner)));
+        Self(inner)
+    }
+
+    /// Add `t` to the current batch, sending the batch if it is full.
+    ///
+    /// Waits if the channel is full. Returns an error holding the batch if
+    /// the receiver is gone.
+    pub async fn send(&self, t: T) -> Result<(), SendError<T>> {
+        let mut pending = self.0.pending.lock().await;
+        if pending.batch.is_empty() {
+            pending.since = Instant::now()
+        }
+        pending.batch.push(t);
+        if pending.batch.len() >= self.0.max_len {
+            let batch = mem::replace(&mut pending.batch, self.0.pool.take());
+            self.0.tx.send(batch).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send the current batch now, if it isn't empty.
+    ///
+    /// When the last sender is dropped the current batch is only sent if
+    /// the channel has room, so flush first to be sure it's delivered.
+    pub async fn flush(&self) -> Result<(), SendError<T>> {
+        self.0.flush(None).await
+    }
+
+    /// Return true if the receiver is gone
+    pub fn is_closed(&self) -> bool {
+        self.0.tx.is_closed()
+    }
+}
+
+async fn flush_on_deadline<T: Send + 'static>(inner: Weak<Inner<T>>) {
+    loop {
+        let deadline = {
+            let Some(inner) = inner.upgrade() else { break };
+            let pending = inner.pending.lock().await;
+            if pending.batch.is_empty() {
+                Instant::now() + inner.max_delay
+            } else {
+                pending.since + inner.max_delay
+            }
+        };
+        time::sleep_until(deadline).await;
+        let Some(inner) = inner.upgrade() else { break };
+        if inner.flush(Some(Instant::now())).await.is_err() {
+            break;
+        }
+    }
+}
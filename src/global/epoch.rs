@@ -0,0 +1,89 @@
+//! Invalidating everything in the global pools at once.
+//!
+//! Sometimes every pooled object becomes unfit for reuse at once, e.g.
+//! buffers that held data encrypted with a key that was just rotated, or
+//! structures shaped by a schema that just changed. Clearing every pool of
+//! every type on every thread is impractical, so instead the global pools
+//! stamp each object with the epoch it was created in, and [bump] starts a
+//! new epoch. Objects from earlier epochs are really dropped instead of
+//! reused, the pooled ones by the next take that finds them, and the
+//! outstanding ones when they are returned.
+//!
+//! This only applies to [GPooled](super::GPooled) objects. Local pools can
+//! be emptied with [local::clear](crate::local::clear).
+//!
+//! The stamp costs a word per object, so it is enabled by the `epoch`
+//! feature. Without it the epoch is still counted, but objects aren't
+//! stamped, and a bump doesn't drop anything.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "epoch")] {
+//! use poolshark::global::{epoch, Pool, PoolControl};
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+//! let mut v = pool.take();
+//! v.extend_from_slice(b"secret");
+//! let mut outstanding = pool.take();
+//! outstanding.extend_from_slice(b"also secret");
+//! drop(v);
+//! assert_eq!(pool.len(), 1);
+//! epoch::bump();
+//! // the pooled vec is dropped, this is a new one
+//! assert_eq!(pool.take().capacity(), 0);
+//! drop(outstanding); // also dropped
+//! assert_eq!(pool.len(), 0);
+//! # }
+//! ```
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) use imp::Stamp;
+
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Return the current epoch
+pub fn current() -> u64 {
+    EPOCH.load(Ordering::Relaxed)
+}
+
+/// Start a new epoch, so every object created before now is really dropped
+/// instead of reused. Returns the new epoch.
+pub fn bump() -> u64 {
+    EPOCH.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+#[cfg(feature = "epoch")]
+mod imp {
+    /// The epoch an object was created in
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Stamp(u64);
+
+    impl Stamp {
+        pub(crate) fn new() -> Self {
+            Self(super::current())
+        }
+
+        pub(crate) fn get(&self) -> Option<u64> {
+            Some(self.0)
+        }
+    }
+}
+
+#[cfg(not(feature = "epoch"))]
+mod imp {
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Stamp;
+
+    impl Stamp {
+        #[inline(always)]
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn get(&self) -> Option<u64> {
+            None
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Notification of objects a pool throws away.
+//!
+//! Polling [stats](super::PoolControl::stats) tells a maintenance task how
+//! many objects were evicted, but not when or why. A pool can instead call a
+//! hook for each eviction, set with
+//! [set_eviction_hook](super::RawPool::set_eviction_hook), and with the
+//! `tokio` feature it can publish them to any number of async subscribers
+//! with [evictions](super::RawPool::evictions), so a task can react, e.g. by
+//! raising the limits of a pool that keeps throwing away full size objects.
+//!
+//! Pools without a hook or a subscriber pay one relaxed atomic load per
+//! eviction.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{evict::{EvictReason, Eviction}, Pool};
+//! use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+//! let oversized = Arc::new(AtomicUsize::new(0));
+//! let n = Arc::clone(&oversized);
+//! pool.set_eviction_hook(Some(move |e: &Eviction| {
+//!     if e.reason == EvictReason::Oversized {
+//!         n.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }));
+//! let mut v = pool.take();
+//! v.reserve(4096);
+//! drop(v);
+//! assert_eq!(oversized.load(Ordering::Relaxed), 1);
+//! ```
+#[cfg(feature = "tokio")]
+use std::sync::OnceLock;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+#[cfg(feature = "tokio")]
+use tokio::sync::broadcast;
+
+/// Why an object was evicted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvictReason {
+    /// It had no capacity, so there was nothing worth keeping
+    Empty,
+    /// Its capacity was more than the pool's `max_elt_capacity`
+    Oversized,
+    /// Its capacity was less than the pool's
+    /// [min_elt_capacity](super::RawPool::set_min_elt_capacity)
+    Undersized,
+    /// The pool was full
+    Full,
+    /// Keeping it would have exceeded the pool's byte budget
+    OverBudget,
+    /// The pool is frozen
+    Frozen,
+    /// It was created before the last [epoch](super::epoch) bump
+    Stale,
+    /// Its [try_reset](crate::Poolable::try_reset) failed
+    ResetFailed,
+    /// It was thrown away by [prune](super::RawPool::prune)
+    Pruned,
+    /// It was thrown away by [clear](super::PoolControl::clear)
+    Cleared,
+}
+
+/// An object a pool threw away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eviction {
+    /// The pooled type
+    pub type_name: &'static str,
+    /// The capacity of the object
+    pub capacity: usize,
+    /// Why it was evicted
+    pub reason: EvictReason,
+}
+
+pub(crate) type Hook = Arc<dyn Fn(&Eviction) + Send + Sync>;
+
+/// The hook and subscribers of one pool
+pub(crate) struct Notifier {
+    active: AtomicBool,
+    hook: RwLock<Option<Hook>>,
+    #[cfg(feature = "tokio")]
+    tx: OnceLock<broadcast::Sender<Eviction>>,
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier").field("active", &self.active).finish_non_exhaustive()
+    }
+}
+
+impl Notifier {
+    pub(crate) fn new() -> Self {
+        Self {
+            active: AtomicBool::new(false),
+            hook: RwLock::new(None),
+            #[cfg(feature = "tokio")]
+            tx: OnceLock::new(),
+        }
+    }
+
+    fn update_active(&self, hook: bool) {
+        #[cfg(feature = "tokio")]
+        let hook = hook || self.tx.get().is_some();
+        self.active.store(hook, Ordering::Relaxed)
+    }
+
+    pub(crate) fn hook(&self) -> Option<Hook> {
+        self.hook.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_hook(&self, hook: Option<Hook>) {
+        let mut h = self.hook.write().unwrap();
+        *h = hook;
+        self.update_active(h.is_some())
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn subscribe(&self) -> EvictionStream {
+        let rx = self.tx.get_or_init(|| broadcast::channel(1024).0).subscribe();
+        self.update_active(true);
+        EvictionStream { rx, missed: 0 }
+    }
+
+    /// Tell the hook and subscribers, if any, about an eviction
+    pub(crate) fn notify<F: FnOnce() -> Eviction>(&self, f: F) {
+        if !self.active.load(Ordering::Relaxed) {
+            return;
+        }
+        let ev = f();
+        // don't hold the lock while calling the hook, it may set a new one
+        let hook = self.hook.read().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(&ev)
+        }
+        #[cfg(feature = "tokio")]
+        if let Some(tx) = self.tx.get() {
+            // no receivers isn't an error
+            let _ = tx.send(ev);
+        }
+    }
+}
+
+/// A subscription to a pool's evictions, see
+/// [evictions](super::RawPool::evictions)
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct EvictionStream {
+    rx: broadcast::Receiver<Eviction>,
+    missed: u64,
+}
+
+#[cfg(feature = "tokio")]
+impl EvictionStream {
+    /// Wait for the next eviction, or return `None` once the pool has been
+    /// dropped.
+    ///
+    /// Subscribers that fall more than 1024 evictions behind skip the
+    /// oldest ones, see [missed](Self::missed).
+    pub async fn recv(&mut self) -> Option<Eviction> {
+        loop {
+            match self.rx.recv().await {
+                Ok(ev) => break Some(ev),
+                Err(broadcast::error::RecvError::Lagged(n)) => self.missed += n,
+                Err(broadcast::error::RecvError::Closed) => break None,
+            }
+        }
+    }
+
+    /// The number of evictions skipped because this subscriber fell behind
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
@@ -0,0 +1,99 @@
+//! Pooling objects that are `Send` but not `Sync`.
+//!
+//! Global pools only move objects between threads, they never share them,
+//! so a [Pool] of a type with, e.g., `Cell` based counters works as it is.
+//! The object it hands out, a [GPooled], is `Sync` only if the object is,
+//! though, which keeps it out of anything that must be `Sync`, such as a
+//! struct shared through an `Arc`, or a future that is held by reference
+//! across threads.
+//!
+//! A [SendPooled] wraps a [GPooled] and only gives access to the object
+//! through `&mut self`, never through a shared reference, so it is `Sync`
+//! whenever the object is `Send`, like `std::sync::Exclusive`.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::{global::{exclusive::SendPooled, Pool}, Poolable};
+//! use std::{cell::Cell, sync::LazyLock};
+//!
+//! #[derive(Default)]
+//! struct Scratch {
+//!     hits: Cell<u64>,
+//!     buf: Vec<u8>,
+//! }
+//!
+//! impl Poolable for Scratch {
+//!     fn empty() -> Self {
+//!         Self::default()
+//!     }
+//!     fn reset(&mut self) {
+//!         self.hits.set(0);
+//!         self.buf.clear()
+//!     }
+//!     fn capacity(&self) -> usize {
+//!         self.buf.capacity()
+//!     }
+//! }
+//!
+//! static SCRATCH: LazyLock<Pool<Scratch>> = LazyLock::new(|| Pool::new(64, 4096));
+//!
+//! fn assert_sync<T: Sync>(_: &T) {}
+//!
+//! let mut s = SendPooled::take_from(&SCRATCH);
+//! assert_sync(&s);
+//! s.get_mut().buf.extend_from_slice(b"hello");
+//! std::thread::spawn(move || drop(s)).join().unwrap();
+//! ```
+use super::{GPooled, Pool};
+use crate::Poolable;
+use std::fmt;
+
+/// A [GPooled] that is `Sync` if `T` is `Send`, see the
+/// [module level documentation](self).
+pub struct SendPooled<T: Poolable>(GPooled<T>);
+
+// no method of SendPooled gives access to the object through &self, so a
+// shared reference can't be used to touch it from more than one thread
+unsafe impl<T: Poolable + Send> Sync for SendPooled<T> {}
+
+impl<T: Poolable> fmt::Debug for SendPooled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendPooled").finish_non_exhaustive()
+    }
+}
+
+impl<T: Poolable> From<GPooled<T>> for SendPooled<T> {
+    fn from(t: GPooled<T>) -> Self {
+        Self(t)
+    }
+}
+
+impl<T: Poolable> SendPooled<T> {
+    /// Wrap `t`
+    pub fn new(t: GPooled<T>) -> Self {
+        Self(t)
+    }
+
+    /// Take an object from `pool`, see [RawPool::take](super::RawPool::take)
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take_from(pool: &Pool<T>) -> Self {
+        Self(pool.take())
+    }
+
+    /// Take an object from `pool`, or `None` if it is empty
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn try_take_from(pool: &Pool<T>) -> Option<Self> {
+        pool.try_take().map(Self)
+    }
+
+    /// Get exclusive access to the object
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Unwrap the pooled object
+    pub fn into_inner(self) -> GPooled<T> {
+        self.0
+    }
+}
@@ -0,0 +1,218 @@
+//! Inspect the registered pools of a running program.
+//!
+//! An application calls [serve] once at startup to publish the stats of
+//! every pool in the [registry](super::registry) on a unix socket, or writes
+//! a dump file with [write_dump]. The `inspect` example is the other half,
+//!
+//! ```text
+//! cargo run --example inspect -- /run/myapp/pools.sock
+//! ```
+//!
+//! connects to the socket (or reads the dump file) and prints a utilization
+//! table. The wire format is plain text, one tab separated line per pool,
+//! so `nc -U` works too.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use poolshark::global::{inspect, registry, Pool};
+//!
+//! let strings: Pool<String> = Pool::new(1024, 4096);
+//! registry::register("strings", &strings);
+//! inspect::serve("/tmp/myapp-pools.sock").unwrap();
+//! // later, from another process
+//! let pools = inspect::connect("/tmp/myapp-pools.sock").unwrap();
+//! print!("{}", inspect::table(&pools));
+//! ```
+use super::registry;
+use std::{
+    fmt::{self, Write as _},
+    io::{self, BufRead, BufReader, Write},
+    str::FromStr,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    thread::{self, JoinHandle},
+};
+
+/// A summary of one registered pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolInfo {
+    /// The name the pool was registered under
+    pub name: String,
+    /// The pooled type
+    pub type_name: String,
+    /// The number of objects in the pool
+    pub len: usize,
+    /// The maximum number of objects the pool will retain
+    pub capacity: usize,
+    /// The maximum capacity of retained objects
+    pub max_elt_capacity: usize,
+    /// Estimated heap memory owned by the pooled objects
+    pub retained_bytes: usize,
+    /// Objects taken and not yet returned, only tracked with the `stats`
+    /// feature
+    pub outstanding: usize,
+    /// The high water mark of `outstanding`
+    pub max_outstanding: usize,
+}
+
+impl fmt::Display for PoolInfo {
+    /// Write the wire format, a tab separated line without the newline
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.name,
+            self.type_name,
+            self.len,
+            self.capacity,
+            self.max_elt_capacity,
+            self.retained_bytes,
+            self.outstanding,
+            self.max_outstanding
+        )
+    }
+}
+
+impl FromStr for PoolInfo {
+    type Err = io::Error;
+
+    /// Parse one line of the wire format
+    fn from_str(s: &str) -> io::Result<Self> {
+        let err =
+            || io::Error::new(io::ErrorKind::InvalidData, format!("bad pool line {s:?}"));
+        let mut parts = s.split('\t');
+        let mut text = || parts.next().map(String::from).ok_or_else(err);
+        let name = text()?;
+        let type_name = text()?;
+        let mut num = || -> io::Result<usize> {
+            parts.next().and_then(|n| n.parse().ok()).ok_or_else(err)
+        };
+        let info = PoolInfo {
+            name,
+            type_name,
+            len: num()?,
+            capacity: num()?,
+            max_elt_capacity: num()?,
+            retained_bytes: num()?,
+            outstanding: num()?,
+            max_outstanding: num()?,
+        };
+        match parts.next() {
+            None => Ok(info),
+            Some(_) => Err(err()),
+        }
+    }
+}
+
+/// Return a summary of every live registered pool
+pub fn snapshot() -> Vec<PoolInfo> {
+    registry::pools()
+        .into_iter()
+        .map(|(name, pool)| {
+            let st = pool.stats();
+            PoolInfo {
+                name: name.into(),
+                type_name: pool.type_name().into(),
+                len: st.len,
+                capacity: st.capacity,
+                max_elt_capacity: st.max_elt_capacity,
+                retained_bytes: st.retained_bytes,
+                outstanding: st.outstanding,
+                max_outstanding: st.max_outstanding,
+            }
+        })
+        .collect()
+}
+
+/// Write a [snapshot] of the registered pools to `w` in the wire format
+pub fn write_dump<W: Write>(mut w: W) -> io::Result<()> {
+    for info in snapshot() {
+        writeln!(w, "{info}")?
+    }
+    w.flush()
+}
+
+/// Read a dump written by [write_dump]
+pub fn read_dump<R: BufRead>(r: R) -> io::Result<Vec<PoolInfo>> {
+    let mut res = Vec::new();
+    for line in r.lines() {
+        let line = line?;
+        if !line.is_empty() {
+            res.push(line.parse()?)
+        }
+    }
+    Ok(res)
+}
+
+/// Serve dumps of the registered pools on the unix socket at `path`.
+///
+/// A background thread accepts connections and writes one dump to each of
+/// them. An existing file at `path` is removed first.
+#[cfg(unix)]
+pub fn serve<P: AsRef<Path>>(path: P) -> io::Result<JoinHandle<()>> {
+    let path = path.as_ref();
+    match std::fs::remove_file(path) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(path)?;
+    thread::Builder::new().name("poolshark-inspect".into()).spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = write_dump(stream);
+        }
+    })
+}
+
+/// Connect to a program running [serve] and read its dump
+#[cfg(unix)]
+pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<Vec<PoolInfo>> {
+    read_dump(BufReader::new(UnixStream::connect(path)?))
+}
+
+fn human(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut n = bytes as f64;
+    let mut i = 0;
+    while n >= 1024. && i < UNITS.len() - 1 {
+        n /= 1024.;
+        i += 1
+    }
+    if i == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{n:.1} {}", UNITS[i])
+    }
+}
+
+/// Format `pools` as a human readable table, largest retained first
+pub fn table(pools: &[PoolInfo]) -> String {
+    let mut pools: Vec<&PoolInfo> = pools.iter().collect();
+    pools.sort_by_key(|p| std::cmp::Reverse(p.retained_bytes));
+    let width = pools.iter().map(|p| p.name.len()).max().unwrap_or(0).max(4);
+    let mut res = String::new();
+    let _ = writeln!(
+        res,
+        "{:width$}  {:>15}  {:>5}  {:>10}  {:>11}  type",
+        "name", "len/capacity", "used", "retained", "outstanding"
+    );
+    for p in pools {
+        let used =
+            if p.capacity == 0 { 0. } else { p.len as f64 * 100. / p.capacity as f64 };
+        let _ = writeln!(
+            res,
+            "{:width$}  {:>15}  {:>4.0}%  {:>10}  {:>11}  {}",
+            p.name,
+            format!("{}/{}", p.len, p.capacity),
+            used,
+            human(p.retained_bytes),
+            format!("{}/{}", p.outstanding, p.max_outstanding),
+            p.type_name
+        );
+    }
+    res
+}
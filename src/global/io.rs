@@ -0,0 +1,99 @@
+//! `std::io` adapters for globally pooled buffers.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::Pool;
+//! use std::io::{Read, Seek, SeekFrom};
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(64, 64 * 1024);
+//! let mut buf = pool.take();
+//! buf.extend_from_slice(b"hello world");
+//! let mut r = buf.reader();
+//! r.seek(SeekFrom::Start(6)).unwrap();
+//! let mut s = String::new();
+//! r.read_to_string(&mut s).unwrap();
+//! assert_eq!(s, "world");
+//! drop(r); // the buffer goes back to the pool here
+//! assert!(pool.try_take().is_some());
+//! ```
+use super::GPooled;
+use std::io::{self, BufRead, Cursor, Read, Seek, SeekFrom};
+
+#[derive(Debug)]
+struct Bytes(GPooled<Vec<u8>>);
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// A [Read] + [Seek] reader that consumes a pooled buffer.
+///
+/// Created by [GPooled::reader]. The buffer is returned to its pool when the
+/// reader is dropped, so downstream decoders can read pooled data without
+/// copying it into a `Cursor` they own.
+#[derive(Debug)]
+pub struct Reader(Cursor<Bytes>);
+
+impl Reader {
+    /// The current position of the reader
+    pub fn position(&self) -> u64 {
+        self.0.position()
+    }
+
+    /// Set the position of the reader
+    pub fn set_position(&mut self, pos: u64) {
+        self.0.set_position(pos)
+    }
+
+    /// Return the whole underlying buffer, regardless of the position
+    pub fn get_ref(&self) -> &[u8] {
+        &self.0.get_ref().0
+    }
+
+    /// Unwrap the underlying buffer
+    pub fn into_inner(self) -> GPooled<Vec<u8>> {
+        self.0.into_inner().0
+    }
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(buf)
+    }
+}
+
+impl BufRead for Reader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl Seek for Reader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        self.0.stream_position()
+    }
+}
+
+impl GPooled<Vec<u8>> {
+    /// Consume the buffer, returning a [Read] + [Seek] reader over it.
+    ///
+    /// The buffer is returned to its pool when the reader is dropped.
+    pub fn reader(self) -> Reader {
+        Reader(Cursor::new(Bytes(self)))
+    }
+}
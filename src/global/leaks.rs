@@ -0,0 +1,167 @@
+//! Finding the code that holds on to pooled objects.
+//!
+//! A pool that keeps missing even though its objects are being returned
+//! usually means some code is hoarding them, e.g. a handler that stashes
+//! buffers in a long lived map. With the `leaks` feature every take of a
+//! [GPooled](super::GPooled) records its caller, and [outstanding] reports
+//! how many objects taken at each call site have not been dropped yet.
+//!
+//! Recording a take locks a global table, so this is meant for debug
+//! builds. Without the feature nothing is recorded and [outstanding] is
+//! always empty.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{self, leaks};
+//!
+//! let hoard: Vec<_> = (0..10).map(|_| global::take::<Vec<u8>>()).collect();
+//! for site in leaks::outstanding() {
+//!     // e.g. src/main.rs:4:41 alloc::vec::Vec<u8> 10
+//!     println!("{site}")
+//! }
+//! # #[cfg(feature = "leaks")]
+//! # assert!(leaks::outstanding().iter().any(|s| s.count == 10));
+//! # drop(hoard)
+//! ```
+use std::{fmt, panic::Location};
+
+/// The objects taken at one call site that are still outstanding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outstanding {
+    /// The name of the pooled type
+    pub type_name: &'static str,
+    /// Where the objects were taken
+    pub location: &'static Location<'static>,
+    /// How many of them have not been dropped
+    pub count: usize,
+}
+
+impl fmt::Display for Outstanding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.location, self.type_name, self.count)
+    }
+}
+
+/// Return the call sites with outstanding objects, the site with the most
+/// first. Always empty without the `leaks` feature.
+pub fn outstanding() -> Vec<Outstanding> {
+    imp::outstanding()
+}
+
+#[cfg(feature = "leaks")]
+mod imp {
+    use super::Outstanding;
+    use fxhash::FxHashMap;
+    use std::{
+        panic::Location,
+        sync::{LazyLock, Mutex},
+    };
+
+    type Key = (&'static Location<'static>, &'static str);
+
+    static SITES: LazyLock<Mutex<FxHashMap<Key, usize>>> =
+        LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+    /// The call site that took an object, counted while the object is out
+    #[derive(Debug)]
+    pub(crate) struct Site(Option<Key>);
+
+    impl Site {
+        pub(crate) fn none() -> Self {
+            Self(None)
+        }
+
+        fn acquire(key: Key) -> Self {
+            *SITES.lock().unwrap().entry(key).or_default() += 1;
+            Self(Some(key))
+        }
+
+        /// Record that the object was taken at `location`, replacing where
+        /// it was previously taken
+        pub(crate) fn set<T>(&mut self, location: &'static Location<'static>) {
+            *self = Self::acquire((location, std::any::type_name::<T>()))
+        }
+
+        /// Move the site out, leaving none
+        pub(crate) fn take(&mut self) -> Self {
+            Self(self.0.take())
+        }
+
+        /// Record that the object is no longer outstanding
+        pub(crate) fn clear(&mut self) {
+            if let Some(key) = self.0.take() {
+                let mut sites = SITES.lock().unwrap();
+                if let Some(n) = sites.get_mut(&key) {
+                    *n -= 1;
+                    if *n == 0 {
+                        sites.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Clone for Site {
+        fn clone(&self) -> Self {
+            match self.0 {
+                None => Self(None),
+                Some(key) => Self::acquire(key),
+            }
+        }
+    }
+
+    impl Drop for Site {
+        fn drop(&mut self) {
+            self.clear()
+        }
+    }
+
+    pub(super) fn outstanding() -> Vec<Outstanding> {
+        let mut res: Vec<Outstanding> = SITES
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((location, type_name), count)| Outstanding {
+                type_name,
+                location,
+                count: *count,
+            })
+            .collect();
+        res.sort_by_key(|s| std::cmp::Reverse(s.count));
+        res
+    }
+}
+
+#[cfg(not(feature = "leaks"))]
+mod imp {
+    use super::Outstanding;
+    use std::panic::Location;
+
+    #[derive(Debug, Clone)]
+    pub(crate) struct Site;
+
+    impl Site {
+        #[inline(always)]
+        pub(crate) fn none() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn set<T>(&mut self, _location: &'static Location<'static>) {}
+
+        #[inline(always)]
+        pub(crate) fn take(&mut self) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn clear(&mut self) {}
+    }
+
+    pub(super) fn outstanding() -> Vec<Outstanding> {
+        Vec::new()
+    }
+}
+
+pub(crate) use imp::Site;
@@ -39,8 +39,18 @@
 //! // Take from thread-local global pool
 //! let map = global::take::<HashMap<String, i32>>();
 //! ```
-use crate::{Discriminant, IsoPoolable, Opaque, Poolable, RawPoolable};
-use crossbeam_queue::ArrayQueue;
+use crate::{
+    discriminant,
+    local::LPooled,
+    pool_discriminant,
+    pooled::Frozen,
+    reserve_pooled, shadow,
+    size_of::SizeOf,
+    stats::{Age, Path, PoolCounters, PoolStats, Timer},
+    testing::faults::{injected, Fault},
+    AllocError, Discriminant, Hooks, IsoPoolable, Opaque, PoolHooks, Poolable,
+    RawPoolable, ResetError,
+};
 use fxhash::FxHashMap;
 #[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -53,13 +63,48 @@ use std::{
     default::Default,
     fmt::{self, Debug, Display},
     hash::{Hash, Hasher},
+    marker::PhantomData,
     mem::{self, ManuallyDrop},
     ops::{Deref, DerefMut},
+    panic::Location,
+    path::{self, PathBuf},
+    pin::Pin,
     ptr,
-    sync::{Arc, LazyLock, Mutex, Weak},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering as AtomicOrdering},
+        Arc, LazyLock, Mutex, OnceLock, Weak,
+    },
 };
 
 pub mod arc;
+pub mod arena;
+pub mod backend;
+#[cfg(feature = "tokio")]
+pub mod batch;
+pub mod epoch;
+pub mod evict;
+pub mod exclusive;
+pub mod inspect;
+pub mod io;
+pub mod leaks;
+mod queue;
+pub mod registry;
+pub mod report;
+pub mod ring;
+pub mod set;
+pub mod sharded;
+pub mod sizes;
+pub mod string;
+pub mod trace;
+pub mod view;
+
+use backend::PoolBackend;
+use epoch::Stamp;
+use evict::{EvictReason, Eviction, Notifier};
+use leaks::Site;
+use queue::Queue;
+use report::{RetentionReport, Tracker};
+use trace::ObjectSpan;
 
 thread_local! {
     static POOLS: RefCell<FxHashMap<Discriminant, Opaque>> =
@@ -68,9 +113,20 @@ thread_local! {
 
 const DEFAULT_SIZES: (usize, usize) = (1024, 1024);
 
+// how many pooled objects take_with_capacity looks at
+const CAPACITY_SCAN: usize = 4;
+
 static SIZES: LazyLock<Mutex<FxHashMap<Discriminant, (usize, usize)>>> =
     LazyLock::new(|| Mutex::new(FxHashMap::default()));
 
+static INITIAL_CAPACITY: LazyLock<Mutex<FxHashMap<Discriminant, usize>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+type Drain = Arc<dyn Fn(*const ()) + Send + Sync>;
+
+static DRAINS: LazyLock<Mutex<FxHashMap<Discriminant, Drain>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
 // This is safe because:
 // 1. Containers are reset before being returned to pools, so they contain no values
 // 2. We only reuse pools for types with identical memory layouts (same size/alignment via Discriminant)
@@ -86,9 +142,10 @@ where
     // pool destructor. This is why we must use try_with on the thread local
     let res = POOLS.try_with(|pools| match pools.try_borrow_mut() {
         Err(_) => (f.take().unwrap())(None),
-        Ok(mut pools) => match T::DISCRIMINANT {
+        Ok(mut pools) => match pool_discriminant::<T>() {
             Some(d) => {
                 let pool = pools.entry(d).or_insert_with(|| {
+                    sizes::seen(d, std::any::type_name::<T>());
                     let (size, cap) = sizes.unwrap_or_else(|| {
                         SIZES
                             .lock()
@@ -98,12 +155,25 @@ where
                             .unwrap_or(DEFAULT_SIZES)
                     });
                     let b = Box::new(Pool::<T>::new(size, cap));
+                    if let Some(n) = INITIAL_CAPACITY.lock().unwrap().get(&d) {
+                        b.set_initial_capacity(*n)
+                    }
                     let t = Box::into_raw(b) as *mut ();
-                    let drop = Some(Box::new(|t: *mut ()| unsafe {
+                    let drop = Box::new(move |t: *mut ()| unsafe {
+                        #[cfg(feature = "stats")]
+                        sizes::observed(
+                            d,
+                            (*(t as *const Pool<T>)).stats().max_outstanding,
+                        );
+                        let drain = DRAINS.lock().unwrap().get(&d).cloned();
+                        if let Some(drain) = drain {
+                            drain(t as *const ())
+                        }
                         drop(Box::from_raw(t as *mut Pool<T>))
-                    }) as Box<dyn FnOnce(*mut ())>);
-                    Opaque { t, drop }
+                    });
+                    Opaque::new::<T>(d, t, drop)
                 });
+                pool.check::<T>(d);
                 (f.take().unwrap())(unsafe { Some(&*(pool.t as *mut Pool<T>)) })
             }
             None => (f.take().unwrap())(None),
@@ -127,19 +197,103 @@ pub fn clear() {
 /// Note this will happen automatically when the current thread dies.
 pub fn clear_type<T: IsoPoolable>() {
     POOLS.with_borrow_mut(|pools| {
-        if let Some(d) = T::DISCRIMINANT {
+        if let Some(d) = discriminant::<T>() {
             pools.remove(&d);
         }
     })
 }
 
+/// Move the objects in a thread local pool of `T`s to `target` when the pool
+/// is destroyed, or stop doing that if `None`.
+///
+/// Thread local pools are destroyed when their thread dies, so without a
+/// drain target every warm object pooled by a short lived thread is freed
+/// with it. With one they are kept in `target`, up to its limits, and
+/// objects taken from it are returned to it. Pools destroyed by [clear] and
+/// [clear_type] are drained too.
+///
+/// ```
+/// use poolshark::global::{self, Pool};
+///
+/// let reserve: Pool<String> = Pool::new(1024, 4096);
+/// global::set_drain_target(Some(&reserve));
+/// std::thread::spawn(|| {
+///     let mut s = global::take::<String>();
+///     s.push_str("warm");
+/// })
+/// .join()
+/// .unwrap();
+/// assert!(reserve.try_take().is_some());
+/// ```
+pub fn set_drain_target<T: IsoPoolable + Send + 'static>(target: Option<&Pool<T>>) {
+    if let Some(d) = discriminant::<T>() {
+        let mut drains = DRAINS.lock().unwrap();
+        match target {
+            None => {
+                drains.remove(&d);
+            }
+            Some(target) => {
+                let target = target.clone();
+                let drain: Drain = Arc::new(move |pool: *const ()| {
+                    // like with_pool, this relies on pools with the same
+                    // discriminant being interchangeable
+                    let pool = unsafe { &*(pool as *const Pool<T>) };
+                    while let Some(mut t) = pool.pop() {
+                        t.assign(&target);
+                        target.insert(t)
+                    }
+                });
+                drains.insert(d, drain);
+            }
+        }
+    }
+}
+
+/// Return every element of `batch` to its pool, leaving `batch` empty but
+/// keeping its capacity.
+///
+/// This does the same thing as `batch.clear()`, but faster. Dropping a
+/// `GPooled` has to upgrade the weak reference to its pool, two contended
+/// atomic operations on the pool's reference count, while this upgrades it
+/// once for each run of consecutive elements from the same pool.
+///
+/// ```
+/// use poolshark::global::{self, GPooled, Pool};
+///
+/// let pool: Pool<String> = Pool::new(1024, 4096);
+/// let mut batch: Vec<GPooled<String>> = (0..100).map(|_| pool.take()).collect();
+/// global::recycle_all(&mut batch);
+/// assert!(batch.is_empty());
+/// ```
+pub fn recycle_all<T: Poolable>(batch: &mut Vec<GPooled<T>>) {
+    let mut current: Option<Pool<T>> = None;
+    for mut t in batch.drain(..) {
+        if !t.really_dropped() {
+            drop(t);
+            continue;
+        }
+        let same = match &current {
+            Some(p) => ptr::eq(Arc::as_ptr(&p.0), t.pool.as_ptr()),
+            None => false,
+        };
+        if !same {
+            current = t.pool.upgrade();
+        }
+        match &current {
+            Some(p) => p.insert(t),
+            None => drop(t),
+        }
+    }
+}
+
 /// Set the pool size for the global pools of `T`.
 ///
 /// Pools that have already been created will not be resized, but new pools (on new threads)
 /// will use the specified size as their max size. If you wish to resize an existing pool you
 /// can first clear_type (or clear) and then set_size.
 pub fn set_size<T: IsoPoolable>(max_pool_size: usize, max_element_capacity: usize) {
-    if let Some(d) = T::DISCRIMINANT {
+    if let Some(d) = discriminant::<T>() {
+        sizes::seen(d, std::any::type_name::<T>());
         SIZES.lock().unwrap().insert(d, (max_pool_size, max_element_capacity));
     }
 }
@@ -148,14 +302,37 @@ pub fn set_size<T: IsoPoolable>(max_pool_size: usize, max_element_capacity: usiz
 ///
 /// If get_size returns None then the type will not be pooled.
 pub fn get_size<T: IsoPoolable>() -> Option<(usize, usize)> {
-    T::DISCRIMINANT.map(|d| {
+    discriminant::<T>().map(|d| {
+        sizes::seen(d, std::any::type_name::<T>());
         SIZES.lock().unwrap().get(&d).map(|(s, c)| (*s, *c)).unwrap_or(DEFAULT_SIZES)
     })
 }
 
-fn take_inner<T: IsoPoolable>(sizes: Option<(usize, usize)>) -> GPooled<T> {
-    with_pool(sizes, |pool| {
-        pool.map(|p| p.take()).unwrap_or_else(|| GPooled::orphan(T::empty()))
+/// Set the capacity reserved by the global pools of `T` for newly allocated
+/// objects.
+///
+/// When a take finds the pool empty the new object will have room for at
+/// least `capacity` elements. Like [set_size] this only affects pools created
+/// after it is called, see [RawPool::set_initial_capacity] to change an
+/// existing pool.
+pub fn set_initial_capacity<T: IsoPoolable>(capacity: usize) {
+    if let Some(d) = discriminant::<T>() {
+        sizes::seen(d, std::any::type_name::<T>());
+        INITIAL_CAPACITY.lock().unwrap().insert(d, capacity);
+    }
+}
+
+fn take_inner<T: IsoPoolable>(
+    sizes: Option<(usize, usize)>,
+    site: &'static Location<'static>,
+) -> GPooled<T> {
+    let timer = Timer::start();
+    with_pool(sizes, |pool| match pool {
+        Some(p) => {
+            p.0.counters.timed(Path::Lookup, timer);
+            p.take_at(site)
+        }
+        None => GPooled::orphan(T::empty()),
     })
 }
 
@@ -163,8 +340,10 @@ fn take_inner<T: IsoPoolable>(sizes: Option<(usize, usize)>) -> GPooled<T> {
 ///
 /// If there is no pool for `T` or there are no `T`s pooled then create a new empty `T`.
 /// If `T` has no discriminant return an orphan.
+#[cfg_attr(feature = "leaks", track_caller)]
 pub fn take<T: IsoPoolable>() -> GPooled<T> {
-    take_inner(None)
+    let site = Location::caller();
+    shadow::take::<T, _, _>(|| take_inner(None, site), |t| t.capacity())
 }
 
 /// Take a `T` from the thread local global pool with custom pool sizes.
@@ -172,8 +351,32 @@ pub fn take<T: IsoPoolable>() -> GPooled<T> {
 /// If there is no pool for `T` or there are no `T`s pooled then create a new empty `T`.
 /// If `T` has no discriminant return an orphan. Also set the pool sizes for this type
 /// if they have not already been set.
+#[cfg_attr(feature = "leaks", track_caller)]
 pub fn take_sz<T: IsoPoolable>(max: usize, max_elements: usize) -> GPooled<T> {
-    take_inner(Some((max, max_elements)))
+    let site = Location::caller();
+    shadow::take::<T, _, _>(
+        || take_inner(Some((max, max_elements)), site),
+        |t| t.capacity(),
+    )
+}
+
+/// Take a `T` with room for at least `capacity` elements from the thread local global pool.
+///
+/// Like [take], but the capacity is reserved with fallible allocation, so if
+/// the allocator fails an error is returned instead of aborting the process.
+#[cfg_attr(feature = "leaks", track_caller)]
+pub fn try_take_or_alloc<T: IsoPoolable>(
+    capacity: usize,
+) -> Result<GPooled<T>, AllocError> {
+    let site = Location::caller();
+    with_pool(None, |pool| match pool {
+        Some(p) => p.try_take_or_alloc_at(capacity, site),
+        None => {
+            let mut t = GPooled::orphan(T::empty());
+            Poolable::try_reserve(&mut *t, capacity)?;
+            Ok(t)
+        }
+    })
 }
 
 /// Get a reference to the thread local global pool of `T`s.
@@ -197,11 +400,113 @@ pub fn pool_sz<T: IsoPoolable>(max: usize, max_elements: usize) -> Option<Pool<T
     with_pool(Some((max, max_elements)), |pool| pool.cloned())
 }
 
+/// A cached handle to the thread local global pool of `T`s.
+///
+/// Every call to [take] looks the pool up in a thread local hash table. In a
+/// hot loop get a handle once with [handle] and take from it instead. The
+/// handle keeps using the pool it was created with, even if it is moved to
+/// another thread, and keeps that pool alive after [clear] or [clear_type].
+pub struct GlobalPoolHandle<T: IsoPoolable>(Option<Pool<T>>);
+
+impl<T: IsoPoolable> Clone for GlobalPoolHandle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: IsoPoolable> Debug for GlobalPoolHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<global pool handle>")
+    }
+}
+
+impl<T: IsoPoolable> GlobalPoolHandle<T> {
+    /// Take a `T` from the pool, see [take]
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take(&self) -> GPooled<T> {
+        match &self.0 {
+            Some(p) => p.take(),
+            None => GPooled::orphan(T::empty()),
+        }
+    }
+
+    /// Take a `T` from the pool, or `None` if it is empty or `T` has no
+    /// discriminant
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn try_take(&self) -> Option<GPooled<T>> {
+        self.0.as_ref().and_then(|p| p.try_take())
+    }
+
+    /// Return the pool, or `None` if `T` has no discriminant
+    pub fn pool(&self) -> Option<&Pool<T>> {
+        self.0.as_ref()
+    }
+}
+
+/// Get a cached handle to the thread local global pool of `T`s.
+///
+/// ```
+/// use poolshark::global;
+///
+/// let strings = global::handle::<String>();
+/// for i in 0..100 {
+///     let mut s = strings.take(); // no thread local lookup
+///     s.push_str("hello");
+/// }
+/// ```
+pub fn handle<T: IsoPoolable>() -> GlobalPoolHandle<T> {
+    GlobalPoolHandle(pool())
+}
+
 thread_local! {
     static ANY_POOLS: RefCell<FxHashMap<TypeId, Box<dyn Any>>> =
         RefCell::new(HashMap::default());
 }
 
+static ANY_SIZES: LazyLock<Mutex<FxHashMap<TypeId, (usize, usize)>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+fn with_any_pool<T, R, F>(sizes: Option<(usize, usize)>, f: F) -> R
+where
+    T: Any + Poolable,
+    F: FnOnce(&Pool<T>) -> R,
+{
+    ANY_POOLS.with_borrow_mut(|pools| {
+        let pool = pools.entry(TypeId::of::<T>()).or_insert_with(|| {
+            let (size, max) = sizes.unwrap_or_else(get_size_any::<T>);
+            Box::new(Pool::<T>::new(size, max))
+        });
+        f(pool.downcast_ref::<Pool<T>>().unwrap())
+    })
+}
+
+/// Set the pool size for the generic thread local pools of `T`.
+///
+/// This is the [Any] pool equivalent of [set_size]. Pools that have already
+/// been created will not be resized, but new pools created by [pool_any_default]
+/// or [take_any_default] (on new threads) will use the specified sizes.
+pub fn set_size_any<T: Any + Poolable>(
+    max_pool_size: usize,
+    max_element_capacity: usize,
+) {
+    ANY_SIZES
+        .lock()
+        .unwrap()
+        .insert(TypeId::of::<T>(), (max_pool_size, max_element_capacity));
+}
+
+/// Get the max pool size and max element capacity for the generic pools of `T`.
+///
+/// Returns the defaults if [set_size_any] has not been called for `T`.
+pub fn get_size_any<T: Any + Poolable>() -> (usize, usize) {
+    ANY_SIZES
+        .lock()
+        .unwrap()
+        .get(&TypeId::of::<T>())
+        .map(|(s, c)| (*s, *c))
+        .unwrap_or(DEFAULT_SIZES)
+}
+
 /// Get a reference to a pool from the generic thread local pool set.
 ///
 /// This works for any type that implements [Any] + [Poolable]. Note this is a different
@@ -210,14 +515,15 @@ thread_local! {
 /// doesn't really matter for performance which one you choose as long as your
 /// choice is consistent.
 pub fn pool_any<T: Any + Poolable>(size: usize, max: usize) -> Pool<T> {
-    ANY_POOLS.with_borrow_mut(|pools| {
-        pools
-            .entry(TypeId::of::<T>())
-            .or_insert_with(|| Box::new(Pool::<T>::new(size, max)))
-            .downcast_ref::<Pool<T>>()
-            .unwrap()
-            .clone()
-    })
+    with_any_pool(Some((size, max)), |pool| pool.clone())
+}
+
+/// Get a reference to a pool from the generic thread local pool set.
+///
+/// Like [pool_any], but if the pool doesn't exist yet it is created with the
+/// sizes configured by [set_size_any].
+pub fn pool_any_default<T: Any + Poolable>() -> Pool<T> {
+    with_any_pool(None, |pool| pool.clone())
 }
 
 /// Take a poolable type `T` from the generic thread local pool set.
@@ -225,15 +531,60 @@ pub fn pool_any<T: Any + Poolable>(size: usize, max: usize) -> Pool<T> {
 /// This works for types that implement [Any] + [Poolable]. It is much more efficient
 /// to use [take] if your container type implements [IsoPoolable], and even more efficient
 /// to use [pool] or [pool_any] and store the pool somewhere.
+#[cfg_attr(feature = "leaks", track_caller)]
 pub fn take_any<T: Any + Poolable>(size: usize, max: usize) -> GPooled<T> {
-    ANY_POOLS.with_borrow_mut(|pools| {
-        pools
-            .entry(TypeId::of::<T>())
-            .or_insert_with(|| Box::new(Pool::<T>::new(size, max)))
-            .downcast_ref::<Pool<T>>()
-            .unwrap()
-            .take()
-    })
+    let site = Location::caller();
+    with_any_pool(Some((size, max)), |pool| pool.take_at(site))
+}
+
+/// Take a poolable type `T` from the generic thread local pool set.
+///
+/// Like [take_any], but if the pool doesn't exist yet it is created with the
+/// sizes configured by [set_size_any].
+#[cfg_attr(feature = "leaks", track_caller)]
+pub fn take_any_default<T: Any + Poolable>() -> GPooled<T> {
+    let site = Location::caller();
+    with_any_pool(None, |pool| pool.take_at(site))
+}
+
+static SHARED_ANY_POOLS: LazyLock<Mutex<FxHashMap<TypeId, Box<dyn Any + Send + Sync>>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+fn shared_pool_any_inner<T: Any + Poolable + Send>(
+    sizes: Option<(usize, usize)>,
+) -> Pool<T> {
+    SHARED_ANY_POOLS
+        .lock()
+        .unwrap()
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| {
+            let (size, max) = sizes.unwrap_or_else(get_size_any::<T>);
+            Box::new(Pool::<T>::new(size, max))
+        })
+        .downcast_ref::<Pool<T>>()
+        .unwrap()
+        .clone()
+}
+
+/// Get the process wide shared pool of `T`s.
+///
+/// Unlike [pool_any], which returns a thread local pool, every thread gets the
+/// same pool, so independent components (e.g. plugins) can share pools by type
+/// without coordinating statics. The pool is created on first use with the
+/// sizes configured by [set_size_any] and lives for the rest of the program.
+///
+/// This takes a global lock, so store the returned pool rather than calling
+/// this in a hot loop.
+pub fn shared_pool_any<T: Any + Poolable + Send>() -> Pool<T> {
+    shared_pool_any_inner(None)
+}
+
+/// Get the process wide shared pool of `T`s with custom sizes.
+///
+/// Like [shared_pool_any], but if the pool has not been created yet it will be
+/// created with the specified sizes.
+pub fn shared_pool_any_sz<T: Any + Poolable + Send>(size: usize, max: usize) -> Pool<T> {
+    shared_pool_any_inner(Some((size, max)))
 }
 
 /// A wrapper for globally pooled objects with cross-thread pool affinity.
@@ -276,12 +627,18 @@ pub fn take_any<T: Any + Poolable>(size: usize, max: usize) -> GPooled<T> {
 ///
 /// - **Pool affinity**: Always returns to the pool it was created from
 /// - **Thread-safe**: Can be sent between threads
-/// - **Overhead**: One word (8 bytes on 64-bit) to store pool pointer
-/// - **Lock-free**: Uses `crossbeam` lock-free queues
+/// - **Overhead**: One word (8 bytes on 64-bit) to store pool pointer, the
+///   `epoch`, `stats`, `leaks`, and `tracing` features each add a little more
+/// - **Lock-free**: Uses `crossbeam` lock-free queues (unless the `deterministic`
+///   feature is enabled)
 #[derive(Clone)]
 pub struct GPooled<T: Poolable> {
     pool: ManuallyDrop<WeakPool<Self>>,
     object: ManuallyDrop<T>,
+    age: Age,
+    epoch: Stamp,
+    site: Site,
+    span: ObjectSpan,
 }
 
 impl<T: Poolable + Debug> fmt::Debug for GPooled<T> {
@@ -297,19 +654,28 @@ impl<T: Poolable + Display> fmt::Display for GPooled<T> {
 }
 
 impl<T: IsoPoolable> Default for GPooled<T> {
+    #[cfg_attr(feature = "leaks", track_caller)]
     fn default() -> Self {
         take()
     }
 }
 
 impl<T: IsoPoolable> GPooled<T> {
+    #[cfg_attr(feature = "leaks", track_caller)]
     pub fn take() -> Self {
         take()
     }
 
+    #[cfg_attr(feature = "leaks", track_caller)]
     pub fn take_sz(max: usize, max_elements: usize) -> Self {
         take_sz(max, max_elements)
     }
+
+    /// Detach the object from its global pool and wrap it as an [LPooled],
+    /// so it is returned to this thread's local pool when dropped.
+    pub fn into_local(self) -> LPooled<T> {
+        LPooled::from(self.detach())
+    }
 }
 
 impl<T: IsoPoolable + Extend<E>, E> Extend<E> for GPooled<T> {
@@ -320,22 +686,81 @@ impl<T: IsoPoolable + Extend<E>, E> Extend<E> for GPooled<T> {
 
 unsafe impl<T: Poolable> RawPoolable for GPooled<T> {
     fn empty(pool: WeakPool<Self>) -> Self {
-        Self {
-            pool: ManuallyDrop::new(pool),
-            object: ManuallyDrop::new(Poolable::empty()),
-        }
+        Self::from_parts(pool, Poolable::empty())
     }
 
     fn reset(&mut self) {
         Poolable::reset(&mut *self.object)
     }
 
+    fn try_reset(&mut self) -> Result<(), ResetError> {
+        Poolable::try_reset(&mut *self.object)
+    }
+
     fn capacity(&self) -> usize {
         Poolable::capacity(&*self.object)
     }
 
-    fn really_drop(self) {
-        drop(self.detach())
+    fn really_drop(mut self) {
+        if self.pool.take_admitted()
+            && let Some(pool) = self.pool.upgrade()
+        {
+            pool.released()
+        }
+        drop(self.detach_quiet())
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        Poolable::allocated_bytes(&*self.object)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        Poolable::try_reserve(&mut *self.object, additional)
+    }
+
+    fn hooks(&self, _: crate::sealed::Token) -> Option<&dyn Hooks> {
+        Some(self)
+    }
+
+    fn hooks_mut(&mut self, _: crate::sealed::Token) -> Option<&mut dyn Hooks> {
+        Some(self)
+    }
+
+    fn weak_pool(&mut self, _: crate::sealed::Token) -> Option<&mut WeakPool<Self>> {
+        Some(&mut self.pool)
+    }
+
+    fn pooled_name(_: crate::sealed::Token) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Poolable> Hooks for GPooled<T> {
+    fn record_use(&mut self) {
+        self.age.used()
+    }
+
+    fn record_site(&mut self, site: &'static Location<'static>) {
+        self.site.set::<T>(site)
+    }
+
+    fn age(&self) -> Option<(std::time::Duration, u64)> {
+        self.age.get()
+    }
+
+    fn epoch(&self) -> Option<u64> {
+        self.epoch.get()
+    }
+
+    fn start_span(&mut self) {
+        self.span.start::<T>(Poolable::capacity(&*self.object))
+    }
+}
+
+impl<T: Poolable> From<T> for GPooled<T> {
+    /// Wrap `t` as an [orphan](GPooled::orphan)
+    fn from(t: T) -> Self {
+        Self::orphan(t)
     }
 }
 
@@ -351,6 +776,18 @@ impl Borrow<str> for GPooled<String> {
     }
 }
 
+impl Borrow<path::Path> for GPooled<PathBuf> {
+    fn borrow(&self) -> &path::Path {
+        &self.object
+    }
+}
+
+impl<T> Borrow<[T]> for GPooled<Vec<T>> {
+    fn borrow(&self) -> &[T] {
+        &self.object
+    }
+}
+
 impl<T: Poolable + PartialEq> PartialEq for GPooled<T> {
     fn eq(&self, other: &GPooled<T>) -> bool {
         self.object.eq(&other.object)
@@ -385,7 +822,71 @@ impl<T: Poolable> GPooled<T> {
     ///
     /// Useful for branches where you know a given `Pooled` will always be empty.
     pub fn orphan(t: T) -> Self {
-        Self { pool: ManuallyDrop::new(WeakPool::new()), object: ManuallyDrop::new(t) }
+        Self::from_parts(WeakPool::new(), t)
+    }
+
+    /// Creates an empty `GPooled` that isn't connected to any pool.
+    ///
+    /// Libraries can use this to return `GPooled` values without forcing
+    /// their callers to set up a pool.
+    pub fn orphan_default() -> Self {
+        Self::orphan(T::empty())
+    }
+
+    /// Return true if the object isn't connected to a live pool, and so
+    /// will be really dropped when it is dropped.
+    pub fn is_orphan(&self) -> bool {
+        self.pool.strong_count() == 0
+    }
+
+    /// Take an object from `pool`, allocating one if it is empty.
+    ///
+    /// This is the same as [RawPool::take], it exists so moving a call site
+    /// from the thread local `GPooled::take()` to a named pool, e.g. one
+    /// declared with [static_pool!](crate::static_pool), is a one word
+    /// change. It skips the thread local lookup, and records the same
+    /// statistics and obeys the same limits as any other take from `pool`.
+    ///
+    /// ```
+    /// use poolshark::{global::GPooled, static_pool};
+    ///
+    /// static_pool!(static NAMES: String);
+    ///
+    /// let before: GPooled<String> = GPooled::take();
+    /// let after: GPooled<String> = GPooled::take_from(&NAMES);
+    /// ```
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take_from(pool: &Pool<T>) -> Self {
+        pool.take()
+    }
+
+    /// Take an object from `pool`, or `None` if it is empty, see
+    /// [RawPool::try_take]
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn try_take_from(pool: &Pool<T>) -> Option<Self> {
+        pool.try_take()
+    }
+
+    /// Creates a `GPooled` holding `t` that returns to `pool` when dropped, if
+    /// the pool still exists.
+    ///
+    /// `GPooled` only ever holds a weak reference to its pool, so a long
+    /// lived cache can hold objects like this without keeping a short lived
+    /// pool alive. If the pool is gone when the object is dropped it is
+    /// dropped normally.
+    pub fn with_weak(pool: WeakPool<Self>, t: T) -> Self {
+        Self::from_parts(pool, t)
+    }
+
+    fn from_parts(pool: WeakPool<Self>, t: T) -> Self {
+        Self {
+            pool: ManuallyDrop::new(pool),
+            object: ManuallyDrop::new(t),
+            age: Age::new(),
+            epoch: Stamp::new(),
+            site: Site::none(),
+            span: ObjectSpan::none(),
+        }
     }
 
     /// Assign the `GPooled` to the specified pool.
@@ -393,20 +894,212 @@ impl<T: Poolable> GPooled<T> {
     /// When dropped, it will be placed in `pool` instead of the pool it was originally
     /// allocated from. If an orphan is assigned a pool it will no longer be orphaned.
     pub fn assign(&mut self, pool: &Pool<T>) {
-        let old = mem::replace(&mut self.pool, ManuallyDrop::new(pool.downgrade()));
+        let mut old = mem::replace(&mut self.pool, ManuallyDrop::new(pool.downgrade()));
+        if old.take_admitted()
+            && let Some(old) = old.upgrade()
+        {
+            old.released()
+        }
         drop(ManuallyDrop::into_inner(old))
     }
 
+    /// [Assign](Self::assign) the object to `pool` if it is an
+    /// [orphan](Self::is_orphan), keep its pool otherwise, see
+    /// [RawPool::adopt_orphan].
+    pub fn ensure_attached(&mut self, pool: &Pool<T>) {
+        if self.is_orphan() {
+            self.assign(pool)
+        }
+    }
+
+    /// Transform the inner value, keeping the pool attachment.
+    ///
+    /// The result will be returned to the same pool as `self` when it is
+    /// dropped. This replaces the detach, orphan, assign dance. If `f` panics
+    /// the inner value is dropped by `f` and nothing is returned to the pool,
+    /// which counts the object as [detached](Self::detach).
+    pub fn map<F: FnOnce(T) -> T>(mut self, f: F) -> Self {
+        let (age, epoch) = (self.age, self.epoch);
+        let site = self.site.take();
+        let span = self.span.take();
+        let (pool, t) = self.into_parts();
+        let mut lost = Lost(Some(pool));
+        let object = ManuallyDrop::new(f(t));
+        let pool = ManuallyDrop::new(lost.0.take().unwrap());
+        Self { pool, object, age, epoch, site, span }
+    }
+
+    /// Fallibly transform the inner value, keeping the pool attachment.
+    ///
+    /// Like [GPooled::map], but if `f` fails the error is returned and the
+    /// inner value, which was consumed by `f`, is not returned to the pool,
+    /// which counts the object as [detached](Self::detach).
+    pub fn try_map<E, F: FnOnce(T) -> Result<T, E>>(mut self, f: F) -> Result<Self, E> {
+        let (age, epoch) = (self.age, self.epoch);
+        let site = self.site.take();
+        let span = self.span.take();
+        let (pool, t) = self.into_parts();
+        let mut lost = Lost(Some(pool));
+        let object = ManuallyDrop::new(f(t)?);
+        let pool = ManuallyDrop::new(lost.0.take().unwrap());
+        Ok(Self { pool, object, age, epoch, site, span })
+    }
+
+    /// Reserve room for `additional` more elements, aborting if the
+    /// allocation fails.
+    ///
+    /// Unlike reserving through the inner object, this knows about the pool,
+    /// and calls the [oversized reserve hook](crate::set_oversized_reserve_hook)
+    /// if the object grew too big to be pooled again.
+    pub fn reserve_pooled(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve_pooled(additional) {
+            panic!("{e}")
+        }
+    }
+
+    /// Like [reserve_pooled](Self::reserve_pooled), but returns an error if
+    /// the allocation fails.
+    pub fn try_reserve_pooled(&mut self, additional: usize) -> Result<(), AllocError> {
+        let pool = &self.pool;
+        reserve_pooled(&mut *self.object, additional, || {
+            pool.upgrade().map(|p| p.0.max_elt_capacity)
+        })
+    }
+
+    /// Borrow the object as a [PooledRef](view::PooledRef), which can be
+    /// handed to functions that accept pooled and plain objects alike, and
+    /// can't detach or reassign it.
+    pub fn downgrade(&mut self) -> view::PooledRef<'_, T> {
+        view::PooledRef::from(self)
+    }
+
+    /// Share the object read only by moving it into a pooled
+    /// [Arc](arc::Arc) from `arcs`, see [Arc::from_pooled](arc::Arc::from_pooled).
+    ///
+    /// ```
+    /// use poolshark::global::{arc::Arc, GPooled, Pool, RawPool};
+    ///
+    /// let strings: Pool<String> = Pool::new(64, 1024);
+    /// let arcs: RawPool<Arc<String>> = RawPool::new(64, 1);
+    /// let mut s = strings.take();
+    /// s.push_str("hello");
+    /// let shared = s.into_shared(&arcs);
+    /// assert_eq!(&*shared, "hello");
+    /// drop(shared); // the arc, and the string in it, go back to arcs
+    /// ```
+    pub fn into_shared(self, arcs: &RawPool<arc::Arc<T>>) -> arc::Arc<T> {
+        arc::Arc::from_pooled(arcs, self)
+    }
+
+    fn into_parts(self) -> (WeakPool<Self>, T) {
+        let mut t = ManuallyDrop::new(self);
+        t.site.clear();
+        t.span.end();
+        unsafe { (ManuallyDrop::take(&mut t.pool), ManuallyDrop::take(&mut t.object)) }
+    }
+
     /// Detach the object from the pool, returning the inner value.
     ///
     /// The detached object will not be returned to any pool when dropped.
-    pub fn detach(self) -> T {
+    /// With the `stats` feature the pool counts it as
+    /// [detached](crate::stats::UsageCounts::detached) rather than
+    /// outstanding.
+    pub fn detach(mut self) -> T {
+        Self::gone(&mut self.pool);
+        self.detach_quiet()
+    }
+
+    // tell the pool an object that was taken from it won't be back
+    fn gone(pool: &mut WeakPool<Self>) {
+        let admitted = pool.take_admitted();
+        if let Some(pool) = pool.upgrade() {
+            if admitted {
+                pool.released()
+            }
+            pool.0.counters.detached()
+        }
+    }
+
+    // detach without telling the pool the object is gone, for objects the
+    // pool already knows about
+    fn detach_quiet(self) -> T {
         let mut t = ManuallyDrop::new(self);
+        t.site.clear();
+        t.span.end();
         unsafe {
             ManuallyDrop::drop(&mut t.pool);
             ManuallyDrop::take(&mut t.object)
         }
     }
+
+    /// Return the object to its pool when the returned future is polled,
+    /// instead of now.
+    ///
+    /// Dropping a `GPooled` resets the object, e.g. drops every element of
+    /// a batch, and pushes it onto the pool's queue, on the dropping thread.
+    /// A latency critical task can instead hand this future to a less
+    /// critical one, or to an executor, to do that work later. If the
+    /// future is dropped without being polled the object is returned then.
+    pub async fn return_async(self) {
+        drop(self)
+    }
+
+    /// Return the object to its pool from a new tokio task, see
+    /// [return_async](Self::return_async).
+    ///
+    /// ```
+    /// use poolshark::global::{Pool, PoolControl};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let pool: Pool<Vec<u64>> = Pool::new(64, 1 << 20);
+    /// let mut batch = pool.take();
+    /// batch.extend(0..100_000);
+    /// // the batch is cleared and pooled off this task's hot path
+    /// batch.spawn_return().await.unwrap();
+    /// assert_eq!(pool.len(), 1);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If called outside a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_return(self) -> tokio::task::JoinHandle<()>
+    where
+        T: Send + 'static,
+    {
+        tokio::task::spawn(self.return_async())
+    }
+}
+
+impl<T: Poolable + Deref> GPooled<T> {
+    /// Freeze the object into an immutable handle to its contents.
+    ///
+    /// The allocation still goes back to this pool when the handle is
+    /// dropped, see [Frozen].
+    pub fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self)
+    }
+}
+
+impl<T: Poolable + Unpin> GPooled<T> {
+    /// Pin the pooled object.
+    ///
+    /// `GPooled` stores the object inline and the pool recycles objects by
+    /// handing out `&mut T` to reset them, so it can't uphold the pinning
+    /// guarantees for a `!Unpin` object itself, which is why `T: Unpin` is
+    /// required, pool those as [Pinned](crate::pooled::Pinned) instead. What
+    /// it does guarantee is that the pool never moves or reallocates the
+    /// heap memory owned by the object, only the object's own methods do.
+    /// For example the buffer of a `GPooled<Vec<U>>` stays at the same
+    /// address for as long as you don't grow it, no matter how many times
+    /// the `GPooled` is moved, and if the pool rejects the object it is
+    /// dropped in place. Intrusive or self referential structures should
+    /// point into that heap memory rather than into the object.
+    pub fn pin(self) -> Pin<Self> {
+        Pin::new(self)
+    }
 }
 
 impl<T: Poolable> AsRef<T> for GPooled<T> {
@@ -429,9 +1122,25 @@ impl<T: Poolable> DerefMut for GPooled<T> {
     }
 }
 
+// the pool of an object map or try_map consumed, which is told the
+// object is gone unless the transform hands back a new one
+struct Lost<T: Poolable>(Option<WeakPool<GPooled<T>>>);
+
+impl<T: Poolable> Drop for Lost<T> {
+    fn drop(&mut self) {
+        if let Some(mut pool) = self.0.take() {
+            GPooled::gone(&mut pool)
+        }
+    }
+}
+
 impl<T: Poolable> Drop for GPooled<T> {
     fn drop(&mut self) {
         if self.really_dropped() {
+            // pooled objects aren't outstanding, and the copy read below
+            // must not release the site or close the span a second time
+            self.site.clear();
+            self.span.end();
             match self.pool.upgrade() {
                 Some(pool) => pool.insert(unsafe { ptr::read(self) }),
                 None => unsafe {
@@ -459,7 +1168,7 @@ impl<'de, T: Poolable + DeserializeOwned + 'static> Deserialize<'de> for GPooled
     where
         D: serde::Deserializer<'de>,
     {
-        let mut t = take_any::<T>(1024, 1024);
+        let mut t = take_any_default::<T>();
         Self::deserialize_in_place(deserializer, &mut t)?;
         Ok(t)
     }
@@ -472,48 +1181,298 @@ impl<'de, T: Poolable + DeserializeOwned + 'static> Deserialize<'de> for GPooled
     }
 }
 
+/// Deserialize a [GPooled] from the `Any + Poolable` pool set with explicit
+/// sizes.
+///
+/// Deserializing a plain `GPooled<T>` takes from the pool of `T`s created
+/// with the sizes configured by [set_size_any]. A seed instead creates the
+/// pool, if it doesn't exist yet, with its own sizes, like [take_any].
+///
+/// ```
+/// use poolshark::global::GPooledSeed;
+/// use serde::de::{value::{Error, SeqDeserializer}, DeserializeSeed};
+///
+/// let seed = GPooledSeed::<Vec<u32>>::new(64, 4096);
+/// let de = SeqDeserializer::<_, Error>::new([1u32, 2, 3].into_iter());
+/// let v = seed.deserialize(de).unwrap();
+/// assert_eq!(&*v, &[1, 2, 3]);
+/// ```
+#[cfg(feature = "serde")]
 #[derive(Debug)]
-struct PoolInner<T: RawPoolable> {
-    max_elt_capacity: usize,
-    pool: ArrayQueue<T>,
+pub struct GPooledSeed<T> {
+    /// The maximum number of objects the pool retains
+    pub size: usize,
+    /// Objects with a capacity larger than this are not retained
+    pub max_elt: usize,
+    ty: std::marker::PhantomData<fn() -> T>,
 }
 
-impl<T: RawPoolable> Drop for PoolInner<T> {
-    fn drop(&mut self) {
-        while let Some(t) = self.pool.pop() {
-            RawPoolable::really_drop(t)
-        }
+#[cfg(feature = "serde")]
+impl<T> Clone for GPooledSeed<T> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
-/// A weak reference to a global Pool
-pub struct WeakPool<T: RawPoolable>(Weak<PoolInner<T>>);
+#[cfg(feature = "serde")]
+impl<T> Copy for GPooledSeed<T> {}
 
-impl<T: RawPoolable> Debug for WeakPool<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "<weak pool>")
+#[cfg(feature = "serde")]
+impl<T> GPooledSeed<T> {
+    /// A seed for pools retaining up to `size` objects of capacity at most
+    /// `max_elt`
+    pub const fn new(size: usize, max_elt: usize) -> Self {
+        Self { size, max_elt, ty: std::marker::PhantomData }
     }
 }
 
-impl<T: RawPoolable> Clone for WeakPool<T> {
-    fn clone(&self) -> Self {
-        Self(Weak::clone(&self.0))
+#[cfg(feature = "serde")]
+impl<'de, T: Any + Poolable + DeserializeOwned> serde::de::DeserializeSeed<'de>
+    for GPooledSeed<T>
+{
+    type Value = GPooled<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<GPooled<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut t = take_any::<T>(self.size, self.max_elt);
+        GPooled::deserialize_in_place(deserializer, &mut t)?;
+        Ok(t)
     }
 }
 
-impl<T: RawPoolable> WeakPool<T> {
-    pub fn new() -> Self {
-        WeakPool(Weak::new())
-    }
+/// The objects taken by [RawPool::take_many]
+pub struct TakeMany<'a, T: RawPoolable> {
+    pool: &'a RawPool<T>,
+    remaining: usize,
+    dry: bool,
+    site: &'static Location<'static>,
+}
+
+impl<T: RawPoolable> Debug for TakeMany<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TakeMany").field("remaining", &self.remaining).finish()
+    }
+}
+
+impl<T: RawPoolable> Iterator for TakeMany<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let pool = self.pool;
+        let (t, hit) = pool.take_maybe_pop(!self.dry, 0, || pool.alloc(), self.site);
+        self.dry |= !hit;
+        Some(t)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: RawPoolable> ExactSizeIterator for TakeMany<'_, T> {}
+
+#[derive(Debug)]
+struct PoolInner<T: RawPoolable> {
+    max_elt_capacity: usize,
+    budget: Option<Arc<Budget>>,
+    prune_mode: AtomicU8,
+    frozen: AtomicBool,
+    bypass_oversized: AtomicBool,
+    trace_spans: AtomicBool,
+    measure_deep: AtomicBool,
+    deep_size: OnceLock<fn(&T) -> usize>,
+    min_elt_capacity: AtomicUsize,
+    max_takers: AtomicUsize,
+    takers: AtomicUsize,
+    initial_capacity: AtomicUsize,
+    prefill_after: AtomicUsize,
+    prefill_count: AtomicUsize,
+    misses: AtomicUsize,
+    counters: PoolCounters,
+    evictions: Notifier,
+    retention: Tracker,
+    pool: Queue<T>,
+}
+
+impl<T: RawPoolable> PoolInner<T> {
+    fn retention_report(&self) -> RetentionReport {
+        RetentionReport {
+            type_name: std::any::type_name::<T>(),
+            capacity: self.pool.capacity(),
+            max_elt_capacity: self.max_elt_capacity,
+            peak_len: 0,
+            takes: 0,
+            hits: 0,
+            evictions: 0,
+            retained_bytes: self.counters.retained_bytes(),
+        }
+    }
+}
+
+impl<T: RawPoolable> Drop for PoolInner<T> {
+    fn drop(&mut self) {
+        self.retention.dropped(self.retention_report());
+        while let Some(t) = self.pool.pop() {
+            if let Some(b) = &self.budget {
+                b.release(t.allocated_bytes())
+            }
+            RawPoolable::really_drop(t)
+        }
+    }
+}
+
+/// A limit on the bytes retained by a group of pools
+#[derive(Debug)]
+pub(crate) struct Budget {
+    max_bytes: usize,
+    used: AtomicUsize,
+}
+
+impl Budget {
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self { max_bytes, used: AtomicUsize::new(0) }
+    }
+
+    pub(crate) fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub(crate) fn used(&self) -> usize {
+        self.used.load(AtomicOrdering::Relaxed)
+    }
+
+    fn try_acquire(&self, bytes: usize) -> bool {
+        let used = self.used.fetch_add(bytes, AtomicOrdering::Relaxed);
+        if used + bytes > self.max_bytes {
+            self.used.fetch_sub(bytes, AtomicOrdering::Relaxed);
+            false
+        } else {
+            true
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, AtomicOrdering::Relaxed);
+    }
+}
+
+fn is_stale<T: RawPoolable>(t: &T) -> bool {
+    t.epoch().is_some_and(|e| e < epoch::current())
+}
+
+/// A weak reference to a global Pool
+pub struct WeakPool<T: RawPoolable> {
+    // from Weak::into_raw, or null for no pool. The low bit, which the
+    // alignment of PoolInner leaves free, is set if the object holding the
+    // reference is counted against the pool's taker limit, so objects
+    // carry that without growing.
+    ptr: *const PoolInner<T>,
+    weak: PhantomData<Weak<PoolInner<T>>>,
+}
+
+unsafe impl<T: RawPoolable> Send for WeakPool<T> where Weak<PoolInner<T>>: Send {}
+unsafe impl<T: RawPoolable> Sync for WeakPool<T> where Weak<PoolInner<T>>: Sync {}
+
+const ADMITTED: usize = 1;
+
+impl<T: RawPoolable> Debug for WeakPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<weak pool>")
+    }
+}
+
+impl<T: RawPoolable> Clone for WeakPool<T> {
+    // the clone belongs to another object, so it is never admitted
+    fn clone(&self) -> Self {
+        match self.weak() {
+            None => Self::new(),
+            Some(w) => Self::from_weak(Weak::clone(&w)),
+        }
+    }
+}
+
+impl<T: RawPoolable> Drop for WeakPool<T> {
+    fn drop(&mut self) {
+        if let Some(w) = self.weak() {
+            drop(ManuallyDrop::into_inner(w))
+        }
+    }
+}
+
+impl<T: RawPoolable> WeakPool<T> {
+    pub fn new() -> Self {
+        WeakPool { ptr: ptr::null(), weak: PhantomData }
+    }
+
+    fn from_weak(w: Weak<PoolInner<T>>) -> Self {
+        let ptr = Weak::into_raw(w);
+        debug_assert_eq!(ptr.addr() & ADMITTED, 0);
+        WeakPool { ptr, weak: PhantomData }
+    }
+
+    fn as_ptr(&self) -> *const PoolInner<T> {
+        self.ptr.map_addr(|a| a & !ADMITTED)
+    }
+
+    // borrow the Weak the pointer came from, if there is one
+    fn weak(&self) -> Option<ManuallyDrop<Weak<PoolInner<T>>>> {
+        let ptr = self.as_ptr();
+        (!ptr.is_null()).then(|| ManuallyDrop::new(unsafe { Weak::from_raw(ptr) }))
+    }
 
     pub fn upgrade(&self) -> Option<RawPool<T>> {
-        self.0.upgrade().map(RawPool)
+        self.weak().and_then(|w| w.upgrade()).map(RawPool)
+    }
+
+    fn strong_count(&self) -> usize {
+        self.weak().map(|w| w.strong_count()).unwrap_or(0)
+    }
+
+    pub(crate) fn set_admitted(&mut self) {
+        self.ptr = self.ptr.map_addr(|a| a | ADMITTED)
+    }
+
+    // clear the admitted bit, returning whether it was set
+    pub(crate) fn take_admitted(&mut self) -> bool {
+        let admitted = self.ptr.addr() & ADMITTED != 0;
+        self.ptr = self.as_ptr();
+        admitted
     }
 }
 
 /// A global pool
 pub type Pool<T> = RawPool<GPooled<T>>;
 
+/// Which pooled objects [RawPool::prune] throws away first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum PruneMode {
+    /// Drop the objects that have been in the pool the longest. This is cheap,
+    /// objects are dropped straight off the queue.
+    #[default]
+    Oldest = 0,
+    /// Drop the objects with the most allocated memory first. Dropping one 1
+    /// MiB buffer frees more memory than dropping ten 4 KiB ones, but each
+    /// prune must drain the pool and sort it, and concurrent takes may miss
+    /// while it runs.
+    Largest = 1,
+}
+
+impl PruneMode {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Largest,
+            _ => Self::Oldest,
+        }
+    }
+}
+
 /// a lock-free, thread-safe, dynamically-sized object pool.
 ///
 /// this pool begins with an initial capacity and will continue
@@ -534,7 +1493,7 @@ impl<T: RawPoolable> Clone for RawPool<T> {
 
 impl<T: RawPoolable> RawPool<T> {
     pub fn downgrade(&self) -> WeakPool<T> {
-        WeakPool(Arc::downgrade(&self.0))
+        WeakPool::from_weak(Arc::downgrade(&self.0))
     }
 
     /// Creates a new `RawPool<T>`.
@@ -542,67 +1501,1268 @@ impl<T: RawPoolable> RawPool<T> {
     /// This pool will retain up to `max_capacity` objects of size less than or equal to
     /// `max_elt_capacity`. Objects larger than `max_elt_capacity` will be deallocated immediately.
     pub fn new(max_capacity: usize, max_elt_capacity: usize) -> RawPool<T> {
+        Self::new_budgeted(max_capacity, max_elt_capacity, None)
+    }
+
+    /// Creates a new `RawPool<T>` that stores its objects in `backend`.
+    ///
+    /// The pool will retain up to `backend.capacity()` objects of size less
+    /// than or equal to `max_elt_capacity`. See the [backend] module.
+    pub fn with_backend<B: PoolBackend<T> + 'static>(
+        backend: B,
+        max_elt_capacity: usize,
+    ) -> RawPool<T> {
+        Self::from_queue(Queue::custom(backend), max_elt_capacity, None)
+    }
+
+    /// Creates a new `RawPool<T>` that hands out the most recently returned
+    /// object first.
+    ///
+    /// The default queue is FIFO, so a take gets the object that has been
+    /// in the pool longest, whose memory is the least likely to be in
+    /// cache. This pool keeps its objects in a [MutexStack] instead, which
+    /// keeps the working set small and warm, at the cost of taking a lock
+    /// on every take and insert.
+    ///
+    /// ```
+    /// use poolshark::global::{backend::TakeOrder, Pool};
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new_lifo(16, 4096);
+    /// assert_eq!(pool.order(), TakeOrder::Lifo);
+    /// let (mut a, mut b) = (pool.take(), pool.take());
+    /// a.reserve(8);
+    /// b.reserve(16);
+    /// drop(a);
+    /// drop(b);
+    /// assert!(pool.take().capacity() >= 16);
+    /// ```
+    ///
+    /// [MutexStack]: backend::MutexStack
+    pub fn new_lifo(max_capacity: usize, max_elt_capacity: usize) -> RawPool<T>
+    where
+        T: Send + 'static,
+    {
+        Self::with_backend(backend::MutexStack::new(max_capacity), max_elt_capacity)
+    }
+
+    /// Creates a new `RawPool<T>` that can be [resized](Self::resize).
+    ///
+    /// Every take and insert on this pool takes the read side of a lock,
+    /// so the queue can be swapped out from under them, which costs a
+    /// shared atomic write per operation. Pools made by [new](Self::new)
+    /// don't pay that, but can't be resized.
+    pub fn new_resizable(max_capacity: usize, max_elt_capacity: usize) -> RawPool<T> {
+        Self::from_queue(Queue::resizable(max_capacity), max_elt_capacity, None)
+    }
+
+    pub(crate) fn new_budgeted(
+        max_capacity: usize,
+        max_elt_capacity: usize,
+        budget: Option<Arc<Budget>>,
+    ) -> RawPool<T> {
+        Self::from_queue(Queue::new(max_capacity), max_elt_capacity, budget)
+    }
+
+    fn from_queue(
+        pool: Queue<T>,
+        max_elt_capacity: usize,
+        budget: Option<Arc<Budget>>,
+    ) -> RawPool<T> {
         RawPool(Arc::new(PoolInner {
-            pool: ArrayQueue::new(max_capacity),
+            budget,
+            pool,
+            counters: PoolCounters::new(),
+            evictions: Notifier::new(),
+            retention: Tracker::new(),
+            prune_mode: AtomicU8::new(PruneMode::Oldest as u8),
+            frozen: AtomicBool::new(false),
+            bypass_oversized: AtomicBool::new(false),
+            trace_spans: AtomicBool::new(false),
+            measure_deep: AtomicBool::new(false),
+            deep_size: OnceLock::new(),
+            min_elt_capacity: AtomicUsize::new(0),
+            max_takers: AtomicUsize::new(0),
+            takers: AtomicUsize::new(0),
+            initial_capacity: AtomicUsize::new(0),
+            prefill_after: AtomicUsize::new(0),
+            prefill_count: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
             max_elt_capacity,
         }))
     }
 
+    /// Create a new empty pool with the same configuration as this one.
+    ///
+    /// The new pool has the same limits and options, e.g. its [PruneMode],
+    /// hooks, and budget, but none of the objects, statistics, or state,
+    /// such as being frozen, of this pool. This is useful for stamping out
+    /// many pools, e.g. one per tenant, from a template.
+    ///
+    /// A pool built on a custom [backend] gets a new one from
+    /// [PoolBackend::clone_empty], or the default backend, with the same
+    /// capacity, if it returns `None`.
+    pub fn clone_config(&self) -> RawPool<T> {
+        let pool = Self::from_queue(
+            self.0.pool.clone_empty(),
+            self.0.max_elt_capacity,
+            self.0.budget.clone(),
+        );
+        pool.set_prune_mode(self.prune_mode());
+        pool.set_bypass_oversized(self.bypass_oversized());
+        pool.set_trace_spans(self.trace_spans());
+        if let Some(f) = self.0.deep_size.get() {
+            let _ = pool.0.deep_size.set(*f);
+        }
+        pool.0.measure_deep.store(self.deep_size(), AtomicOrdering::Relaxed);
+        pool.set_min_elt_capacity(self.min_elt_capacity());
+        if let Some(max) = self.max_takers() {
+            pool.set_max_takers(max)
+        }
+        pool.set_initial_capacity(self.initial_capacity());
+        if let Some((after, count)) = self.miss_prefill() {
+            pool.set_miss_prefill(after, count)
+        }
+        pool.0.evictions.set_hook(self.0.evictions.hook());
+        pool.set_track_retention(self.0.retention.is_active());
+        pool.0.retention.set_hook(self.0.retention.hook());
+        pool.set_track_threads(self.track_threads());
+        pool
+    }
+
+    /// An alias for [clone_config](Self::clone_config)
+    pub fn with_same_config(&self) -> RawPool<T> {
+        self.clone_config()
+    }
+
+    // the bytes `t` owns, if the pool counts them, which it only does for
+    // the byte budget and the stats
+    fn counted_bytes(&self, t: &T) -> usize {
+        if self.0.budget.is_some() || cfg!(feature = "stats") {
+            t.allocated_bytes()
+        } else {
+            0
+        }
+    }
+
+    // `t` has left the queue
+    fn unretain(&self, t: &T) {
+        let bytes = self.counted_bytes(t);
+        self.0.counters.unretained(bytes);
+        if let Some(b) = &self.0.budget {
+            b.release(bytes)
+        }
+    }
+
+    // all pops go through here so the retained accounting stays correct
+    fn pop(&self) -> Option<T> {
+        let t = self.0.pool.pop()?;
+        self.unretain(&t);
+        Some(t)
+    }
+
+    // pop, really dropping objects from before the last epoch bump
+    fn pop_fresh(&self) -> Option<T> {
+        loop {
+            let t = self.pop()?;
+            if is_stale(&t) {
+                self.evict(t, EvictReason::Stale)
+            } else {
+                break Some(t);
+            }
+        }
+    }
+
+    // pop up to CAPACITY_SCAN objects looking for one with room for
+    // `capacity` elements, putting the others back
+    fn pop_with_capacity(&self, capacity: usize) -> Option<T> {
+        let mut skipped: [Option<T>; CAPACITY_SCAN] = Default::default();
+        let mut found = None;
+        for slot in skipped.iter_mut() {
+            match self.pop_fresh() {
+                None => break,
+                Some(t) if t.capacity() >= capacity => {
+                    found = Some(t);
+                    break;
+                }
+                Some(t) => *slot = Some(t),
+            }
+        }
+        for t in skipped.into_iter().flatten() {
+            if let Err((t, reason)) = self.push(t) {
+                self.evict(t, reason)
+            }
+        }
+        found
+    }
+
+    // all pushes go through here so the retained accounting stays correct
+    fn push(&self, t: T) -> Result<(), (T, EvictReason)> {
+        let bytes = self.counted_bytes(&t);
+        if let Some(b) = &self.0.budget
+            && !b.try_acquire(bytes)
+        {
+            return Err((t, EvictReason::OverBudget));
+        }
+        self.0.counters.retained(bytes);
+        match self.0.pool.push(t) {
+            Ok(()) => {
+                self.0.retention.pushed(|| self.0.pool.len());
+                Ok(())
+            }
+            Err(t) => {
+                self.0.counters.unretained(bytes);
+                if let Some(b) = &self.0.budget {
+                    b.release(bytes)
+                }
+                Err((t, EvictReason::Full))
+            }
+        }
+    }
+
     /// Try to take an element from the pool.
     ///
     /// Returns `None` if the pool is empty.
+    #[cfg_attr(feature = "leaks", track_caller)]
     pub fn try_take(&self) -> Option<T> {
-        self.0.pool.pop()
+        let counted = self.admit()?;
+        let timer = Timer::start();
+        let popped = if injected(T::pooled_type_name(), Fault::Empty) {
+            None
+        } else {
+            self.pop_fresh()
+        };
+        match popped {
+            Some(mut t) => {
+                if counted {
+                    self.admitted(&mut t)
+                }
+                t.record_use();
+                t.record_site(Location::caller());
+                if self.trace_spans() {
+                    t.start_span()
+                }
+                self.0.retention.taken(true);
+                self.0.counters.looked(true);
+                self.0.counters.taken(t.capacity());
+                self.0.counters.timed(Path::TakeHit, timer);
+                Some(t)
+            }
+            None => {
+                if counted {
+                    self.released()
+                }
+                self.0.retention.taken(false);
+                self.0.counters.looked(false);
+                self.0.counters.timed(Path::TakeMiss, timer);
+                None
+            }
+        }
     }
 
     /// Takes an item from the pool.
     ///
     /// Creates a new item if none are available.
+    #[cfg_attr(feature = "leaks", track_caller)]
     pub fn take(&self) -> T {
-        self.0.pool.pop().unwrap_or_else(|| RawPoolable::empty(self.downgrade()))
+        self.take_at(Location::caller())
+    }
+
+    fn take_at(&self, site: &'static Location<'static>) -> T {
+        self.take_or_else(|| self.alloc(), site)
+    }
+
+    fn take_or_else<F: FnOnce() -> T>(
+        &self,
+        f: F,
+        site: &'static Location<'static>,
+    ) -> T {
+        self.take_maybe_pop(true, 0, f, site).0
+    }
+
+    // take, but only look in the queue if `pop`, and only accept an object
+    // with room for `min_capacity` elements, returning whether the object
+    // came from the pool
+    fn take_maybe_pop<F: FnOnce() -> T>(
+        &self,
+        pop: bool,
+        min_capacity: usize,
+        f: F,
+        site: &'static Location<'static>,
+    ) -> (T, bool) {
+        // check the fault first, an orphan never holds a taker slot
+        if injected(T::pooled_type_name(), Fault::Orphan) {
+            return (RawPoolable::empty(WeakPool::new()), false);
+        }
+        let Some(counted) = self.admit() else {
+            return (RawPoolable::empty(WeakPool::new()), false);
+        };
+        let timer = Timer::start();
+        let pop = pop && !injected(T::pooled_type_name(), Fault::Empty);
+        let popped = pop.then(|| {
+            if min_capacity > 0 {
+                self.pop_with_capacity(min_capacity)
+            } else {
+                self.pop_fresh()
+            }
+        });
+        let (mut t, path) = match popped.flatten() {
+            Some(t) => {
+                self.hit();
+                (t, Path::TakeHit)
+            }
+            None => {
+                self.missed();
+                (f(), Path::TakeMiss)
+            }
+        };
+        if counted {
+            self.admitted(&mut t)
+        }
+        t.record_use();
+        t.record_site(site);
+        if self.trace_spans() {
+            t.start_span()
+        }
+        self.0.retention.taken(matches!(path, Path::TakeHit));
+        self.0.counters.looked(matches!(path, Path::TakeHit));
+        self.0.counters.taken(t.capacity());
+        self.0.counters.timed(path, timer);
+        (t, matches!(path, Path::TakeHit))
+    }
+
+    /// Take `n` objects, as if by calling [take](Self::take) `n` times.
+    ///
+    /// Once the pool runs dry the rest are allocated without looking in
+    /// it again, so a batch producer doesn't pay for `n` failed pops when
+    /// the pool is cold. Objects are taken as the iterator is advanced.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::new(1024, 1024);
+    /// let batch: Vec<_> = pool.take_many(100).collect();
+    /// assert_eq!(batch.len(), 100);
+    /// ```
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take_many(&self, n: usize) -> TakeMany<'_, T> {
+        TakeMany { pool: self, remaining: n, dry: false, site: Location::caller() }
+    }
+
+    // a hit ends a run of misses. The count is shared by every taker, so
+    // only look at it if prefilling is on, and only write it if it isn't 0
+    // already
+    fn hit(&self) {
+        if self.0.prefill_after.load(AtomicOrdering::Relaxed) > 0
+            && self.0.misses.load(AtomicOrdering::Relaxed) > 0
+        {
+            self.0.misses.store(0, AtomicOrdering::Relaxed)
+        }
+    }
+
+    fn missed(&self) {
+        let after = self.0.prefill_after.load(AtomicOrdering::Relaxed);
+        if after > 0 {
+            let n = self.0.misses.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+            if n >= after {
+                self.0.misses.store(0, AtomicOrdering::Relaxed);
+                self.prefill(self.0.prefill_count.load(AtomicOrdering::Relaxed));
+            }
+        }
+    }
+
+    /// Allocate `n` new objects into the pool now, so the first takes
+    /// don't have to, returning how many were added.
+    ///
+    /// The objects get the pool's [initial capacity](Self::set_initial_capacity).
+    /// It stops early if the pool is full, or over its byte budget.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(64, 4096);
+    /// pool.set_initial_capacity(1024);
+    /// assert_eq!(pool.prefill(16), 16);
+    /// assert!(pool.take().capacity() >= 1024);
+    /// ```
+    pub fn prefill(&self, n: usize) -> usize {
+        self.prefill_inner(n, 0)
+    }
+
+    /// Like [prefill](Self::prefill), but reserve room for `capacity`
+    /// elements in each object. To pay for the largest objects the pool
+    /// keeps up front pass [max_elt_capacity](Self::max_elt_capacity), but
+    /// types that round their capacity up, such as hash maps, need a little
+    /// less, or they will be too big to return. It also stops if an
+    /// allocation fails.
+    pub fn prefill_with_capacity(&self, n: usize, capacity: usize) -> usize {
+        self.prefill_inner(n, capacity)
+    }
+
+    fn prefill_inner(&self, n: usize, capacity: usize) -> usize {
+        for i in 0..n {
+            let mut t = self.alloc();
+            if capacity > 0 && t.try_reserve(capacity).is_err() {
+                RawPoolable::really_drop(t);
+                return i;
+            }
+            if let Err((t, _)) = self.push(t) {
+                RawPoolable::really_drop(t);
+                return i;
+            }
+        }
+        n
+    }
+
+    /// Refill the pool with `count` newly allocated objects whenever `after`
+    /// takes in a row find it empty, or turn that off if `after` is 0.
+    ///
+    /// When load ramps up faster than objects come back, every take
+    /// allocates until the pool warms up. This allocates a batch at once
+    /// instead, on the thread that took the last miss. New objects are
+    /// empty, so unless they allocate on creation set an
+    /// [initial capacity](Self::set_initial_capacity) too. Off by default.
+    pub fn set_miss_prefill(&self, after: usize, count: usize) {
+        self.0.prefill_count.store(count, AtomicOrdering::Relaxed);
+        self.0.misses.store(0, AtomicOrdering::Relaxed);
+        self.0.prefill_after.store(after, AtomicOrdering::Relaxed)
+    }
+
+    /// Return the miss prefill settings `(after, count)`, or `None` if it's
+    /// off, see [set_miss_prefill](Self::set_miss_prefill)
+    pub fn miss_prefill(&self) -> Option<(usize, usize)> {
+        match self.0.prefill_after.load(AtomicOrdering::Relaxed) {
+            0 => None,
+            after => Some((after, self.0.prefill_count.load(AtomicOrdering::Relaxed))),
+        }
+    }
+
+    fn alloc(&self) -> T {
+        let mut t = RawPoolable::empty(self.downgrade());
+        let n = self.0.initial_capacity.load(AtomicOrdering::Relaxed);
+        if n > 0 {
+            // this is only a hint, if it fails the first use will try again
+            let _ = t.try_reserve(n);
+        }
+        t
+    }
+
+    fn evict(&self, t: T, reason: EvictReason) {
+        let capacity = t.capacity();
+        self.0.counters.evicted(capacity, reason, t.age());
+        self.0.retention.evicted();
+        self.0.evictions.notify(|| Eviction {
+            type_name: std::any::type_name::<T>(),
+            capacity,
+            reason,
+        });
+        RawPoolable::really_drop(t)
+    }
+
+    /// Call `hook` with every object the pool evicts, or remove the hook if
+    /// `None`.
+    ///
+    /// The hook runs on the thread that evicted the object, usually the one
+    /// dropping it, so it should be quick. See the [evict] module.
+    pub fn set_eviction_hook<F: Fn(&Eviction) + Send + Sync + 'static>(
+        &self,
+        hook: Option<F>,
+    ) {
+        self.0.evictions.set_hook(hook.map(|f| Arc::new(f) as _))
+    }
+
+    /// Start or stop counting what [retention_report](Self::retention_report)
+    /// reports, see the [report] module. Stopping forgets the counts.
+    pub fn set_track_retention(&self, track: bool) {
+        self.0.retention.set_active(track)
+    }
+
+    /// Return a summary of the pool's use since it started tracking
+    /// retention, or `None` if it isn't, see the [report] module.
+    pub fn retention_report(&self) -> Option<RetentionReport> {
+        self.0.retention.report(self.0.retention_report())
+    }
+
+    /// Call `hook` with the pool's [retention_report](Self::retention_report)
+    /// when it is dropped, or remove the hook if `None`. Setting a hook turns
+    /// on retention tracking.
+    pub fn set_retention_hook<F: Fn(&RetentionReport) + Send + Sync + 'static>(
+        &self,
+        hook: Option<F>,
+    ) {
+        self.0.retention.set_hook(hook.map(|f| Arc::new(f) as _))
+    }
+
+    /// Subscribe to the objects the pool evicts, see the [evict] module.
+    #[cfg(feature = "tokio")]
+    pub fn evictions(&self) -> evict::EvictionStream {
+        self.0.evictions.subscribe()
+    }
+
+    /// Return a snapshot of the pool's occupancy and usage, see the [stats]
+    /// module. Without the `stats` feature only the occupancy is filled in.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    /// pool.take().push(0);
+    /// let st = pool.stats();
+    /// assert_eq!(st.len, 1);
+    /// # #[cfg(feature = "stats")]
+    /// assert_eq!((st.counts.misses, st.counts.returns), (1, 1));
+    /// ```
+    ///
+    /// [stats]: crate::stats
+    pub fn stats(&self) -> PoolStats {
+        let mut stats = PoolStats {
+            len: self.len(),
+            capacity: self.capacity(),
+            max_elt_capacity: self.max_elt_capacity(),
+            retained_bytes: self.0.counters.retained_bytes(),
+            ..PoolStats::default()
+        };
+        self.0.counters.fill(&mut stats);
+        stats
     }
 
     /// Insert an object into the pool.
     ///
     /// The object may be dropped if the pool is at capacity or if the object
     /// has too much capacity.
-    pub fn insert(&self, mut t: T) {
-        let cap = t.capacity();
-        if cap > 0 && cap <= self.0.max_elt_capacity {
-            t.reset();
-            if let Err(t) = self.0.pool.push(t) {
-                RawPoolable::really_drop(t)
+    pub fn insert(&self, t: T) {
+        self.insert_inner(t, true)
+    }
+
+    /// Insert an object into the pool without resetting it.
+    ///
+    /// This is the global counterpart of [local::insert_raw], for protocols
+    /// that reset an object incrementally while using it, e.g. a buffer
+    /// that is consumed as it is read, and so can skip the reset. It
+    /// obeys the same limits as [insert](Self::insert).
+    ///
+    /// ```
+    /// use poolshark::global::{Pool, PoolControl};
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    /// let mut buf = pool.take();
+    /// buf.extend_from_slice(b"message");
+    /// while buf.pop().is_some() {} // consume it
+    /// unsafe { pool.insert_unreset(buf) }
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `t` must be in the state [reset](RawPoolable::reset) would leave it in,
+    /// see [ReusePolicy](crate::ReusePolicy). The thread local global pools
+    /// hand objects out again as any type with the same
+    /// [Discriminant](crate::Discriminant), so an object that isn't empty
+    /// may be read as the wrong type.
+    ///
+    /// [local::insert_raw]: crate::local::insert_raw
+    pub unsafe fn insert_unreset(&self, t: T) {
+        self.insert_inner(t, false)
+    }
+
+    /// Insert every object in `iter` into the pool, as if by calling
+    /// [insert](Self::insert) on each.
+    ///
+    /// Draining a collection of pooled objects into their pool this way
+    /// skips the pool lookup each of them would do when dropped, and once
+    /// the pool is full the rest are dropped without trying the queue.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::new(4, 1024);
+    /// let mut batch: Vec<_> = (0..8).map(|_| pool.take()).collect();
+    /// for s in &mut batch {
+    ///     s.push_str("message")
+    /// }
+    /// pool.insert_many(batch.drain(..));
+    /// assert_eq!(pool.len(), 4);
+    /// ```
+    pub fn insert_many<I: IntoIterator<Item = T>>(&self, iter: I) {
+        let mut full = false;
+        for mut t in iter {
+            let timer = Timer::start();
+            let res = if full {
+                if t.take_admitted() {
+                    self.released()
+                }
+                self.0.counters.returned(t.capacity());
+                Err((t, EvictReason::Full))
+            } else {
+                self.try_insert_inner(t, true)
+            };
+            let path = match res {
+                Ok(()) => Path::InsertPooled,
+                Err((t, reason)) => {
+                    full = reason == EvictReason::Full;
+                    self.evict(t, reason);
+                    Path::InsertDropped
+                }
+            };
+            self.0.counters.timed(path, timer)
+        }
+    }
+
+    fn insert_inner(&self, t: T, reset: bool) {
+        let timer = Timer::start();
+        let path = match self.try_insert_inner(t, reset) {
+            Ok(()) => Path::InsertPooled,
+            Err((t, reason)) => {
+                self.evict(t, reason);
+                Path::InsertDropped
             }
+        };
+        self.0.counters.timed(path, timer)
+    }
+
+    /// Try to insert an object into the pool, returning it if the pool won't
+    /// retain it.
+    ///
+    /// This is the same as [insert](Self::insert), except instead of really
+    /// dropping objects that are too big, or that don't fit, they are given
+    /// back so the caller can implement a fallback, such as inserting them
+    /// into a secondary pool. The returned object may already have been
+    /// reset. Note that dropping a returned `GPooled` or pooled `Arc` will
+    /// try to insert it into this pool again, use
+    /// [really_drop](RawPoolable::really_drop) to discard it.
+    pub fn try_insert(&self, t: T) -> Result<(), T> {
+        self.try_insert_inner(t, true).map_err(|(t, _)| t)
+    }
+
+    fn try_insert_inner(&self, mut t: T, reset: bool) -> Result<(), (T, EvictReason)> {
+        // cleared first, so an object that is given back, and inserted
+        // again when it is dropped, is released once
+        if t.take_admitted() {
+            self.released()
+        }
+        let cap = t.capacity();
+        self.0.counters.returned(cap);
+        if cap == 0 {
+            return Err((t, EvictReason::Empty));
+        }
+        let size = self.elt_size(&t);
+        if size > self.0.max_elt_capacity {
+            Err((t, EvictReason::Oversized))
+        } else if size < self.min_elt_capacity() {
+            Err((t, EvictReason::Undersized))
+        } else if self.is_frozen() {
+            Err((t, EvictReason::Frozen))
+        } else if is_stale(&t) {
+            Err((t, EvictReason::Stale))
+        } else if reset && t.try_reset().is_err() {
+            Err((t, EvictReason::ResetFailed))
+        } else if injected(T::pooled_type_name(), Fault::Full) {
+            Err((t, EvictReason::Full))
         } else {
-            RawPoolable::really_drop(t)
+            self.push(t)
+        }
+    }
+
+    /// Drop everything in the pool and stop retaining objects until
+    /// [thaw](Self::thaw) is called.
+    ///
+    /// While the pool is frozen take still works, but always allocates, and
+    /// every object given back to the pool is really dropped. This is useful
+    /// for releasing memory during quiet periods without destroying static
+    /// pools or invalidating objects that are still outstanding.
+    pub fn freeze(&self) {
+        self.0.frozen.store(true, AtomicOrdering::Relaxed);
+        while let Some(t) = self.pop() {
+            self.evict(t, EvictReason::Frozen)
         }
     }
 
+    /// Resume retaining objects after [freeze](Self::freeze).
+    pub fn thaw(&self) {
+        self.0.frozen.store(false, AtomicOrdering::Relaxed)
+    }
+
+    /// Return true if the pool is frozen
+    pub fn is_frozen(&self) -> bool {
+        self.0.frozen.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Serve takes that ask for more than `max_elt_capacity` with a newly
+    /// allocated orphan instead of a pooled object.
+    ///
+    /// Such an object would be evicted when it is returned anyway, so taking
+    /// a pooled buffer for it just throws a good buffer away. This affects
+    /// the takes that know the capacity up front, such as
+    /// [take_with_capacity](RawPool::take_with_capacity). It is off by
+    /// default.
+    pub fn set_bypass_oversized(&self, bypass: bool) {
+        self.0.bypass_oversized.store(bypass, AtomicOrdering::Relaxed)
+    }
+
+    /// Return true if oversized takes bypass the pool, see
+    /// [set_bypass_oversized](Self::set_bypass_oversized)
+    pub fn bypass_oversized(&self) -> bool {
+        self.0.bypass_oversized.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Attach a [trace span](trace) to every object taken from the pool,
+    /// from the take until it is returned. Does nothing without the
+    /// `tracing` feature. It is off by default.
+    pub fn set_trace_spans(&self, trace: bool) {
+        self.0.trace_spans.store(trace, AtomicOrdering::Relaxed)
+    }
+
+    /// Return true if objects taken from the pool get a trace span, see
+    /// [set_trace_spans](Self::set_trace_spans)
+    pub fn trace_spans(&self) -> bool {
+        self.0.trace_spans.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Compare the [deep size](crate::size_of) of returned objects, in
+    /// bytes, to `max_elt_capacity` and
+    /// [min_elt_capacity](Self::set_min_elt_capacity) instead of their
+    /// capacity. It is off by default.
+    pub fn set_deep_size(&self, measure: bool)
+    where
+        T: SizeOf,
+    {
+        let _ = self.0.deep_size.set(T::deep_size_of);
+        self.0.measure_deep.store(measure, AtomicOrdering::Relaxed)
+    }
+
+    /// Return true if the pool limits the deep size of objects, see
+    /// [set_deep_size](Self::set_deep_size)
+    pub fn deep_size(&self) -> bool {
+        self.0.measure_deep.load(AtomicOrdering::Relaxed)
+    }
+
+    // the size compared to the element capacity limits
+    fn elt_size(&self, t: &T) -> usize {
+        match self.0.deep_size.get() {
+            Some(f) if self.deep_size() => f(t),
+            _ => t.capacity(),
+        }
+    }
+
+    /// Don't pool objects with a capacity less than `min_elt_capacity`.
+    ///
+    /// Small objects are often cheaper to allocate than to recycle, and
+    /// when they are pooled alongside big ones they take the pooled big
+    /// ones' place, so takes that need room miss anyway. Small objects are
+    /// evicted when they are returned, and takes that know the capacity up
+    /// front, such as [take_with_capacity](RawPool::take_with_capacity),
+    /// serve requests for less with a newly allocated orphan. The default
+    /// is 0, pool everything.
+    pub fn set_min_elt_capacity(&self, min_elt_capacity: usize) {
+        self.0.min_elt_capacity.store(min_elt_capacity, AtomicOrdering::Relaxed)
+    }
+
+    /// Limit the number of objects taken from the pool and not yet returned
+    /// to `max`, or remove the limit if `max` is 0.
+    ///
+    /// Once `max` objects are out, take returns new orphans, which are
+    /// never returned to the pool, and try_take returns `None`. A shared
+    /// pool can use this to stop one subsystem that holds on to its objects
+    /// from draining the pool the others depend on. Objects taken before
+    /// the limit was set aren't counted. Off by default.
+    pub fn set_max_takers(&self, max: usize) {
+        self.0.max_takers.store(max, AtomicOrdering::Relaxed)
+    }
+
+    /// Return the limit on outstanding objects, or `None` if there isn't
+    /// one, see [set_max_takers](Self::set_max_takers)
+    pub fn max_takers(&self) -> Option<usize> {
+        match self.0.max_takers.load(AtomicOrdering::Relaxed) {
+            0 => None,
+            max => Some(max),
+        }
+    }
+
+    /// Return the number of objects counted against the
+    /// [max_takers](Self::set_max_takers) limit
+    pub fn takers(&self) -> usize {
+        self.0.takers.load(AtomicOrdering::Relaxed)
+    }
+
+    // count a take against the taker limit, returning None if it's
+    // reached, and whether the take was counted otherwise
+    fn admit(&self) -> Option<bool> {
+        match self.0.max_takers.load(AtomicOrdering::Relaxed) {
+            0 => Some(false),
+            max => self
+                .0
+                .takers
+                .fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |n| {
+                    (n < max).then_some(n + 1)
+                })
+                .ok()
+                .map(|_| true),
+        }
+    }
+
+    // mark an object whose take admit counted, so only it gives the count
+    // back. Objects that can't carry the mark aren't counted.
+    fn admitted(&self, t: &mut T) {
+        if !t.set_admitted() {
+            self.released()
+        }
+    }
+
+    // an object counted by admit is no longer out
+    fn released(&self) {
+        let _ = self.0.takers.fetch_update(
+            AtomicOrdering::Relaxed,
+            AtomicOrdering::Relaxed,
+            |n| n.checked_sub(1),
+        );
+    }
+
+    /// The number of objects currently in the pool
+    pub fn len(&self) -> usize {
+        self.0.pool.len()
+    }
+
+    /// The order the pool hands out its objects in, FIFO for the default
+    /// queue, see [new_lifo](Self::new_lifo)
+    pub fn order(&self) -> backend::TakeOrder {
+        self.0.pool.order()
+    }
+
+    /// Return true if there are no objects in the pool
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of objects the pool will retain
+    pub fn capacity(&self) -> usize {
+        self.0.pool.capacity()
+    }
+
+    /// Change the number of objects the pool retains to `max_capacity`.
+    ///
+    /// This swaps in a new queue and moves the pooled objects over, if
+    /// there are more than fit the rest are evicted as if the pool were
+    /// full. Handles to the pool, and objects taken from it, keep working
+    /// throughout, so a long running service can grow or shrink its pools
+    /// with the load. Takes and inserts wait while the objects are moved,
+    /// so it is meant to be done occasionally, not on every request.
+    ///
+    /// Only pools built by [new_resizable](Self::new_resizable) can be
+    /// resized, return false, and change nothing, for any other pool.
+    ///
+    /// # Panics
+    ///
+    /// If `max_capacity` is 0, like [new](Self::new)
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new_resizable(2, 1024);
+    /// assert!(pool.resize(64));
+    /// assert_eq!(pool.capacity(), 64);
+    /// ```
+    pub fn resize(&self, max_capacity: usize) -> bool {
+        let Some(excess) = self.0.pool.resize(max_capacity) else { return false };
+        for t in excess {
+            self.unretain(&t);
+            self.evict(t, EvictReason::Full)
+        }
+        true
+    }
+
+    /// Return the capacity above which objects aren't pooled
+    pub fn max_elt_capacity(&self) -> usize {
+        self.0.max_elt_capacity
+    }
+
+    /// Return the capacity below which objects aren't pooled, see
+    /// [set_min_elt_capacity](Self::set_min_elt_capacity)
+    pub fn min_elt_capacity(&self) -> usize {
+        self.0.min_elt_capacity.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Reserve room for `capacity` elements in objects newly allocated by
+    /// take when the pool is empty, so cold start objects are born the
+    /// right size. The default is 0, allocate empty objects.
+    pub fn set_initial_capacity(&self, capacity: usize) {
+        self.0.initial_capacity.store(capacity, AtomicOrdering::Relaxed)
+    }
+
+    /// Return the initial capacity of newly allocated objects, see
+    /// [set_initial_capacity](Self::set_initial_capacity)
+    pub fn initial_capacity(&self) -> usize {
+        self.0.initial_capacity.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Record which threads take objects from and return objects to the
+    /// pool, see [PoolStats::threads] and [PoolStats::same_thread_ratio].
+    ///
+    /// This takes a lock on every take and insert, so it is off by default,
+    /// and it does nothing without the `stats` feature. Turning it off
+    /// forgets what was recorded.
+    pub fn set_track_threads(&self, track: bool) {
+        self.0.counters.set_track_threads(track)
+    }
+
+    /// Return true if the pool records which threads use it, see
+    /// [set_track_threads](Self::set_track_threads). Always false without
+    /// the `stats` feature.
+    pub fn track_threads(&self) -> bool {
+        self.0.counters.track_threads()
+    }
+
+    /// Set which objects [prune](Self::prune) throws away first.
+    pub fn set_prune_mode(&self, mode: PruneMode) {
+        self.0.prune_mode.store(mode as u8, AtomicOrdering::Relaxed)
+    }
+
+    /// Return which objects [prune](Self::prune) throws away first.
+    pub fn prune_mode(&self) -> PruneMode {
+        PruneMode::from_u8(self.0.prune_mode.load(AtomicOrdering::Relaxed))
+    }
+
     /// Throw away some pooled objects to reduce memory usage.
     ///
     /// If the number of pooled objects is > 10% of the capacity then throw away 10%
     /// of the capacity. Otherwise throw away 1% of the capacity. Always throw away
-    /// at least 1 object until the pool is empty.
+    /// at least 1 object until the pool is empty. Which objects are thrown away
+    /// is decided by the pool's [PruneMode].
     pub fn prune(&self) {
+        self.prune_n(self.prune_count())
+    }
+
+    /// Really drop every object currently in the pool.
+    ///
+    /// Unlike [prune](Self::prune) this empties the pool at once, and
+    /// unlike [freeze](Self::freeze) the pool goes on retaining objects
+    /// returned to it afterwards. This is useful to release the memory
+    /// built up by a large transient workload, and to start tests from an
+    /// empty pool.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    /// pool.prefill(16);
+    /// pool.clear();
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn clear(&self) {
+        while let Some(t) = self.pop() {
+            self.evict(t, EvictReason::Cleared)
+        }
+    }
+
+    // the number of objects prune throws away
+    fn prune_count(&self) -> usize {
         let len = self.0.pool.len();
         let ten_percent = std::cmp::max(1, self.0.pool.capacity() / 10);
         let one_percent = std::cmp::max(1, ten_percent / 10);
         if len > ten_percent {
-            for _ in 0..ten_percent {
-                if let Some(v) = self.0.pool.pop() {
-                    RawPoolable::really_drop(v)
+            ten_percent
+        } else if len > one_percent {
+            one_percent
+        } else {
+            std::cmp::min(len, 1)
+        }
+    }
+
+    /// Throw away the objects [prune](Self::prune) would, but from a
+    /// background task, `per_second` objects per second.
+    ///
+    /// Really dropping thousands of large buffers at once can stall the
+    /// allocator long enough to show up as a latency spike. This spreads
+    /// the same work out over time. The pool can be used as normal while
+    /// the task runs, and the task stops early if the pool is empty or
+    /// dropped. Await or abort the returned handle to wait for it or to
+    /// stop it.
+    ///
+    /// ```
+    /// use poolshark::global::{Pool, PoolControl};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let pool: Pool<Vec<u8>> = Pool::new(100, 4096);
+    /// let bufs: Vec<_> = (0..100).map(|_| pool.take_with_capacity(4096)).collect();
+    /// drop(bufs);
+    /// pool.prune_gradually(1000).await.unwrap(); // 10 buffers over 10ms
+    /// assert_eq!(pool.len(), 90);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If called outside a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn prune_gradually(&self, per_second: u32) -> tokio::task::JoinHandle<()>
+    where
+        T: Send + 'static,
+    {
+        use std::time::Duration;
+        use tokio::time::{self, MissedTickBehavior};
+        let per_second = per_second.max(1) as u64;
+        // tick at most every millisecond, evicting enough per tick to keep
+        // up with the rate
+        let per_tick = per_second.div_ceil(1000) as usize;
+        let period = Duration::from_nanos(1_000_000_000 * per_tick as u64 / per_second);
+        let mut remaining = self.prune_count();
+        let pool = self.downgrade();
+        tokio::task::spawn(async move {
+            let mut ticks = time::interval(period);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            while remaining > 0 {
+                ticks.tick().await;
+                let Some(pool) = pool.upgrade() else { break };
+                if pool.0.pool.len() == 0 {
+                    break;
                 }
+                let n = std::cmp::min(per_tick, remaining);
+                pool.prune_n(n);
+                remaining -= n;
             }
-        } else if len > one_percent {
-            for _ in 0..one_percent {
-                if let Some(v) = self.0.pool.pop() {
-                    RawPoolable::really_drop(v)
+        })
+    }
+
+    fn prune_n(&self, n: usize) {
+        match self.prune_mode() {
+            PruneMode::Oldest => {
+                for _ in 0..n {
+                    if let Some(v) = self.pop() {
+                        self.evict(v, EvictReason::Pruned)
+                    }
                 }
             }
-        } else if len > 0 {
-            if let Some(v) = self.0.pool.pop() {
-                RawPoolable::really_drop(v)
+            PruneMode::Largest => self.prune_largest(n),
+        }
+    }
+
+    fn prune_largest(&self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let len = self.0.pool.len();
+        let mut all = Vec::with_capacity(len);
+        while all.len() < len {
+            match self.pop() {
+                Some(t) => all.push(t),
+                None => break,
+            }
+        }
+        all.sort_by_key(|t| std::cmp::Reverse(t.allocated_bytes()));
+        let n = std::cmp::min(n, all.len());
+        for t in all.drain(..n) {
+            self.evict(t, EvictReason::Pruned)
+        }
+        // put the survivors back smallest first, so they keep their relative
+        // age as well as we can manage
+        while let Some(t) = all.pop() {
+            if let Err((t, reason)) = self.push(t) {
+                self.evict(t, reason)
             }
         }
     }
 }
+
+impl<T: Poolable> RawPool<GPooled<T>> {
+    /// Takes an item with room for at least `capacity` elements from the pool.
+    ///
+    /// Creates a new item if none are available. The capacity is reserved with
+    /// fallible allocation, so services that must degrade gracefully get an
+    /// error instead of aborting when the allocator fails.
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn try_take_or_alloc(&self, capacity: usize) -> Result<GPooled<T>, AllocError> {
+        self.try_take_or_alloc_at(capacity, Location::caller())
+    }
+
+    fn try_take_or_alloc_at(
+        &self,
+        capacity: usize,
+        site: &'static Location<'static>,
+    ) -> Result<GPooled<T>, AllocError> {
+        let mut t = if (capacity > self.0.max_elt_capacity && self.bypass_oversized())
+            || capacity < self.min_elt_capacity()
+        {
+            GPooled::orphan(T::empty())
+        } else {
+            // nothing pooled can have room for more than max_elt_capacity
+            let min = if capacity <= self.0.max_elt_capacity { capacity } else { 0 };
+            self.take_maybe_pop(true, min, || self.alloc(), site).0
+        };
+        Poolable::try_reserve(&mut *t, capacity)?;
+        Ok(t)
+    }
+
+    /// Takes an item with room for at least `capacity` elements from the pool.
+    ///
+    /// The first few pooled items are checked for one that already has
+    /// enough room, so a pool holding a mix of small and large items doesn't
+    /// hand out a small one only for it to be reallocated at once. The
+    /// items passed over stay in the pool. If none of them is big enough a
+    /// new item is allocated with room for `capacity`.
+    ///
+    /// If the pool is set to [bypass oversized](RawPool::set_bypass_oversized)
+    /// takes and `capacity` is more than the pool will retain, or if
+    /// `capacity` is less than the pool's
+    /// [min_elt_capacity](RawPool::set_min_elt_capacity), the item is a new
+    /// orphan.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 1 << 20);
+    /// let (small, big) = (pool.take_with_capacity(16), pool.take_with_capacity(4096));
+    /// drop(small);
+    /// drop(big);
+    /// let v = pool.take_with_capacity(4096);
+    /// assert!(v.capacity() >= 4096);
+    /// assert_eq!(pool.len(), 1); // the small one is still pooled
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the allocation fails, use [try_take_or_alloc](Self::try_take_or_alloc)
+    /// to handle that.
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take_with_capacity(&self, capacity: usize) -> GPooled<T> {
+        match self.try_take_or_alloc(capacity) {
+            Ok(t) => t,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Takes an item from the pool, or calls `f` to make one if it's empty.
+    ///
+    /// `f` only runs on a miss, so it can afford expensive priming, such as
+    /// `Vec::with_capacity(64 * 1024)`, that would be wasted on a reused
+    /// object. Its result belongs to this pool like any other item.
+    ///
+    /// ```
+    /// use poolshark::global::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 64 * 1024);
+    /// let v = pool.take_or_default_with(|| Vec::with_capacity(64 * 1024));
+    /// assert!(v.capacity() >= 64 * 1024);
+    /// ```
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take_or_default_with<F: FnOnce() -> T>(&self, f: F) -> GPooled<T> {
+        let site = Location::caller();
+        self.take_or_else(|| GPooled::from_parts(self.downgrade(), f()), site)
+    }
+
+    /// Attach `t` to this pool if it is an orphan, and return it.
+    ///
+    /// Library code that accepts `GPooled` values can't know how its
+    /// callers made them, an orphan made with [GPooled::orphan] or `From`,
+    /// or one whose pool is gone, is really dropped instead of recycled.
+    /// Adopting such values at the API boundary guarantees they end up in
+    /// this pool. Objects that belong to a live pool are left alone, see
+    /// [GPooled::ensure_attached].
+    ///
+    /// ```
+    /// use poolshark::global::{GPooled, Pool, PoolControl};
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    /// let buf = pool.adopt_orphan(GPooled::from(vec![1, 2, 3]));
+    /// assert!(!buf.is_orphan());
+    /// drop(buf);
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    pub fn adopt_orphan(&self, mut t: GPooled<T>) -> GPooled<T> {
+        t.ensure_attached(self);
+        t
+    }
+}
+
+/// Type erased control of a global pool.
+///
+/// Every [RawPool] implements this trait, so management code can hold pools of
+/// many different element types, e.g. in a `Vec<Box<dyn PoolControl>>`, and
+/// prune, clear, or inspect all of them uniformly.
+///
+/// ```
+/// use poolshark::global::{Pool, PoolControl};
+///
+/// let strings: Pool<String> = Pool::new(1024, 4096);
+/// let vecs: Pool<Vec<u64>> = Pool::new(64, 1024);
+/// let pools: Vec<Box<dyn PoolControl>> = vec![Box::new(strings), Box::new(vecs)];
+/// for p in &pools {
+///     p.prune();
+///     println!("{}: {:?}", p.type_name(), p.stats())
+/// }
+/// ```
+pub trait PoolControl {
+    /// The name of the pooled type, for display purposes
+    fn type_name(&self) -> &'static str;
+
+    /// Throw away some pooled objects, see [RawPool::prune]
+    fn prune(&self);
+
+    /// Really drop every object currently in the pool, see
+    /// [RawPool::clear]
+    fn clear(&self);
+
+    /// Drop everything in the pool and stop retaining objects, see
+    /// [RawPool::freeze]
+    fn freeze(&self);
+
+    /// Resume retaining objects, see [RawPool::thaw]
+    fn thaw(&self);
+
+    /// The number of objects currently in the pool
+    fn len(&self) -> usize;
+
+    /// Return true if there are no objects in the pool
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return a snapshot of the pool's statistics
+    fn stats(&self) -> PoolStats;
+
+    /// Estimated heap memory, in bytes, owned by the pooled objects, only
+    /// counted with the `stats` feature, 0 otherwise
+    fn retained_bytes(&self) -> usize;
+
+    /// Return the pool's retention report, if it is tracking retention, see
+    /// [RawPool::retention_report]
+    fn retention_report(&self) -> Option<RetentionReport> {
+        None
+    }
+}
+
+impl<T: RawPoolable> PoolControl for RawPool<T> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn prune(&self) {
+        RawPool::prune(self)
+    }
+
+    fn clear(&self) {
+        RawPool::clear(self)
+    }
+
+    fn freeze(&self) {
+        RawPool::freeze(self)
+    }
+
+    fn thaw(&self) {
+        RawPool::thaw(self)
+    }
+
+    fn len(&self) -> usize {
+        RawPool::len(self)
+    }
+
+    fn stats(&self) -> PoolStats {
+        RawPool::stats(self)
+    }
+
+    fn retained_bytes(&self) -> usize {
+        self.0.counters.retained_bytes()
+    }
+
+    fn retention_report(&self) -> Option<RetentionReport> {
+        RawPool::retention_report(self)
+    }
+}
@@ -0,0 +1,215 @@
+//! The bounded queue backing every [RawPool](super::RawPool).
+//!
+//! Normally this is a lock-free crossbeam [ArrayQueue]. When the
+//! `deterministic` feature is enabled, or the crate is built with
+//! `RUSTFLAGS="--cfg loom"`, it is replaced by a mutex protected `VecDeque`
+//! with the same FIFO semantics. Under loom the mutex is loom's, so the pool
+//! logic can be model checked, and in either case every operation happens in a
+//! well defined order, so unit tests of unsafe [RawPoolable](crate::RawPoolable)
+//! implementations can be stepped through deterministically.
+//!
+//! Either way a pool can instead be built on a user provided
+//! [PoolBackend].
+//!
+//! A pool built with a resizable queue can be resized, by swapping in a
+//! new queue and moving the objects over, see [Queue::resize]. That queue
+//! is behind a lock, so it is opt in.
+use super::backend::{PoolBackend, TakeOrder};
+use std::{
+    fmt,
+    sync::{RwLock, RwLockReadGuard},
+};
+
+#[cfg(not(any(loom, feature = "deterministic")))]
+mod imp {
+    use crossbeam_queue::ArrayQueue;
+
+    #[derive(Debug)]
+    pub(crate) struct Queue<T>(ArrayQueue<T>);
+
+    impl<T> Queue<T> {
+        pub(crate) fn new(cap: usize) -> Self {
+            Self(ArrayQueue::new(cap))
+        }
+
+        #[inline(always)]
+        pub(crate) fn push(&self, t: T) -> Result<(), T> {
+            self.0.push(t)
+        }
+
+        #[inline(always)]
+        pub(crate) fn pop(&self) -> Option<T> {
+            self.0.pop()
+        }
+
+        pub(crate) fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        pub(crate) fn capacity(&self) -> usize {
+            self.0.capacity()
+        }
+    }
+}
+
+#[cfg(any(loom, feature = "deterministic"))]
+mod imp {
+    #[cfg(loom)]
+    use loom::sync::Mutex;
+    use std::collections::VecDeque;
+    #[cfg(not(loom))]
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    pub(crate) struct Queue<T> {
+        cap: usize,
+        inner: Mutex<VecDeque<T>>,
+    }
+
+    impl<T> Queue<T> {
+        pub(crate) fn new(cap: usize) -> Self {
+            // same as ArrayQueue
+            assert!(cap > 0, "capacity must be non-zero");
+            Self { cap, inner: Mutex::new(VecDeque::with_capacity(cap)) }
+        }
+
+        pub(crate) fn push(&self, t: T) -> Result<(), T> {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.len() >= self.cap {
+                Err(t)
+            } else {
+                inner.push_back(t);
+                Ok(())
+            }
+        }
+
+        pub(crate) fn pop(&self) -> Option<T> {
+            self.inner.lock().unwrap().pop_front()
+        }
+
+        pub(crate) fn len(&self) -> usize {
+            self.inner.lock().unwrap().len()
+        }
+
+        pub(crate) fn capacity(&self) -> usize {
+            self.cap
+        }
+    }
+}
+
+// the builtin queue is the common case, so it stays inline. A resizable
+// queue is behind a lock so it can be swapped out, operations only take
+// the read side, which is uncontended except while a resize is in
+// progress, but it is still a shared write, so only pools that ask for it
+// pay for it.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Queue<T> {
+    Builtin(imp::Queue<T>),
+    Resizable(RwLock<imp::Queue<T>>),
+    Custom(Box<dyn PoolBackend<T>>),
+}
+
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Builtin(q) => q.fmt(f),
+            Self::Resizable(q) => read(q).fmt(f),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+// nothing can panic while holding the lock and leave the queue broken
+#[inline(always)]
+fn read<T>(q: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    q.read().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<T> Queue<T> {
+    pub(crate) fn new(cap: usize) -> Self {
+        Self::Builtin(imp::Queue::new(cap))
+    }
+
+    pub(crate) fn resizable(cap: usize) -> Self {
+        Self::Resizable(RwLock::new(imp::Queue::new(cap)))
+    }
+
+    pub(crate) fn custom<B: PoolBackend<T> + 'static>(backend: B) -> Self {
+        Self::Custom(Box::new(backend))
+    }
+
+    #[inline(always)]
+    pub(crate) fn push(&self, t: T) -> Result<(), T> {
+        match self {
+            Self::Builtin(q) => q.push(t),
+            Self::Resizable(q) => read(q).push(t),
+            Self::Custom(q) => q.push(t),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn pop(&self) -> Option<T> {
+        match self {
+            Self::Builtin(q) => q.pop(),
+            Self::Resizable(q) => read(q).pop(),
+            Self::Custom(q) => q.pop(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Builtin(q) => q.len(),
+            Self::Resizable(q) => read(q).len(),
+            Self::Custom(q) => q.len(),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            Self::Builtin(q) => q.capacity(),
+            Self::Resizable(q) => read(q).capacity(),
+            Self::Custom(q) => q.capacity(),
+        }
+    }
+
+    pub(crate) fn order(&self) -> TakeOrder {
+        match self {
+            Self::Builtin(_) | Self::Resizable(_) => TakeOrder::Fifo,
+            Self::Custom(q) => q.order(),
+        }
+    }
+
+    /// A new empty queue like this one, see [PoolBackend::clone_empty]
+    pub(crate) fn clone_empty(&self) -> Self {
+        match self {
+            Self::Custom(q) => match q.clone_empty() {
+                Some(q) => Self::Custom(q),
+                None => Self::new(q.capacity()),
+            },
+            Self::Builtin(q) => Self::new(q.capacity()),
+            Self::Resizable(q) => Self::resizable(read(q).capacity()),
+        }
+    }
+
+    /// Replace a resizable queue with one holding up to `cap` objects, and
+    /// move the objects over. The old queue is freed before returning, and
+    /// the objects that didn't fit are returned, to be disposed of outside
+    /// the lock. Return None, and change nothing, if the queue isn't
+    /// resizable.
+    pub(crate) fn resize(&self, cap: usize) -> Option<Vec<T>> {
+        let Self::Resizable(q) = self else { return None };
+        let new = imp::Queue::new(cap);
+        let mut excess = Vec::new();
+        // the write lock waits out every push and pop on the old queue, so
+        // nothing can be left behind in it, and takers never see it half
+        // moved
+        let mut q = q.write().unwrap_or_else(|e| e.into_inner());
+        let old = std::mem::replace(&mut *q, new);
+        while let Some(t) = old.pop() {
+            if let Err(t) = q.push(t) {
+                excess.push(t)
+            }
+        }
+        Some(excess)
+    }
+}
@@ -0,0 +1,133 @@
+//! A process wide registry of named global pools.
+//!
+//! Registering a pool lets management code, such as a periodic pruning task
+//! or a stats endpoint, find every pool in the program without each module
+//! having to export its pools. The registry only holds weak references, so
+//! registering a pool doesn't keep it alive, and dead pools are forgotten
+//! the next time the registry is listed.
+//!
+//! Pools declared with [static_pool!](crate::static_pool) are registered
+//! automatically the first time they are used.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{registry, Pool};
+//!
+//! let strings: Pool<String> = Pool::new(1024, 4096);
+//! registry::register("strings", &strings);
+//! for (name, pool) in registry::pools() {
+//!     pool.prune();
+//!     println!("{name}: {:?}", pool.stats())
+//! }
+//! ```
+use super::{report::RetentionReport, PoolControl, RawPool, RawPoolable, WeakPool};
+use std::sync::{LazyLock, Mutex};
+
+trait Upgrade: Send + Sync {
+    fn upgrade(&self) -> Option<Box<dyn PoolControl>>;
+}
+
+impl<T: RawPoolable + Send + 'static> Upgrade for WeakPool<T> {
+    fn upgrade(&self) -> Option<Box<dyn PoolControl>> {
+        WeakPool::upgrade(self).map(|p| Box::new(p) as Box<dyn PoolControl>)
+    }
+}
+
+type Entry = (&'static str, Box<dyn Upgrade>);
+
+static REGISTRY: LazyLock<Mutex<Vec<Entry>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Register `pool` under `name`.
+///
+/// Names don't have to be unique, but they are what reports identify pools
+/// by, so it's best if they are.
+pub fn register<T: RawPoolable + Send + 'static>(name: &'static str, pool: &RawPool<T>) {
+    REGISTRY.lock().unwrap().push((name, Box::new(pool.downgrade())))
+}
+
+/// Return all the registered pools that are still alive, in the order they
+/// were registered.
+pub fn pools() -> Vec<(&'static str, Box<dyn PoolControl>)> {
+    let mut reg = REGISTRY.lock().unwrap();
+    let mut res = Vec::with_capacity(reg.len());
+    reg.retain(|(name, w)| match w.upgrade() {
+        Some(p) => {
+            res.push((*name, p));
+            true
+        }
+        None => false,
+    });
+    res
+}
+
+/// Return the [retention reports](super::report) of all the registered pools
+/// that are tracking retention.
+///
+/// Pools that live until the program exits are never dropped, so call this
+/// on the way out, e.g. at the end of main, to audit them.
+pub fn retention_reports() -> Vec<(&'static str, RetentionReport)> {
+    pools()
+        .into_iter()
+        .filter_map(|(name, pool)| pool.retention_report().map(|r| (name, r)))
+        .collect()
+}
+
+/// Return the registered pool named `name`, if it is still alive
+pub fn get(name: &str) -> Option<Box<dyn PoolControl>> {
+    let reg = REGISTRY.lock().unwrap();
+    reg.iter().filter(|(n, _)| *n == name).find_map(|(_, w)| w.upgrade())
+}
+
+/// Declare a named, lazily initialized, registered global pool.
+///
+/// This expands to a `static NAME: LazyLock<Pool<T>>`, optionally with the
+/// maximum pool size and maximum element capacity (both default to 1024),
+/// and optionally a typed accessor function returning `&'static Pool<T>`.
+/// The pool is registered with the [registry](crate::global::registry)
+/// under its module path and name the first time it is used.
+///
+/// ```
+/// use poolshark::static_pool;
+///
+/// static_pool! {
+///     /// Strings for outgoing messages
+///     pub static STRINGS: String = (1024, 4096);
+///     pub fn strings;
+/// }
+///
+/// static_pool!(static BUFS: Vec<u8>);
+///
+/// let mut s = strings().take();
+/// s.push_str("hello");
+/// let b = BUFS.take();
+/// ```
+#[macro_export]
+macro_rules! static_pool {
+    (
+        $(#[$meta:meta])*
+        $vis:vis static $name:ident: $t:ty $(= ($max:expr, $max_elt:expr))?
+        $(; $fvis:vis fn $accessor:ident)? $(;)?
+    ) => {
+        $(#[$meta])*
+        $vis static $name: ::std::sync::LazyLock<$crate::global::Pool<$t>> =
+            ::std::sync::LazyLock::new(|| {
+                #[allow(unused_mut, unused_assignments)]
+                let mut sizes: (usize, usize) = (1024, 1024);
+                $(sizes = ($max, $max_elt);)?
+                let pool = $crate::global::Pool::<$t>::new(sizes.0, sizes.1);
+                $crate::global::registry::register(
+                    ::std::concat!(::std::module_path!(), "::", ::std::stringify!($name)),
+                    &pool,
+                );
+                pool
+            });
+
+        $(
+            #[doc = ::std::concat!("Return the [", ::std::stringify!($name), "] pool")]
+            $fvis fn $accessor() -> &'static $crate::global::Pool<$t> {
+                &$name
+            }
+        )?
+    };
+}
@@ -0,0 +1,186 @@
+//! Reports on whether a pool earns the memory it retains.
+//!
+//! A pool that is tracking retention counts its takes, hits, evictions and
+//! peak length, which is enough to audit whether it is worth keeping. Turn
+//! it on with [set_track_retention](super::RawPool::set_track_retention),
+//! and read the [RetentionReport] with
+//! [retention_report](super::RawPool::retention_report), or have it handed
+//! to a hook when the pool is dropped with
+//! [set_retention_hook](super::RawPool::set_retention_hook).
+//!
+//! Pools that live for the whole program, e.g. in a static, are never
+//! dropped, so register them and call
+//! [registry::retention_reports](super::registry::retention_reports) on the
+//! way out instead.
+//!
+//! Pools that aren't tracking retention pay one relaxed atomic load per
+//! take, insert and eviction.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{report::RetentionReport, Pool};
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+//! pool.set_retention_hook(Some(|r: &RetentionReport| eprintln!("{r}")));
+//! for _ in 0..4 {
+//!     let mut v = pool.take();
+//!     v.push(0);
+//! }
+//! assert_eq!(pool.retention_report().unwrap().hit_rate(), Some(0.75));
+//! drop(pool); // prints the report
+//! ```
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+};
+
+/// A summary of how much a pool was used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// The name of the pooled type
+    pub type_name: &'static str,
+    /// The maximum number of objects the pool will retain
+    pub capacity: usize,
+    /// Objects with a capacity larger than this are not retained
+    pub max_elt_capacity: usize,
+    /// The most objects the pool held at once
+    pub peak_len: usize,
+    /// The number of takes, including try_takes that found the pool empty
+    pub takes: usize,
+    /// The number of takes that got a pooled object
+    pub hits: usize,
+    /// The number of objects the pool threw away
+    pub evictions: usize,
+    /// Estimated heap memory, in bytes, owned by the pooled objects
+    pub retained_bytes: usize,
+}
+
+impl RetentionReport {
+    /// The fraction of takes that got a pooled object, or `None` if nothing
+    /// was taken
+    pub fn hit_rate(&self) -> Option<f64> {
+        if self.takes == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / self.takes as f64)
+        }
+    }
+}
+
+impl fmt::Display for RetentionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: peak {}/{}, {} takes, ",
+            self.type_name, self.peak_len, self.capacity, self.takes
+        )?;
+        match self.hit_rate() {
+            Some(r) => write!(f, "{:.1}% hit", r * 100.)?,
+            None => write!(f, "no hits")?,
+        }
+        write!(f, ", {} evicted, {} bytes retained", self.evictions, self.retained_bytes)
+    }
+}
+
+pub(crate) type Hook = Arc<dyn Fn(&RetentionReport) + Send + Sync>;
+
+/// The retention counters and drop hook of one pool
+pub(crate) struct Tracker {
+    active: AtomicBool,
+    hook: RwLock<Option<Hook>>,
+    takes: AtomicUsize,
+    hits: AtomicUsize,
+    evictions: AtomicUsize,
+    peak_len: AtomicUsize,
+}
+
+impl fmt::Debug for Tracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracker").field("active", &self.active).finish_non_exhaustive()
+    }
+}
+
+impl Tracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            active: AtomicBool::new(false),
+            hook: RwLock::new(None),
+            takes: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+            peak_len: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_active(&self, active: bool) {
+        if !active {
+            for n in [&self.takes, &self.hits, &self.evictions, &self.peak_len] {
+                n.store(0, Ordering::Relaxed)
+            }
+        }
+        self.active.store(active, Ordering::Relaxed)
+    }
+
+    pub(crate) fn hook(&self) -> Option<Hook> {
+        self.hook.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_hook(&self, hook: Option<Hook>) {
+        if hook.is_some() {
+            self.set_active(true)
+        }
+        *self.hook.write().unwrap() = hook
+    }
+
+    pub(crate) fn taken(&self, hit: bool) {
+        if self.is_active() {
+            self.takes.fetch_add(1, Ordering::Relaxed);
+            if hit {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn pushed<F: FnOnce() -> usize>(&self, len: F) {
+        if self.is_active() {
+            self.peak_len.fetch_max(len(), Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn evicted(&self) {
+        if self.is_active() {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Fill in the counted fields of `report`, or return `None` if the pool
+    /// isn't tracking retention
+    pub(crate) fn report(&self, mut report: RetentionReport) -> Option<RetentionReport> {
+        if !self.is_active() {
+            return None;
+        }
+        report.peak_len = self.peak_len.load(Ordering::Relaxed);
+        report.takes = self.takes.load(Ordering::Relaxed);
+        report.hits = self.hits.load(Ordering::Relaxed);
+        report.evictions = self.evictions.load(Ordering::Relaxed);
+        Some(report)
+    }
+
+    /// Call the hook, if any, with the final report of a dropped pool
+    pub(crate) fn dropped(&self, report: RetentionReport) {
+        let hook = self.hook.read().unwrap().clone();
+        if let Some(hook) = hook
+            && let Some(report) = self.report(report)
+        {
+            hook(&report)
+        }
+    }
+}
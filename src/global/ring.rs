@@ -0,0 +1,127 @@
+//! A single producer single consumer ring of pooled frames.
+//!
+//! This packages the batching pattern from the global example. Each frame is
+//! copied into a segment, a `Vec<u8>` taken from a global pool, and the
+//! segment is passed to the consumer through a bounded queue. When the
+//! consumer drops a frame its segment goes back to the pool, so once the
+//! working set is allocated moving frames between threads doesn't allocate.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{ring, Pool};
+//! use std::thread;
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(64, 64 * 1024);
+//! let (mut tx, mut rx) = ring::channel(16, &pool);
+//! let producer = thread::spawn(move || {
+//!     for i in 0..100u8 {
+//!         while tx.push_frame(&[i; 4]).is_err() {
+//!             thread::yield_now()
+//!         }
+//!     }
+//! });
+//! let mut n = 0;
+//! while n < 100 {
+//!     match rx.pop_frame() {
+//!         Some(frame) => {
+//!             assert_eq!(&*frame, &[n; 4]);
+//!             n += 1;
+//!         } // the segment goes back to the pool here
+//!         None => thread::yield_now(),
+//!     }
+//! }
+//! producer.join().unwrap();
+//! ```
+use super::{queue::Queue, GPooled, Pool};
+use std::sync::Arc;
+
+type Segment = GPooled<Vec<u8>>;
+
+/// Create a ring that holds up to `capacity` frames in flight, with segments
+/// taken from `pool`.
+pub fn channel(capacity: usize, pool: &Pool<Vec<u8>>) -> (FrameProducer, FrameConsumer) {
+    let queue = Arc::new(Queue::new(capacity));
+    let tx = FrameProducer { queue: Arc::clone(&queue), pool: pool.clone() };
+    (tx, FrameConsumer { queue })
+}
+
+/// The sending half of a frame ring
+#[derive(Debug)]
+pub struct FrameProducer {
+    queue: Arc<Queue<Segment>>,
+    pool: Pool<Vec<u8>>,
+}
+
+impl FrameProducer {
+    /// Take an empty segment from the pool.
+    ///
+    /// Use this with [push](Self::push) to build a frame in place instead of
+    /// copying it.
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn segment(&self) -> Segment {
+        self.pool.take()
+    }
+
+    /// Send a segment to the consumer.
+    ///
+    /// If the ring is full the segment is returned.
+    pub fn push(&mut self, segment: Segment) -> Result<(), Segment> {
+        self.queue.push(segment)
+    }
+
+    /// Copy `frame` into a segment and send it to the consumer.
+    ///
+    /// If the ring is full the filled segment is returned, and can be retried
+    /// with [push](Self::push) without copying the frame again.
+    pub fn push_frame(&mut self, frame: &[u8]) -> Result<(), Segment> {
+        let mut segment = self.segment();
+        segment.extend_from_slice(frame);
+        self.push(segment)
+    }
+
+    /// The number of frames waiting to be consumed
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return true if no frames are waiting to be consumed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return true if the consumer has been dropped
+    pub fn is_disconnected(&self) -> bool {
+        Arc::strong_count(&self.queue) == 1
+    }
+}
+
+/// The receiving half of a frame ring
+#[derive(Debug)]
+pub struct FrameConsumer {
+    queue: Arc<Queue<Segment>>,
+}
+
+impl FrameConsumer {
+    /// Receive the next frame, or `None` if the ring is empty.
+    ///
+    /// The frame's segment is returned to the pool when it is dropped.
+    pub fn pop_frame(&mut self) -> Option<Segment> {
+        self.queue.pop()
+    }
+
+    /// The number of frames waiting to be consumed
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return true if no frames are waiting to be consumed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return true if the producer has been dropped
+    pub fn is_disconnected(&self) -> bool {
+        Arc::strong_count(&self.queue) == 1
+    }
+}
@@ -0,0 +1,130 @@
+//! Sets of pools that share a memory budget.
+//!
+//! A [PoolSet] holds one global pool per element type, all drawing on one
+//! shared limit on retained bytes. A multi tenant server can give each tenant
+//! its own set, so one tenant can't fill the pools at the expense of the
+//! others, and throw all of a tenant's pooled memory away at once by dropping
+//! its set.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::set::PoolSet;
+//!
+//! // this tenant's pools may retain at most 1 MiB between them
+//! let tenant = PoolSet::new(1024 * 1024);
+//! let mut s = tenant.take::<String>();
+//! s.push_str("hello");
+//! let v = tenant.take::<Vec<u64>>();
+//! drop((s, v));
+//! assert!(tenant.retained_bytes() > 0);
+//! drop(tenant); // everything it retained is freed
+//! ```
+use super::{get_size_any, Budget, GPooled, Pool, PoolControl};
+use crate::Poolable;
+use fxhash::FxHashMap;
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+struct Entry {
+    pool: Box<dyn Any + Send + Sync>,
+    control: Box<dyn PoolControl + Send + Sync>,
+}
+
+/// A set of pools, one per element type, with a shared byte budget.
+///
+/// Objects are only retained while the total
+/// [allocated_bytes](Poolable::allocated_bytes) retained by all the pools in
+/// the set stays under the budget, once it's reached returned objects are
+/// dropped. Objects that are out of the pool when the set is dropped are
+/// dropped normally when they are done with.
+pub struct PoolSet {
+    budget: Arc<Budget>,
+    pools: Mutex<FxHashMap<TypeId, Entry>>,
+}
+
+impl fmt::Debug for PoolSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolSet")
+            .field("max_bytes", &self.budget.max_bytes())
+            .field("retained_bytes", &self.budget.used())
+            .finish()
+    }
+}
+
+impl PoolSet {
+    /// Create an empty set whose pools may retain at most `max_bytes`
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            budget: Arc::new(Budget::new(max_bytes)),
+            pools: Mutex::new(FxHashMap::default()),
+        }
+    }
+
+    fn pool_inner<T: Any + Poolable + Send>(
+        &self,
+        sizes: Option<(usize, usize)>,
+    ) -> Pool<T> {
+        let mut pools = self.pools.lock().unwrap();
+        let e = pools.entry(TypeId::of::<T>()).or_insert_with(|| {
+            let (size, max) = sizes.unwrap_or_else(get_size_any::<T>);
+            let pool = Pool::<T>::new_budgeted(size, max, Some(Arc::clone(&self.budget)));
+            Entry { control: Box::new(pool.clone()), pool: Box::new(pool) }
+        });
+        e.pool.downcast_ref::<Pool<T>>().unwrap().clone()
+    }
+
+    /// Get the set's pool of `T`s.
+    ///
+    /// The pool is created on first use with the sizes configured by
+    /// [set_size_any](super::set_size_any).
+    pub fn pool<T: Any + Poolable + Send>(&self) -> Pool<T> {
+        self.pool_inner(None)
+    }
+
+    /// Get the set's pool of `T`s, creating it with the specified sizes if it
+    /// does not exist yet.
+    pub fn pool_sz<T: Any + Poolable + Send>(&self, size: usize, max: usize) -> Pool<T> {
+        self.pool_inner(Some((size, max)))
+    }
+
+    /// Take a `T` from the set's pool of `T`s
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take<T: Any + Poolable + Send>(&self) -> GPooled<T> {
+        self.pool_inner::<T>(None).take()
+    }
+
+    /// The maximum number of bytes the set's pools may retain
+    pub fn max_bytes(&self) -> usize {
+        self.budget.max_bytes()
+    }
+
+    /// The number of bytes currently retained by the set's pools
+    pub fn retained_bytes(&self) -> usize {
+        self.budget.used()
+    }
+
+    /// Drop every object retained by the set's pools
+    pub fn clear(&self) {
+        for e in self.pools.lock().unwrap().values() {
+            e.control.clear()
+        }
+    }
+
+    /// Prune every pool in the set, see [RawPool::prune](super::RawPool::prune)
+    pub fn prune(&self) {
+        for e in self.pools.lock().unwrap().values() {
+            e.control.prune()
+        }
+    }
+
+    /// Call `f` with each of the set's pools
+    pub fn for_each<F: FnMut(&dyn PoolControl)>(&self, mut f: F) {
+        for e in self.pools.lock().unwrap().values() {
+            f(&*e.control)
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Pools split into independent shards.
+//!
+//! A [ShardedPool] owns `N` independent pools and picks one by a caller
+//! provided key, such as a connection id. Objects taken for a key go back to
+//! that key's shard, so per connection recycling stays cache friendly and
+//! uncontended without every connection owning a whole pool.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{sharded::ShardedPool, GPooled};
+//!
+//! let pool: ShardedPool<GPooled<Vec<u8>>, 8> = ShardedPool::new(128, 64 * 1024);
+//! let conn_id = 42;
+//! let mut buf = pool.take(conn_id);
+//! buf.extend_from_slice(b"request");
+//! drop(buf); // returns to shard 42 % 8
+//! ```
+use super::{RawPool, RawPoolable};
+
+/// `N` independent pools selected by key, see the
+/// [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct ShardedPool<T: RawPoolable, const N: usize>([RawPool<T>; N]);
+
+impl<T: RawPoolable, const N: usize> ShardedPool<T, N> {
+    /// Create `N` shards, each retaining up to `max_capacity` objects of
+    /// capacity at most `max_elt_capacity`.
+    pub fn new(max_capacity: usize, max_elt_capacity: usize) -> Self {
+        const { assert!(N > 0, "a ShardedPool needs at least one shard") };
+        Self(std::array::from_fn(|_| RawPool::new(max_capacity, max_elt_capacity)))
+    }
+
+    /// Return the shard for `key`
+    pub fn shard(&self, key: usize) -> &RawPool<T> {
+        &self.0[key % N]
+    }
+
+    /// Return all the shards
+    pub fn shards(&self) -> &[RawPool<T>; N] {
+        &self.0
+    }
+
+    /// Take an object from the shard for `key`, allocating one if it is
+    /// empty.
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn take(&self, key: usize) -> T {
+        self.shard(key).take()
+    }
+
+    /// Take an object from the shard for `key`, or `None` if it is empty
+    pub fn try_take(&self, key: usize) -> Option<T> {
+        self.shard(key).try_take()
+    }
+
+    /// Prune every shard, see [RawPool::prune]
+    pub fn prune(&self) {
+        for p in &self.0 {
+            p.prune()
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Saving and restoring the sizes of the thread local global pools.
+//!
+//! Pool limits are easiest to tune by watching a realistic workload, e.g.
+//! in staging, and then using what was learned in production. [export]
+//! returns the limits configured with [set_size](super::set_size) and
+//! [set_initial_capacity](super::set_initial_capacity) for every type that
+//! has been pooled, along with what was observed about its use. [import]
+//! applies saved limits, including to types that haven't been pooled yet.
+//!
+//! Types are identified by their name, because discriminants are only
+//! stable within one build. With the `serde` feature [SizeConfig]
+//! implements `Serialize` and `Deserialize`, so the exported list can be
+//! saved in any format.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{self, sizes};
+//!
+//! global::set_size::<Vec<u32>>(64, 4096);
+//! drop(global::take::<Vec<u32>>());
+//! let saved = sizes::export();
+//! // ... at the next startup
+//! sizes::import(&saved);
+//! assert_eq!(global::get_size::<Vec<u32>>(), Some((64, 4096)));
+//! ```
+use super::{DEFAULT_SIZES, INITIAL_CAPACITY, SIZES};
+use crate::Discriminant;
+use fxhash::FxHashMap;
+use std::sync::{LazyLock, Mutex};
+
+/// The saved configuration of the global pools of one type
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize, serde_derive::Deserialize))]
+pub struct SizeConfig {
+    /// The name of the pooled type, as given by `std::any::type_name`
+    pub type_name: String,
+    /// The maximum number of objects each pool retains
+    pub max_pool_size: usize,
+    /// Objects with a capacity larger than this are not retained
+    pub max_element_capacity: usize,
+    /// The capacity of newly allocated objects
+    pub initial_capacity: usize,
+    /// The most objects any one pool had taken and not yet returned, among
+    /// the pools of threads that have exited. This is only counted with the
+    /// `stats` feature, and it is ignored by [import].
+    pub max_outstanding: usize,
+}
+
+// the types seen so far, and what was observed about them
+static SEEN: LazyLock<Mutex<FxHashMap<Discriminant, Seen>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+// imported configs for types that haven't been seen yet
+static PENDING: LazyLock<Mutex<FxHashMap<String, SizeConfig>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+struct Seen {
+    type_name: &'static str,
+    max_outstanding: usize,
+}
+
+/// Note that `d` is the discriminant of `type_name`, and apply any imported
+/// config for it that is still pending
+pub(super) fn seen(d: Discriminant, type_name: &'static str) {
+    let mut seen = SEEN.lock().unwrap();
+    if seen.contains_key(&d) {
+        return;
+    }
+    seen.insert(d, Seen { type_name, max_outstanding: 0 });
+    drop(seen);
+    if let Some(cfg) = PENDING.lock().unwrap().remove(type_name) {
+        apply(d, &cfg)
+    }
+}
+
+/// Record the high water mark of a pool that is being destroyed
+#[cfg(feature = "stats")]
+pub(super) fn observed(d: Discriminant, max_outstanding: usize) {
+    if let Some(s) = SEEN.lock().unwrap().get_mut(&d) {
+        s.max_outstanding = s.max_outstanding.max(max_outstanding)
+    }
+}
+
+fn apply(d: Discriminant, cfg: &SizeConfig) {
+    SIZES.lock().unwrap().insert(d, (cfg.max_pool_size, cfg.max_element_capacity));
+    INITIAL_CAPACITY.lock().unwrap().insert(d, cfg.initial_capacity);
+}
+
+/// Return the configuration of every type that has been pooled, or
+/// configured, by the global pools, sorted by type name
+pub fn export() -> Vec<SizeConfig> {
+    let seen = SEEN.lock().unwrap();
+    let sizes = SIZES.lock().unwrap();
+    let initial = INITIAL_CAPACITY.lock().unwrap();
+    let mut res: Vec<SizeConfig> = seen
+        .iter()
+        .map(|(d, s)| {
+            let (max_pool_size, max_element_capacity) =
+                sizes.get(d).copied().unwrap_or(DEFAULT_SIZES);
+            SizeConfig {
+                type_name: s.type_name.into(),
+                max_pool_size,
+                max_element_capacity,
+                initial_capacity: initial.get(d).copied().unwrap_or(0),
+                max_outstanding: s.max_outstanding,
+            }
+        })
+        .collect();
+    res.sort_by(|a, b| a.type_name.cmp(&b.type_name));
+    res
+}
+
+/// Apply saved configurations.
+///
+/// Types that have already been pooled are configured immediately, the
+/// rest when they are first used. Like [set_size](super::set_size) this
+/// only affects pools created afterwards, so call it at startup.
+pub fn import(configs: &[SizeConfig]) {
+    let seen = SEEN.lock().unwrap();
+    let mut pending = PENDING.lock().unwrap();
+    for cfg in configs {
+        match seen.iter().find(|(_, s)| s.type_name == cfg.type_name) {
+            Some((d, _)) => apply(*d, cfg),
+            None => {
+                pending.insert(cfg.type_name.clone(), cfg.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Pooled strings that leave small strings unpooled.
+//!
+//! Recycling a string costs a pair of queue operations, which is more than
+//! allocating a short one, and short strings returned to a pool take the
+//! place of the long ones that are worth keeping. A [PooledString] is
+//! taken from a pool with a [min_elt_capacity](super::RawPool::set_min_elt_capacity)
+//! threshold. Strings that need less than the threshold are ordinary
+//! allocations that never touch the pool, and the rest are pooled.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{string::{self, PooledString}, PoolControl};
+//!
+//! // retain up to 256 strings of 64 bytes to 4 KiB
+//! let pool = string::pool(256, 4096, 64);
+//! let name = PooledString::from_str_in(&pool, "alice");
+//! let body = PooledString::from_str_in(&pool, &"x".repeat(1024));
+//! assert!(!name.is_pooled() && body.is_pooled());
+//! drop((name, body));
+//! assert_eq!(pool.len(), 1);
+//! ```
+use super::{GPooled, Pool};
+use std::{
+    borrow::Borrow,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Create a pool retaining up to `max_capacity` strings, with a capacity
+/// of at least `min_elt_capacity` and at most `max_elt_capacity` bytes
+pub fn pool(
+    max_capacity: usize,
+    max_elt_capacity: usize,
+    min_elt_capacity: usize,
+) -> Pool<String> {
+    let pool = Pool::new(max_capacity, max_elt_capacity);
+    pool.set_min_elt_capacity(min_elt_capacity);
+    pool
+}
+
+/// A string that is pooled only if it is big enough, see the
+/// [module level documentation](self).
+///
+/// Whether a string is pooled is decided when it is created, a small
+/// string that grows past the threshold is still dropped normally.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PooledString(GPooled<String>);
+
+impl PooledString {
+    /// Take an empty string with room for `capacity` bytes from `pool`, or
+    /// allocate one that isn't pooled if `capacity` is below the pool's
+    /// threshold
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn with_capacity_in(pool: &Pool<String>, capacity: usize) -> Self {
+        Self(pool.take_with_capacity(capacity))
+    }
+
+    /// Copy `s` into a string from `pool`, see
+    /// [with_capacity_in](Self::with_capacity_in)
+    #[cfg_attr(feature = "leaks", track_caller)]
+    pub fn from_str_in(pool: &Pool<String>, s: &str) -> Self {
+        let mut t = Self::with_capacity_in(pool, s.len());
+        t.push_str(s);
+        t
+    }
+
+    /// Return true if the string will be returned to a pool when dropped
+    pub fn is_pooled(&self) -> bool {
+        !self.0.is_orphan()
+    }
+
+    /// Return the underlying pooled string
+    pub fn into_inner(self) -> GPooled<String> {
+        self.0
+    }
+}
+
+impl Deref for PooledString {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl DerefMut for PooledString {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.0
+    }
+}
+
+impl AsRef<str> for PooledString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for PooledString {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for PooledString {
+    fn eq(&self, other: &str) -> bool {
+        self.0.as_str() == other
+    }
+}
+
+impl fmt::Write for PooledString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PooledString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0.as_str(), f)
+    }
+}
+
+impl fmt::Display for PooledString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<PooledString> for GPooled<String> {
+    fn from(s: PooledString) -> Self {
+        s.0
+    }
+}
@@ -0,0 +1,105 @@
+//! Tracing how long pooled objects are held.
+//!
+//! A pool that runs dry under load is often short of objects because some
+//! request type holds its buffers far longer than the others. With the
+//! `tracing` feature, a pool can be told to
+//! [trace spans](super::RawPool::set_trace_spans), and then every object
+//! taken from it carries a `pooled` span from the take until it is
+//! returned or detached. The span is created inside whatever span is
+//! current at the take, so in a distributed trace it shows up under the
+//! request that took the object, and its duration is how long that request
+//! held it.
+//!
+//! The span is at the `DEBUG` level, and records the `type_name` of the
+//! object and its `capacity` when it was taken. Creating a span costs
+//! about as much as a take from the pool, so turn it on for the pools you
+//! are investigating, not all of them. Without the feature setting the
+//! option does nothing.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::Pool;
+//! use std::sync::LazyLock;
+//!
+//! static FRAMES: LazyLock<Pool<Vec<u8>>> = LazyLock::new(|| {
+//!     let pool = Pool::new(64, 1 << 16);
+//!     pool.set_trace_spans(true);
+//!     pool
+//! });
+//!
+//! // with a subscriber installed, this take opens a span in the current
+//! // request's span, and it closes when the frame is dropped
+//! let mut frame = FRAMES.take();
+//! frame.extend_from_slice(b"hello");
+//! drop(frame);
+//! ```
+
+#[cfg(feature = "tracing")]
+mod imp {
+    use tracing::Span;
+
+    /// The span covering the time an object is out of its pool. It is
+    /// boxed so objects from pools that don't trace only pay a pointer.
+    #[derive(Debug)]
+    pub(crate) struct ObjectSpan(Option<Box<Span>>);
+
+    impl ObjectSpan {
+        pub(crate) fn none() -> Self {
+            Self(None)
+        }
+
+        /// Open a span for an object of type `T` that was just taken
+        pub(crate) fn start<T>(&mut self, capacity: usize) {
+            let span = tracing::debug_span!(
+                "pooled",
+                type_name = std::any::type_name::<T>(),
+                capacity
+            );
+            self.0 = (!span.is_disabled()).then(|| Box::new(span))
+        }
+
+        /// Move the span out, leaving none
+        pub(crate) fn take(&mut self) -> Self {
+            Self(self.0.take())
+        }
+
+        /// Close the span, the object is back in the pool or gone
+        pub(crate) fn end(&mut self) {
+            self.0 = None
+        }
+    }
+
+    // a clone is a different object that was never taken
+    impl Clone for ObjectSpan {
+        fn clone(&self) -> Self {
+            Self::none()
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod imp {
+    #[derive(Debug, Clone)]
+    pub(crate) struct ObjectSpan;
+
+    impl ObjectSpan {
+        #[inline(always)]
+        pub(crate) fn none() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn start<T>(&mut self, _capacity: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn take(&mut self) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn end(&mut self) {}
+    }
+}
+
+pub(crate) use imp::ObjectSpan;
@@ -0,0 +1,119 @@
+//! Borrowing pooled and plain objects through one type.
+//!
+//! A function that fills a buffer usually only needs `&mut T`, but then it
+//! can't use the pool aware operations of [GPooled], such as
+//! [reserve_pooled](GPooled::reserve_pooled), and taking `&mut GPooled<T>`
+//! instead forces every caller to have a pooled object, and lets the callee
+//! detach or reassign it. A [PooledRef] borrows either one. It dereferences
+//! to the object and knows about the pool if there is one, but it can't
+//! detach, assign, or otherwise take the object away from its owner.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::global::{view::PooledRef, GPooled};
+//!
+//! fn fill<'a>(buf: impl Into<PooledRef<'a, Vec<u8>>>) {
+//!     let mut buf = buf.into();
+//!     buf.reserve_pooled(4);
+//!     buf.extend_from_slice(b"abcd")
+//! }
+//!
+//! let mut pooled: GPooled<Vec<u8>> = GPooled::take();
+//! let mut plain = Vec::new();
+//! fill(&mut pooled);
+//! fill(&mut plain);
+//! fill(pooled.downgrade());
+//! assert_eq!(&pooled[..], b"abcdabcd");
+//! assert_eq!(&plain[..], b"abcd");
+//! ```
+use super::GPooled;
+use crate::{AllocError, Poolable};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+enum Inner<'a, T: Poolable> {
+    Pooled(&'a mut GPooled<T>),
+    Plain(&'a mut T),
+}
+
+/// A mutable borrow of a pooled or a plain `T`, see the
+/// [module level documentation](self).
+pub struct PooledRef<'a, T: Poolable>(Inner<'a, T>);
+
+impl<T: Poolable + fmt::Debug> fmt::Debug for PooledRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &**self)
+    }
+}
+
+impl<'a, T: Poolable> From<&'a mut GPooled<T>> for PooledRef<'a, T> {
+    fn from(t: &'a mut GPooled<T>) -> Self {
+        Self(Inner::Pooled(t))
+    }
+}
+
+impl<'a, T: Poolable> From<&'a mut T> for PooledRef<'a, T> {
+    fn from(t: &'a mut T) -> Self {
+        Self(Inner::Plain(t))
+    }
+}
+
+impl<T: Poolable> PooledRef<'_, T> {
+    /// Return true if the borrowed object is a [GPooled], even an orphan
+    pub fn is_pooled(&self) -> bool {
+        matches!(self.0, Inner::Pooled(_))
+    }
+
+    /// Borrow the view again for a shorter lifetime, to hand it to another
+    /// function without giving it up
+    pub fn reborrow(&mut self) -> PooledRef<'_, T> {
+        match &mut self.0 {
+            Inner::Pooled(t) => PooledRef(Inner::Pooled(t)),
+            Inner::Plain(t) => PooledRef(Inner::Plain(t)),
+        }
+    }
+
+    /// Reserve room for `additional` more elements, see
+    /// [GPooled::reserve_pooled]. A plain object just reserves.
+    ///
+    /// # Panics
+    ///
+    /// If the allocation fails
+    pub fn reserve_pooled(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve_pooled(additional) {
+            panic!("{e}")
+        }
+    }
+
+    /// Like [reserve_pooled](Self::reserve_pooled), but returns an error if
+    /// the allocation fails.
+    pub fn try_reserve_pooled(&mut self, additional: usize) -> Result<(), AllocError> {
+        match &mut self.0 {
+            Inner::Pooled(t) => t.try_reserve_pooled(additional),
+            Inner::Plain(t) => Poolable::try_reserve(*t, additional),
+        }
+    }
+}
+
+impl<T: Poolable> Deref for PooledRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match &self.0 {
+            Inner::Pooled(t) => t,
+            Inner::Plain(t) => t,
+        }
+    }
+}
+
+impl<T: Poolable> DerefMut for PooledRef<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match &mut self.0 {
+            Inner::Pooled(t) => t,
+            Inner::Plain(t) => t,
+        }
+    }
+}
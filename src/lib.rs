@@ -90,13 +90,31 @@
 //!     drop(w) // puts the widget back in the local pool
 //! }
 //! ```
+// lets the derive macros refer to ::poolshark inside this crate too
+extern crate self as poolshark;
+
 use global::WeakPool;
-pub use poolshark_derive::location_id;
-use std::alloc::Layout;
+pub use poolshark_derive::{location_id, Poolable};
+use std::{alloc::Layout, error::Error, fmt, pin::Pin};
 
+pub mod bench;
+pub mod collect;
+pub mod compat;
+pub mod decode;
+pub mod encode;
+pub mod format;
 pub mod global;
 pub mod local;
+pub mod log;
 pub mod pooled;
+pub mod prelude;
+pub mod scratch;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod shadow;
+pub mod size_of;
+pub mod stats;
+pub mod testing;
 
 /// A globally unique id for a source code position
 ///
@@ -104,6 +122,81 @@ pub mod pooled;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct LocationId(pub u16);
 
+impl LocationId {
+    /// Make an id from a stable key, without the [location_id] macro.
+    ///
+    /// This is for libraries that can't depend on the proc macro crate. The
+    /// id is a 15 bit fold of a 64 bit FNV-1a hash of `key`, so the key
+    /// should be unique to the container type, e.g. its full path and
+    /// crate name, and must never change. Unlike the macro two keys can
+    /// collide, so prefer the macro when possible. The id is marked as
+    /// hashed, and the local and thread local global pools check that only
+    /// one container type uses it, in release builds too, and panic on a
+    /// collision.
+    ///
+    /// ```
+    /// use poolshark::{Discriminant, IsoPoolable, LocationId, Poolable};
+    ///
+    /// #[derive(Default)]
+    /// struct Frames<T>(Vec<T>);
+    ///
+    /// impl<T> Poolable for Frames<T> {
+    ///     fn empty() -> Self {
+    ///         Self(Vec::new())
+    ///     }
+    ///     fn reset(&mut self) {
+    ///         self.0.clear()
+    ///     }
+    ///     fn capacity(&self) -> usize {
+    ///         self.0.capacity()
+    ///     }
+    /// }
+    ///
+    /// unsafe impl<T> IsoPoolable for Frames<T> {
+    ///     const DISCRIMINANT: Option<Discriminant> =
+    ///         Discriminant::new_p1::<T>(LocationId::from_key_hash("my_codec::Frames"));
+    /// }
+    /// ```
+    pub const fn from_key_hash(key: &str) -> Self {
+        let key = key.as_bytes();
+        let mut h: u64 = 0xcbf29ce484222325;
+        let mut i = 0;
+        while i < key.len() {
+            h ^= key[i] as u64;
+            h = h.wrapping_mul(0x100000001b3);
+            i += 1;
+        }
+        let id = (h ^ (h >> 16) ^ (h >> 32) ^ (h >> 48)) as u16;
+        Self(id | Self::HASHED)
+    }
+
+    // set in ids made by hashing a key, which the macro never allocates
+    const HASHED: u16 = 0x8000;
+
+    // return true if the id was made by hashing, and so may collide
+    const fn is_hashed(self) -> bool {
+        self.0 & Self::HASHED != 0
+    }
+}
+
+// the container types that have made a pool for each location id, ids
+// made by hashing can collide, and in debug builds we want to know
+#[cfg(debug_assertions)]
+static CONTAINERS: std::sync::LazyLock<
+    std::sync::Mutex<fxhash::FxHashMap<LocationId, &'static str>>,
+> = std::sync::LazyLock::new(|| std::sync::Mutex::new(fxhash::FxHashMap::default()));
+
+// the name of T without its parameters, types that differ only in their
+// parameters, e.g. Vec<u32> and Vec<f32>, legitimately share location ids
+fn container_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.split_once('<').map_or(name, |(c, _)| c)
+}
+
+fn location_id_collision(id: LocationId, a: &str, b: &str) -> ! {
+    panic!("{a} and {b} both use {id:?}, the ids collide")
+}
+
 #[cfg(test)]
 mod test;
 
@@ -340,9 +433,138 @@ impl Discriminant {
     }
 }
 
+/// An allocation requested by a fallible take failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError {
+    /// The additional capacity that could not be allocated
+    pub additional: usize,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to allocate capacity for {} more elements", self.additional)
+    }
+}
+
+impl Error for AllocError {}
+
+/// A pooled object could not be reset, so it must be really dropped instead
+/// of being reused, see [Poolable::try_reset]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetError {
+    /// Why the reset failed
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ResetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to reset pooled object: {}", self.reason)
+    }
+}
+
+impl Error for ResetError {}
+
+/// A reserve on a pooled object grew it past what its pool will retain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OversizedReserve {
+    /// The pooled type
+    pub type_name: &'static str,
+    /// The capacity of the object after the reserve
+    pub capacity: usize,
+    /// The largest capacity the object's pool will retain
+    pub max_elt_capacity: usize,
+}
+
+type ReserveHook = std::sync::Arc<dyn Fn(&OversizedReserve) + Send + Sync>;
+
+static RESERVE_HOOK: std::sync::RwLock<Option<ReserveHook>> =
+    std::sync::RwLock::new(None);
+
+/// Call `hook` whenever `reserve_pooled` or `try_reserve_pooled` grows a
+/// pooled object past the `max_elt_capacity` of its pool, or remove the hook
+/// if `None`.
+///
+/// Such an object will be thrown away instead of pooled when it is dropped,
+/// which is usually a sign that the pool's limits need raising. This is
+/// process wide and applies to both [local::LPooled] and [global::GPooled].
+///
+/// ```
+/// use poolshark::{global::Pool, OversizedReserve};
+///
+/// poolshark::set_oversized_reserve_hook(Some(|r: &OversizedReserve| {
+///     eprintln!("{} grew to {} > {}", r.type_name, r.capacity, r.max_elt_capacity)
+/// }));
+/// let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+/// let mut v = pool.take();
+/// v.reserve_pooled(4096); // the hook is called
+/// ```
+pub fn set_oversized_reserve_hook<F>(hook: Option<F>)
+where
+    F: Fn(&OversizedReserve) + Send + Sync + 'static,
+{
+    *RESERVE_HOOK.write().unwrap() = hook.map(|f| std::sync::Arc::new(f) as ReserveHook)
+}
+
+/// Reserve `additional` more elements in `t`, and call the hook if that made
+/// it too big for its pool
+pub(crate) fn reserve_pooled<T: Poolable>(
+    t: &mut T,
+    additional: usize,
+    max_elt_capacity: impl FnOnce() -> Option<usize>,
+) -> Result<(), AllocError> {
+    t.try_reserve(additional)?;
+    let capacity = t.capacity();
+    if let Some(max_elt_capacity) = max_elt_capacity()
+        && capacity > max_elt_capacity
+    {
+        let hook = RESERVE_HOOK.read().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(&OversizedReserve {
+                type_name: std::any::type_name::<T>(),
+                capacity,
+                max_elt_capacity,
+            })
+        }
+    }
+    Ok(())
+}
+
 struct Opaque {
     t: *mut (),
     drop: Option<Box<dyn FnOnce(*mut ())>>,
+    container: &'static str,
+}
+
+impl Opaque {
+    /// Wrap the new pool `t` for `T`s with discriminant `d`. In debug
+    /// builds panic if another container type has used `d`'s location id.
+    fn new<T>(d: Discriminant, t: *mut (), drop: Box<dyn FnOnce(*mut ())>) -> Self {
+        let container = container_name::<T>();
+        #[cfg(debug_assertions)]
+        {
+            let prev =
+                *CONTAINERS.lock().unwrap().entry(d.container).or_insert(container);
+            if prev != container {
+                location_id_collision(d.container, prev, container)
+            }
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = d;
+        Self { t, drop: Some(drop), container }
+    }
+
+    /// Panic if the pool wasn't made for `T`'s container. Allocated ids
+    /// can't collide, so they are only checked in debug builds, hashed ids
+    /// always are, since a collision would hand out objects as the wrong
+    /// type.
+    #[inline(always)]
+    fn check<T>(&self, d: Discriminant) {
+        if (cfg!(debug_assertions) || d.container.is_hashed())
+            && self.container != container_name::<T>()
+        {
+            location_id_collision(d.container, self.container, container_name::<T>())
+        }
+    }
 }
 
 impl Drop for Opaque {
@@ -353,15 +575,156 @@ impl Drop for Opaque {
     }
 }
 
+/// What a [Poolable] object is left holding by [reset](Poolable::reset)
+///
+/// Pools keyed by [Discriminant], the local pools and the thread local
+/// global pools, hand an object returned as one type out again as another
+/// isomorphic type, which is only sound if the object is empty. Pools that
+/// only ever hold one type, such as a [Pool](global::Pool) you create with
+/// [RawPool::new](global::RawPool::new), have no such requirement, so types
+/// that only pool that way may keep part of their state across uses.
+///
+/// Using a `Retained` type with a pool keyed by [Discriminant] is a compile
+/// error.
+///
+/// ```compile_fail
+/// use poolshark::{location_id, local::LPooled, Discriminant, IsoPoolable, Poolable, ReusePolicy};
+///
+/// struct Cache(Vec<u32>);
+///
+/// impl Poolable for Cache {
+///     const REUSE: ReusePolicy = ReusePolicy::Retained;
+///     fn empty() -> Self { Cache(vec![1, 2, 3]) }
+///     fn reset(&mut self) {}
+///     fn capacity(&self) -> usize { 1 }
+/// }
+///
+/// unsafe impl IsoPoolable for Cache {
+///     const DISCRIMINANT: Option<Discriminant> = Discriminant::new(location_id!());
+/// }
+///
+/// let c: LPooled<Cache> = LPooled::take();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReusePolicy {
+    /// reset leaves the object empty, as if it had just been created by
+    /// [empty](Poolable::empty). This is the default, and it is required by
+    /// [IsoPoolable] types.
+    Empty,
+    /// reset only clears the part of the object that changes between uses,
+    /// for example keeping a preloaded lookup table while clearing the
+    /// scratch space next to it. Objects taken from the pool may not be
+    /// empty.
+    Retained,
+}
+
+/// Return the discriminant of `T`, failing to compile if `T` can't be
+/// pooled by discriminant
+pub(crate) const fn discriminant<T: IsoPoolable>() -> Option<Discriminant> {
+    const {
+        assert!(
+            matches!(T::REUSE, ReusePolicy::Empty),
+            "types with ReusePolicy::Retained can't be pooled by discriminant"
+        )
+    };
+    T::DISCRIMINANT
+}
+
+static NO_POOL_SET: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+static NO_POOL: std::sync::LazyLock<std::sync::RwLock<fxhash::FxHashSet<Discriminant>>> =
+    std::sync::LazyLock::new(|| std::sync::RwLock::new(fxhash::FxHashSet::default()));
+
+/// Return the discriminant of `T` if it may be pooled by discriminant right
+/// now, i.e. it isn't on the [do not pool](set_no_pool) list
+pub(crate) fn pool_discriminant<T: IsoPoolable>() -> Option<Discriminant> {
+    let d = discriminant::<T>()?;
+    if no_pool_set() && NO_POOL.read().unwrap().contains(&d) {
+        return None;
+    }
+    Some(d)
+}
+
+/// Return true if anything is on the do not pool list, a cheap check for
+/// paths that skip [pool_discriminant]
+pub(crate) fn no_pool_set() -> bool {
+    NO_POOL_SET.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Add or remove `d` from the do not pool list, see [set_no_pool]
+pub fn set_no_pool_discriminant(d: Discriminant, no_pool: bool) {
+    let mut set = NO_POOL.write().unwrap();
+    if no_pool {
+        set.insert(d);
+    } else {
+        set.remove(&d);
+    }
+    NO_POOL_SET.store(!set.is_empty(), std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Stop pooling `T` in the pools keyed by [Discriminant].
+///
+/// From now on the local pools and the thread local global pools will
+/// allocate a new `T` on every take and really drop every `T` given back, as
+/// if `T` had no discriminant. This is meant for incidents, to rule out
+/// pooling of a suspect type without a code change. Since pools are shared
+/// by discriminant it applies to every type isomorphic to `T`. Objects
+/// already pooled stay there until their thread exits or
+/// [local::clear_type] or [global::clear_type] is called. Named global
+/// pools aren't affected, use [RawPool::freeze](global::RawPool::freeze) for
+/// those.
+///
+/// ```
+/// use poolshark::local::{self, LPooled};
+///
+/// poolshark::set_no_pool::<Vec<u32>>();
+/// let mut v: LPooled<Vec<u32>> = LPooled::take();
+/// v.push(42);
+/// drop(v); // really dropped
+/// poolshark::unset_no_pool::<Vec<u32>>();
+/// ```
+pub fn set_no_pool<T: IsoPoolable>() {
+    if let Some(d) = discriminant::<T>() {
+        set_no_pool_discriminant(d, true)
+    }
+}
+
+/// Resume pooling `T`, see [set_no_pool]
+pub fn unset_no_pool<T: IsoPoolable>() {
+    if let Some(d) = discriminant::<T>() {
+        set_no_pool_discriminant(d, false)
+    }
+}
+
+/// Return true if `T` is on the do not pool list, see [set_no_pool]
+pub fn is_no_pool<T: IsoPoolable>() -> bool {
+    discriminant::<T>().is_some() && pool_discriminant::<T>().is_none()
+}
+
 /// Trait for poolable objects
 pub trait Poolable {
+    /// what reset leaves behind, see [ReusePolicy]
+    const REUSE: ReusePolicy = ReusePolicy::Empty;
+
     /// allocate a new empty collection
     fn empty() -> Self;
 
     /// empty the collection and reset it to its default state so it
-    /// can be put back in the pool.
+    /// can be put back in the pool. If [REUSE](Poolable::REUSE) is
+    /// [Retained](ReusePolicy::Retained) it need only reset the parts
+    /// of the object that shouldn't survive until the next use.
     fn reset(&mut self);
 
+    /// try to reset the object, failing if it can't be put back in a
+    /// reusable state, e.g. if draining an internal channel failed. Pools
+    /// call this instead of [reset](Poolable::reset), and really drop
+    /// objects that fail. The default calls reset and never fails.
+    fn try_reset(&mut self) -> Result<(), ResetError> {
+        self.reset();
+        Ok(())
+    }
+
     /// return the capacity of the collection
     fn capacity(&self) -> usize;
 
@@ -370,6 +733,38 @@ pub trait Poolable {
     fn really_dropped(&mut self) -> bool {
         true
     }
+
+    /// return an estimate of the heap memory, in bytes, owned by the
+    /// collection. This is used to report how much memory a pool is
+    /// retaining. The default assumes one byte per unit of capacity.
+    fn allocated_bytes(&self) -> usize {
+        self.capacity()
+    }
+
+    /// try to reserve capacity for at least `additional` more elements,
+    /// reporting allocation failure instead of aborting. This is used by the
+    /// fallible take functions. The default does nothing.
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let _ = additional;
+        Ok(())
+    }
+}
+
+/// Trait for objects that must never move once they are created, such as
+/// intrusive or self referential structures. They are pooled wrapped in a
+/// [Pinned](pooled::Pinned).
+///
+/// Unlike [Poolable] the object is only ever reset through a pinned
+/// reference, so a reset can't move it either.
+pub trait PinPoolable {
+    /// allocate a new empty object
+    fn empty() -> Self;
+
+    /// reset the object in place so it can be put back in the pool
+    fn reset(self: Pin<&mut Self>);
+
+    /// return the capacity of the object
+    fn capacity(&self) -> usize;
 }
 
 /// Low level global pool trait for maximum control
@@ -396,6 +791,13 @@ pub unsafe trait RawPoolable: Sized {
     /// can be put back in the pool
     fn reset(&mut self);
 
+    /// try to reset the object, the pool really drops objects that fail.
+    /// The default calls reset and never fails.
+    fn try_reset(&mut self) -> Result<(), ResetError> {
+        self.reset();
+        Ok(())
+    }
+
     /// return the capacity of the collection
     fn capacity(&self) -> usize;
 
@@ -403,14 +805,143 @@ pub unsafe trait RawPoolable: Sized {
     /// make sure you do not call both this method and the drop
     /// implementation that puts the object back in the pool!
     fn really_drop(self);
+
+    /// return an estimate of the heap memory, in bytes, owned by the
+    /// object. The default assumes one byte per unit of capacity.
+    fn allocated_bytes(&self) -> usize {
+        self.capacity()
+    }
+
+    /// try to reserve capacity for at least `additional` more elements.
+    /// This is used by pools configured with an initial capacity. The
+    /// default does nothing.
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let _ = additional;
+        Ok(())
+    }
+
+    /// the pool's bookkeeping for the object, see [PoolHooks]. Only this
+    /// crate can name the argument, so only [GPooled](global::GPooled)
+    /// provides it, and nothing outside the crate can call it.
+    #[doc(hidden)]
+    fn hooks(&self, _: sealed::Token) -> Option<&dyn sealed::Hooks> {
+        None
+    }
+
+    /// see [hooks](RawPoolable::hooks)
+    #[doc(hidden)]
+    fn hooks_mut(&mut self, _: sealed::Token) -> Option<&mut dyn sealed::Hooks> {
+        None
+    }
+
+    /// the object's reference to its pool, which marks whether the object
+    /// is counted against the pool's taker limit, see
+    /// [hooks](RawPoolable::hooks)
+    #[doc(hidden)]
+    fn weak_pool(&mut self, _: sealed::Token) -> Option<&mut WeakPool<Self>> {
+        None
+    }
+
+    /// see [PoolHooks::pooled_type_name]
+    #[doc(hidden)]
+    fn pooled_name(_: sealed::Token) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+mod sealed {
+    /// Can only be made in this crate, so methods that take it can neither
+    /// be overridden nor called from outside
+    pub struct Token(pub(crate) ());
+
+    /// The per object bookkeeping behind [PoolHooks](super::PoolHooks)
+    pub trait Hooks {
+        fn record_use(&mut self);
+        fn record_site(&mut self, site: &'static std::panic::Location<'static>);
+        fn age(&self) -> Option<(std::time::Duration, u64)>;
+        fn epoch(&self) -> Option<u64>;
+        fn start_span(&mut self);
+    }
 }
 
+pub(crate) use sealed::Hooks;
+
+/// What the pools record about each [RawPoolable] object, for leaks, ages,
+/// epochs, traces and faults. Implementations other than
+/// [GPooled](global::GPooled) keep none of it, and get the defaults.
+pub(crate) trait PoolHooks: RawPoolable {
+    /// record that the object was handed out by a take, for the ageing
+    /// statistics
+    fn record_use(&mut self) {
+        if let Some(h) = self.hooks_mut(sealed::Token(())) {
+            h.record_use()
+        }
+    }
+
+    /// record the call site of the take that handed out the object, for
+    /// the [leaks](crate::global::leaks) report
+    fn record_site(&mut self, site: &'static std::panic::Location<'static>) {
+        if let Some(h) = self.hooks_mut(sealed::Token(())) {
+            h.record_site(site)
+        }
+    }
+
+    /// return how long ago the object was created and how many times it
+    /// was handed out, if it keeps track
+    fn age(&self) -> Option<(std::time::Duration, u64)> {
+        self.hooks(sealed::Token(())).and_then(|h| h.age())
+    }
+
+    /// return the [epoch](crate::global::epoch) the object was created in,
+    /// if it keeps track, `None` is never stale
+    fn epoch(&self) -> Option<u64> {
+        self.hooks(sealed::Token(())).and_then(|h| h.epoch())
+    }
+
+    /// start the [trace span](crate::global::trace) covering the time the
+    /// object is out of the pool
+    fn start_span(&mut self) {
+        if let Some(h) = self.hooks_mut(sealed::Token(())) {
+            h.start_span()
+        }
+    }
+
+    /// mark the object as counted against its pool's
+    /// [taker limit](crate::global::RawPool::set_max_takers), returning
+    /// false if it has nowhere to keep the mark
+    fn set_admitted(&mut self) -> bool {
+        match self.weak_pool(sealed::Token(())) {
+            Some(p) => {
+                p.set_admitted();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// clear the mark set by [set_admitted](PoolHooks::set_admitted),
+    /// returning whether it was set
+    fn take_admitted(&mut self) -> bool {
+        self.weak_pool(sealed::Token(())).is_some_and(|p| p.take_admitted())
+    }
+
+    /// return the name of the pooled type, which [injected
+    /// faults](crate::testing::faults) are keyed by, by default the name
+    /// of `Self`
+    fn pooled_type_name() -> &'static str {
+        Self::pooled_name(sealed::Token(()))
+    }
+}
+
+impl<T: RawPoolable> PoolHooks for T {}
+
 /// Trait for isomorphicly poolable objects.
 ///
 /// That is objects that can safely be pooled by memory layout and container
 /// type. For example two `HashMap`s, `HashMap<usize, usize>` and
 /// `HashMap<ArcStr, ArcStr>` are isomorphic, their memory allocations can be
-/// used interchangably so long as they are empty.
+/// used interchangably so long as they are empty. Implementors must use
+/// [ReusePolicy::Empty].
 pub unsafe trait IsoPoolable: Poolable {
     /// # Getting the Layout Right
     ///
@@ -468,3 +999,71 @@ pub unsafe trait IsoPoolable: Poolable {
     /// then pooling these objects could cause undefined behavior.
     const DISCRIMINANT: Option<Discriminant>;
 }
+
+/// Fail compilation unless every listed type can be pooled by discriminant.
+///
+/// A type whose [DISCRIMINANT](IsoPoolable::DISCRIMINANT) is `None`, e.g.
+/// because an element is larger than 4 KiB or the container has too many
+/// type parameters, still works with the local pools, it just silently
+/// never pools anything. Asserting it makes that decision explicit.
+///
+/// ```
+/// use poolshark::assert_poolable;
+/// use std::collections::HashMap;
+///
+/// assert_poolable!(Vec<u64>, HashMap<String, [u8; 64]>);
+/// ```
+///
+/// ```compile_fail
+/// use poolshark::assert_poolable;
+///
+/// assert_poolable!(Vec<[u8; 8192]>); // too big for a discriminant
+/// ```
+#[macro_export]
+macro_rules! assert_poolable {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            const _: () = ::std::assert!(
+                <$t as $crate::IsoPoolable>::DISCRIMINANT.is_some(),
+                ::std::concat!(::std::stringify!($t), " can't be pooled by discriminant"),
+            );
+        )+
+    };
+}
+
+/// Containers whose allocation can be handed to and from another container.
+///
+/// Every [IsoPoolable] type has its own location id, so its pools never
+/// share with other containers, even when their allocations are
+/// interchangeable. For example a `VecDeque<T>` can be turned into a
+/// `Vec<T>` and back without reallocating. Implementing this trait opts a
+/// container into sharing the pool of its `Base` through
+/// [local::take_family] and [local::insert_family], which improves reuse in
+/// programs that use both.
+///
+/// The conversions should not reallocate, otherwise sharing doesn't save
+/// anything. They are only ever called on empty containers, and the base is
+/// reset before it is pooled, so a conversion can't put anything unsafe in
+/// the pool.
+///
+/// ```
+/// use poolshark::local;
+/// use std::collections::VecDeque;
+///
+/// let mut q: VecDeque<u64> = local::take_family();
+/// q.extend([1, 2, 3]);
+/// local::insert_family(q);
+/// // the deque's allocation is now pooled as a Vec<u64>
+/// let v: Vec<u64> = local::take();
+/// assert!(v.capacity() >= 3);
+/// ```
+pub trait AllocFamily: Poolable + Sized {
+    /// The container whose pool this one shares
+    type Base: IsoPoolable;
+
+    /// Turn an empty `Self` into a `Base`
+    fn into_base(self) -> Self::Base;
+
+    /// Turn an empty `Base` into a `Self`
+    fn from_base(base: Self::Base) -> Self;
+}
@@ -0,0 +1,211 @@
+//! Local pooling that skips the pool lookup on drop.
+//!
+//! Dropping an [LPooled](super::LPooled) finds this thread's pool for `T`
+//! by borrowing the thread local table of pools and hashing `T`'s
+//! [Discriminant](crate::Discriminant). For small objects that are taken and
+//! dropped in a tight loop that lookup is a large part of the cost. An
+//! [LPooledFast] remembers the pool it was taken from, and when it is
+//! dropped on the same thread, and nothing has [cleared](super::clear) the
+//! pools since, it pushes straight into it. Dropped anywhere else it falls
+//! back to the normal path, and goes to the dropping thread's pool like an
+//! `LPooled` would. So does any drop while something is on the
+//! [do not pool](crate::set_no_pool) list, which only the normal path
+//! consults.
+//!
+//! The cache costs two words, the pool pointer and a token identifying the
+//! thread's pools, so `LPooledFast` is opt in. The token can't be folded
+//! into the pointer: the pool may have been freed, so the pointer can't be
+//! followed to check it, and the pointer's spare bits are too few for a
+//! token that never repeats, which is what makes the check sound. The
+//! `local_fast` benchmark compares the two.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::local::fast::LPooledFast;
+//!
+//! for i in 0..1000u64 {
+//!     let mut v: LPooledFast<Vec<u64>> = LPooledFast::take();
+//!     v.extend(0..i % 16);
+//! } // each vec goes straight back into the pool it came from
+//! ```
+use super::{alloc, insert, insert_raw, with_pool, LPooled, Pool, POOLS};
+use crate::{shadow, IsoPoolable};
+use std::{
+    cell::Cell,
+    fmt::{self, Debug, Display},
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+thread_local! {
+    // identifies this thread's pools until they are cleared, 0 if no token
+    // has been handed out since then
+    static TOKEN: Cell<u64> = const { Cell::new(0) };
+}
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+// the token of this thread's pools, tokens are never reused, so two equal
+// tokens mean the same thread and no clear in between
+fn token() -> u64 {
+    TOKEN
+        .try_with(|t| match t.get() {
+            0 => {
+                let n = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+                t.set(n);
+                n
+            }
+            n => n,
+        })
+        .unwrap_or(0)
+}
+
+/// Forget the pools cached by outstanding [LPooledFast]s on this thread,
+/// must be called before any pool is freed
+pub(super) fn invalidate() {
+    let _ = TOKEN.try_with(|t| t.set(0));
+}
+
+/// An [LPooled](super::LPooled) that caches a pointer to its pool, see the
+/// [module level documentation](self).
+pub struct LPooledFast<T: IsoPoolable> {
+    object: ManuallyDrop<T>,
+    pool: *mut Pool<T>,
+    token: u64,
+}
+
+// the pool pointer is only used on the thread that took the object, after
+// checking the token, so it doesn't affect thread safety
+unsafe impl<T: IsoPoolable + Send> Send for LPooledFast<T> {}
+unsafe impl<T: IsoPoolable + Sync> Sync for LPooledFast<T> {}
+
+impl<T: IsoPoolable + Debug> Debug for LPooledFast<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &*self.object)
+    }
+}
+
+impl<T: IsoPoolable + Display> Display for LPooledFast<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &*self.object)
+    }
+}
+
+impl<T: IsoPoolable> Default for LPooledFast<T> {
+    fn default() -> Self {
+        Self::take()
+    }
+}
+
+impl<T: IsoPoolable> From<T> for LPooledFast<T> {
+    /// Wrap `t`, which has no cached pool, so it is returned the normal way
+    fn from(t: T) -> Self {
+        Self { object: ManuallyDrop::new(t), pool: ptr::null_mut(), token: 0 }
+    }
+}
+
+impl<T: IsoPoolable> LPooledFast<T> {
+    /// Take an object from this thread's pool, or create one if the pool is
+    /// empty, and remember the pool.
+    pub fn take() -> Self {
+        let (t, pool) = shadow::take::<T, _, _>(
+            || {
+                with_pool(None, |pool: Option<&mut Pool<T>>| match pool {
+                    None => (alloc(0), ptr::null_mut()),
+                    Some(p) => {
+                        let t = p.pop().unwrap_or_else(|| alloc(p.initial_capacity));
+                        (t, p as *mut Pool<T>)
+                    }
+                })
+            },
+            |(t, _)| t.capacity(),
+        );
+        let token = if pool.is_null() { 0 } else { token() };
+        Self { object: ManuallyDrop::new(t), pool, token }
+    }
+
+    /// Return true if the object will skip the pool lookup if it is dropped
+    /// now
+    pub fn is_cached(&self) -> bool {
+        self.token != 0
+            && !crate::no_pool_set()
+            && TOKEN.try_with(Cell::get) == Ok(self.token)
+    }
+
+    /// Forget the cached pool and wrap the object as an [LPooled]
+    pub fn into_lpooled(self) -> LPooled<T> {
+        LPooled::from(self.detach())
+    }
+
+    /// Detach the object from the pool, returning the inner value.
+    ///
+    /// The detached object will not be returned to the pool when dropped.
+    pub fn detach(self) -> T {
+        let mut t = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::take(&mut t.object) }
+    }
+}
+
+impl<T: IsoPoolable> AsRef<T> for LPooledFast<T> {
+    fn as_ref(&self) -> &T {
+        &self.object
+    }
+}
+
+impl<T: IsoPoolable> Deref for LPooledFast<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.object
+    }
+}
+
+impl<T: IsoPoolable> DerefMut for LPooledFast<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.object
+    }
+}
+
+impl<T: IsoPoolable + Extend<E>, E> Extend<E> for LPooledFast<T> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.object.extend(iter)
+    }
+}
+
+impl<T: IsoPoolable> Drop for LPooledFast<T> {
+    fn drop(&mut self) {
+        if !self.object.really_dropped() {
+            return unsafe { ManuallyDrop::drop(&mut self.object) };
+        }
+        let mut t = unsafe { ManuallyDrop::take(&mut self.object) };
+        if self.token == 0 || crate::no_pool_set() {
+            drop(insert(t));
+            return;
+        }
+        // resetting may drop other pooled objects, so it must happen before
+        // the pools are borrowed
+        if t.try_reset().is_err() {
+            return;
+        }
+        let mut slot = Some(t);
+        let mut rejected = None;
+        let _ = POOLS.try_with(|pools| {
+            // holding the borrow keeps any other access to the pools out
+            // while we push, just like with_pool
+            if let Ok(_pools) = pools.try_borrow_mut()
+                && TOKEN.try_with(Cell::get) == Ok(self.token)
+            {
+                // the token proves this is the thread that took the object
+                // and nothing has freed its pools since then
+                rejected = unsafe { (*self.pool).push(slot.take().unwrap()) };
+            }
+        });
+        drop(rejected);
+        if let Some(t) = slot {
+            drop(unsafe { insert_raw(t) })
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Buffered I/O with pooled buffers.
+//!
+//! [PooledBufReader] and [PooledBufWriter] work like `std::io::BufReader` and
+//! `std::io::BufWriter`, but borrow their buffer from the local `Vec<u8>` pool
+//! and give it back when they are dropped, so programs that open many short
+//! lived streams stop allocating a buffer per stream.
+//!
+//! Note that the default local pool sizes only retain vecs with a capacity
+//! of up to 1024 elements, so to pool buffers of the default size you must
+//! raise that limit at startup, e.g. with
+//! `local::set_size::<Vec<u8>>(1024, 64 * 1024)`.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::local::io::{PooledBufReader, PooledBufWriter};
+//! use std::io::{BufRead, Write};
+//!
+//! let mut out = Vec::new();
+//! {
+//!     let mut w = PooledBufWriter::new(&mut out);
+//!     writeln!(w, "hello").unwrap();
+//!     writeln!(w, "world").unwrap();
+//! } // flushed, and the buffer goes back to the pool
+//! let r = PooledBufReader::new(&out[..]);
+//! let lines: Vec<String> = r.lines().map(|l| l.unwrap()).collect();
+//! assert_eq!(lines, ["hello", "world"]);
+//! ```
+use super::LPooled;
+use std::{
+    cmp, fmt,
+    io::{self, BufRead, Read, Write},
+    mem::ManuallyDrop,
+    ptr,
+};
+
+/// The buffer size used by `new`, the same as std's
+pub const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// A `BufReader` whose buffer is taken from the local pool.
+pub struct PooledBufReader<R> {
+    inner: R,
+    buf: LPooled<Vec<u8>>,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: fmt::Debug> fmt::Debug for PooledBufReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBufReader")
+            .field("inner", &self.inner)
+            .field(
+                "buffer",
+                &format_args!("{}/{}", self.filled - self.pos, self.capacity()),
+            )
+            .finish()
+    }
+}
+
+impl<R> PooledBufReader<R> {
+    /// Wrap `inner` with a pooled buffer of [DEFAULT_BUF_SIZE] bytes
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Wrap `inner` with a pooled buffer of `capacity` bytes
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        let mut buf: LPooled<Vec<u8>> = LPooled::take();
+        buf.resize(capacity, 0);
+        Self { inner, buf, pos: 0, filled: 0 }
+    }
+
+    /// The size of the buffer
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// The currently buffered data
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly will skip any buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying reader, returning the buffer to the pool.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for PooledBufReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // skip the buffer for large reads when it is empty
+        if self.pos == self.filled && out.len() >= self.capacity() {
+            return self.inner.read(out);
+        }
+        let avail = self.fill_buf()?;
+        let n = cmp::min(avail.len(), out.len());
+        out[..n].copy_from_slice(&avail[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for PooledBufReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.filled {
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.filled)
+    }
+}
+
+/// A `BufWriter` whose buffer is taken from the local pool.
+///
+/// Like `BufWriter` buffered data is written when the writer is dropped, and
+/// errors doing that are ignored, so call [flush](Write::flush) first if you
+/// need to know about them.
+pub struct PooledBufWriter<W: Write> {
+    inner: W,
+    buf: LPooled<Vec<u8>>,
+    capacity: usize,
+}
+
+impl<W: Write + fmt::Debug> fmt::Debug for PooledBufWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBufWriter")
+            .field("inner", &self.inner)
+            .field("buffer", &format_args!("{}/{}", self.buf.len(), self.capacity))
+            .finish()
+    }
+}
+
+impl<W: Write> PooledBufWriter<W> {
+    /// Wrap `inner` with a pooled buffer of [DEFAULT_BUF_SIZE] bytes
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Wrap `inner` with a pooled buffer of `capacity` bytes
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        let mut buf: LPooled<Vec<u8>> = LPooled::take();
+        buf.reserve(capacity);
+        Self { inner, buf, capacity }
+    }
+
+    /// The size of the buffer
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The currently buffered data
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying writer.
+    ///
+    /// Writing to it directly will skip any buffered data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    fn flush_buf(&mut self) -> io::Result<()> {
+        let res = self.inner.write_all(&self.buf);
+        self.buf.clear();
+        res
+    }
+
+    /// Write any buffered data and unwrap the underlying writer, returning
+    /// the buffer to the pool.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush_buf()?;
+        let mut t = ManuallyDrop::new(self);
+        unsafe {
+            ptr::drop_in_place(&mut t.buf);
+            Ok(ptr::read(&t.inner))
+        }
+    }
+}
+
+impl<W: Write> Write for PooledBufWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + data.len() > self.capacity {
+            self.flush_buf()?;
+        }
+        if data.len() >= self.capacity {
+            self.inner.write(data)
+        } else {
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for PooledBufWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush_buf();
+    }
+}
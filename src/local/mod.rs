@@ -57,30 +57,221 @@
 //!   current rust that means there will be a pool for thin references and a
 //!   pool for fat references).
 
-use crate::{Discriminant, IsoPoolable, Opaque};
+use crate::{
+    discriminant,
+    global::{self, evict::EvictReason, GPooled},
+    pool_discriminant,
+    pooled::Frozen,
+    reserve_pooled, shadow,
+    stats::{LocalCounters, PoolStats},
+    testing::faults::{injected, Fault},
+    AllocError, AllocFamily, Discriminant, IsoPoolable, Opaque,
+};
 use fxhash::FxHashMap;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     borrow::Borrow,
     cell::RefCell,
     collections::HashMap,
-    fmt::Display,
+    fmt::{self, Display},
     hash::Hash,
     mem::ManuallyDrop,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
     ptr,
-    sync::{LazyLock, Mutex},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        LazyLock, Mutex,
+    },
 };
 
+pub mod fast;
+pub mod io;
+pub mod shared;
+
 struct Pool<T: IsoPoolable> {
     max: usize,
     max_capacity: usize,
+    initial_capacity: usize,
+    budgeted: bool,
     data: Vec<T>,
+    counters: LocalCounters,
 }
 
 impl<T: IsoPoolable> Pool<T> {
-    fn new(max: usize, max_capacity: usize) -> Self {
-        Self { max, max_capacity, data: Vec::with_capacity(max) }
+    fn new(max: usize, max_capacity: usize, initial_capacity: usize) -> Self {
+        let budgeted = BUDGET.load(Ordering::Relaxed) != usize::MAX;
+        Self {
+            max,
+            max_capacity,
+            initial_capacity,
+            budgeted,
+            data: Vec::with_capacity(max),
+            counters: LocalCounters::new(),
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let name = std::any::type_name::<T>();
+        if injected(name, Fault::Orphan) {
+            // as if the type had no pool, so not a miss
+            return None;
+        }
+        let t = if injected(name, Fault::Empty) { None } else { self.data.pop() };
+        self.counters.looked(t.is_some());
+        let t = t?;
+        if self.budgeted {
+            BUDGET_USED.fetch_sub(t.allocated_bytes(), Ordering::Relaxed);
+        }
+        Some(t)
+    }
+
+    // pop one of the top CAPACITY_SCAN objects with room for `capacity`
+    // elements, if there is one
+    fn pop_with_capacity(&mut self, capacity: usize) -> Option<T> {
+        if capacity > self.max_capacity {
+            // nothing pooled can have room, any object will be grown
+            return self.pop();
+        }
+        let n = self.data.len();
+        match (n.saturating_sub(CAPACITY_SCAN)..n)
+            .rev()
+            .find(|i| self.data[*i].capacity() >= capacity)
+        {
+            Some(i) => {
+                self.data.swap(i, n - 1);
+                self.pop()
+            }
+            None => {
+                self.counters.looked(false);
+                None
+            }
+        }
+    }
+
+    // pop up to `n` objects into `out`, counting the rest as misses
+    fn pop_many(&mut self, n: usize, out: &mut Vec<T>) {
+        for i in 0..n {
+            match self.pop() {
+                Some(t) => out.push(t),
+                None => {
+                    for _ in i + 1..n {
+                        self.counters.looked(false)
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, t: T) -> Option<T> {
+        self.counters.returned();
+        match self.put(t) {
+            Ok(()) => None,
+            Err((t, reason)) => {
+                self.counters.discarded(reason);
+                Some(t)
+            }
+        }
+    }
+
+    fn put(&mut self, t: T) -> Result<(), (T, EvictReason)> {
+        if t.capacity() > self.max_capacity {
+            return Err((t, EvictReason::Oversized));
+        }
+        if self.data.len() >= self.max
+            || injected(std::any::type_name::<T>(), Fault::Full)
+        {
+            return Err((t, EvictReason::Full));
+        }
+        if self.budgeted {
+            let bytes = t.allocated_bytes();
+            let used = BUDGET_USED.fetch_add(bytes, Ordering::Relaxed);
+            if used + bytes > BUDGET.load(Ordering::Relaxed) {
+                BUDGET_USED.fetch_sub(bytes, Ordering::Relaxed);
+                BUDGET_REJECTED.fetch_add(1, Ordering::Relaxed);
+                return Err((t, EvictReason::OverBudget));
+            }
+        }
+        self.data.push(t);
+        Ok(())
+    }
+
+    // allocate up to `n` objects into the pool, returning how many fit
+    fn prefill(&mut self, n: usize, capacity: usize) -> usize {
+        for i in 0..n {
+            let mut t = alloc::<T>(self.initial_capacity);
+            if (capacity > 0 && t.try_reserve(capacity).is_err()) || self.put(t).is_err()
+            {
+                return i;
+            }
+        }
+        n
+    }
+}
+
+impl<T: IsoPoolable> Drop for Pool<T> {
+    fn drop(&mut self) {
+        if self.budgeted {
+            let bytes: usize = self.data.iter().map(|t| t.allocated_bytes()).sum();
+            BUDGET_USED.fetch_sub(bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+static BUDGET: AtomicUsize = AtomicUsize::new(usize::MAX);
+static BUDGET_USED: AtomicUsize = AtomicUsize::new(0);
+static BUDGET_REJECTED: AtomicU64 = AtomicU64::new(0);
+
+/// Set a process wide limit on the bytes retained by all local pools.
+///
+/// When the limit is reached objects returned to any local pool are dropped
+/// instead of pooled, so local pools can't collectively exceed it. `None`
+/// removes the limit.
+///
+/// The budget is only enforced by pools created after it is first set, like
+/// [set_size] pools that already exist are not affected, so set it at
+/// startup before using local pools. Budgeted pools update a shared atomic
+/// counter on every take and insert, which costs some of the speed that
+/// makes local pools attractive.
+///
+/// ```
+/// use poolshark::local::{self, LPooled};
+///
+/// local::set_budget(Some(1024));
+/// let mut a: LPooled<Vec<u8>> = LPooled::take();
+/// let mut b: LPooled<Vec<u8>> = LPooled::take();
+/// a.reserve_exact(1000);
+/// b.reserve_exact(1000);
+/// drop(a); // pooled
+/// drop(b); // over budget, really dropped
+/// let st = local::budget();
+/// assert_eq!(st.retained_bytes, 1000);
+/// assert_eq!(st.rejected, 1);
+/// ```
+pub fn set_budget(max_bytes: Option<usize>) {
+    BUDGET.store(max_bytes.unwrap_or(usize::MAX), Ordering::Relaxed)
+}
+
+/// The state of the local pool budget, see [set_budget]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetStats {
+    /// The limit, if one is set
+    pub max_bytes: Option<usize>,
+    /// The bytes currently retained by budgeted local pools
+    pub retained_bytes: usize,
+    /// The number of returned objects that were dropped because the budget
+    /// was exhausted
+    pub rejected: u64,
+}
+
+/// Return the state of the local pool budget
+pub fn budget() -> BudgetStats {
+    let max = BUDGET.load(Ordering::Relaxed);
+    BudgetStats {
+        max_bytes: if max == usize::MAX { None } else { Some(max) },
+        retained_bytes: BUDGET_USED.load(Ordering::Relaxed),
+        rejected: BUDGET_REJECTED.load(Ordering::Relaxed),
     }
 }
 
@@ -89,11 +280,17 @@ thread_local! {
         RefCell::new(HashMap::default());
 }
 
+// how many pooled objects take_with_capacity looks at
+const CAPACITY_SCAN: usize = 4;
+
 const DEFAULT_SIZES: (usize, usize) = (1024, 1024);
 
 static SIZES: LazyLock<Mutex<FxHashMap<Discriminant, (usize, usize)>>> =
     LazyLock::new(|| Mutex::new(FxHashMap::default()));
 
+static INITIAL_CAPACITY: LazyLock<Mutex<FxHashMap<Discriminant, usize>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
 // This is safe because:
 // 1. Containers are reset before being returned to pools, so they contain no values
 // 2. We only reuse pools for types with identical memory layouts (same size/alignment via Discriminant)
@@ -109,7 +306,7 @@ where
     // pool destructor. This is why we must use try_with on the thread local
     let res = POOLS.try_with(|pools| match pools.try_borrow_mut() {
         Err(_) => (f.take().unwrap())(None),
-        Ok(mut pools) => match T::DISCRIMINANT {
+        Ok(mut pools) => match pool_discriminant::<T>() {
             Some(d) => {
                 let pool = pools.entry(d).or_insert_with(|| {
                     let (size, cap) = sizes.unwrap_or_else(|| {
@@ -120,13 +317,16 @@ where
                             .map(|(s, c)| (*s, *c))
                             .unwrap_or(DEFAULT_SIZES)
                     });
-                    let b = Box::new(Pool::<T>::new(size, cap));
+                    let init =
+                        INITIAL_CAPACITY.lock().unwrap().get(&d).copied().unwrap_or(0);
+                    let b = Box::new(Pool::<T>::new(size, cap, init));
                     let t = Box::into_raw(b) as *mut ();
-                    let drop = Some(Box::new(|t: *mut ()| unsafe {
+                    let drop = Box::new(|t: *mut ()| unsafe {
                         drop(Box::from_raw(t as *mut Pool<T>))
-                    }) as Box<dyn FnOnce(*mut ())>);
-                    Opaque { t, drop }
+                    });
+                    Opaque::new::<T>(d, t, drop)
                 });
+                pool.check::<T>(d);
                 (f.take().unwrap())(unsafe { Some(&mut *(pool.t as *mut Pool<T>)) })
             }
             None => (f.take().unwrap())(None),
@@ -142,6 +342,7 @@ where
 ///
 /// Note this will happen automatically when the thread dies.
 pub fn clear() {
+    fast::invalidate();
     POOLS.with_borrow_mut(|pools| pools.clear())
 }
 
@@ -149,8 +350,9 @@ pub fn clear() {
 ///
 /// This will happen automatically when the current thread dies.
 pub fn clear_type<T: IsoPoolable>() {
+    fast::invalidate();
     POOLS.with_borrow_mut(|pools| {
-        if let Some(d) = T::DISCRIMINANT {
+        if let Some(d) = discriminant::<T>() {
             pools.remove(&d);
         }
     })
@@ -162,7 +364,7 @@ pub fn clear_type<T: IsoPoolable>() {
 /// will use the specified size as their max size. If you wish to resize an existing pool you
 /// can first clear_type (or clear) and then set_size.
 pub fn set_size<T: IsoPoolable>(max_pool_size: usize, max_element_capacity: usize) {
-    if let Some(d) = T::DISCRIMINANT {
+    if let Some(d) = discriminant::<T>() {
         SIZES.lock().unwrap().insert(d, (max_pool_size, max_element_capacity));
     }
 }
@@ -171,20 +373,109 @@ pub fn set_size<T: IsoPoolable>(max_pool_size: usize, max_element_capacity: usiz
 ///
 /// If get_size returns None then the type will not be pooled.
 pub fn get_size<T: IsoPoolable>() -> Option<(usize, usize)> {
-    T::DISCRIMINANT.map(|d| {
+    discriminant::<T>().map(|d| {
         SIZES.lock().unwrap().get(&d).map(|(s, c)| (*s, *c)).unwrap_or(DEFAULT_SIZES)
     })
 }
 
+/// Return a snapshot of this thread's pool for `T`, or `None` if `T` isn't
+/// pooled, see the [stats](crate::stats) module.
+///
+/// The pool is created if it doesn't exist yet. Local pools only record the
+/// [UsageCounts](crate::stats::UsageCounts), and only with the `stats`
+/// feature.
+///
+/// ```
+/// use poolshark::local::{self, LPooled};
+///
+/// LPooled::<Vec<u16>>::take().push(0);
+/// let st = local::stats::<Vec<u16>>().unwrap();
+/// assert_eq!(st.len, 1);
+/// # #[cfg(feature = "stats")]
+/// assert_eq!((st.counts.misses, st.counts.returns), (1, 1));
+/// ```
+pub fn stats<T: IsoPoolable>() -> Option<PoolStats> {
+    with_pool(None, |pool: Option<&mut Pool<T>>| {
+        pool.map(|p| {
+            let mut stats = PoolStats {
+                len: p.data.len(),
+                capacity: p.max,
+                max_elt_capacity: p.max_capacity,
+                retained_bytes: p.data.iter().map(|t| t.allocated_bytes()).sum(),
+                ..PoolStats::default()
+            };
+            p.counters.fill(&mut stats);
+            stats
+        })
+    })
+}
+
+/// Set the capacity reserved for newly allocated `T`s.
+///
+/// When a take finds the pool empty the new object will have room for at
+/// least `capacity` elements, so cold start objects are born the right size.
+/// Like [set_size] this only affects pools created after it is called.
+pub fn set_initial_capacity<T: IsoPoolable>(capacity: usize) {
+    if let Some(d) = discriminant::<T>() {
+        INITIAL_CAPACITY.lock().unwrap().insert(d, capacity);
+    }
+}
+
+/// Allocate `n` new `T`s into this thread's pool now, so the first takes
+/// don't have to, returning how many were added.
+///
+/// The objects get the [initial capacity](set_initial_capacity) for `T`.
+/// It stops early if the pool is full, or over the [budget](set_budget).
+///
+/// ```
+/// use poolshark::local::{self, LPooled};
+///
+/// local::set_initial_capacity::<Vec<u64>>(256);
+/// assert_eq!(local::prefill::<Vec<u64>>(8), 8);
+/// assert!(LPooled::<Vec<u64>>::take().capacity() >= 256);
+/// ```
+pub fn prefill<T: IsoPoolable>(n: usize) -> usize {
+    with_pool(None, |pool: Option<&mut Pool<T>>| pool.map_or(0, |p| p.prefill(n, 0)))
+}
+
+/// Like [prefill], but reserve room for `capacity` elements in each
+/// object. Objects bigger than the pool's maximum element capacity, see
+/// [set_size], are not added. It also stops if an allocation fails.
+pub fn prefill_with_capacity<T: IsoPoolable>(n: usize, capacity: usize) -> usize {
+    with_pool(None, |pool: Option<&mut Pool<T>>| {
+        pool.map_or(0, |p| p.prefill(n, capacity))
+    })
+}
+
+// a new T for a take that missed, with room for `initial_capacity` elements
+fn alloc<T: IsoPoolable>(initial_capacity: usize) -> T {
+    let mut t = T::empty();
+    if initial_capacity > 0 {
+        // this is only a hint, if it fails the first use will try again
+        let _ = t.try_reserve(initial_capacity);
+    }
+    t
+}
+
 fn take_inner<T: IsoPoolable>(sizes: Option<(usize, usize)>) -> T {
-    with_pool(sizes, |pool| pool.and_then(|p| p.data.pop())).unwrap_or_else(|| T::empty())
+    with_pool(sizes, |pool| match pool {
+        None => Err(0),
+        Some(p) => p.pop().ok_or(p.initial_capacity),
+    })
+    .unwrap_or_else(alloc)
+}
+
+/// Take a T from the pool, or `None` if there is no pool for `T` or it is
+/// empty.
+pub(crate) fn try_take<T: IsoPoolable>() -> Option<T> {
+    with_pool(None, |pool| pool.and_then(|p| p.pop()))
 }
 
 /// Take a T from the pool.
 ///
 /// If there is no pool for T or there are no Ts pooled then create a new empty T.
 pub fn take<T: IsoPoolable>() -> T {
-    take_inner(None)
+    shadow::take::<T, _, _>(|| take_inner(None), |t| t.capacity())
 }
 
 /// Take a T from the pool with custom pool sizes.
@@ -192,7 +483,138 @@ pub fn take<T: IsoPoolable>() -> T {
 /// If there is no pool for T or there are no Ts pooled then create a new empty T.
 /// Configures the max size and max_elt size of the pool if it has not already been created.
 pub fn take_sz<T: IsoPoolable>(max: usize, max_elt: usize) -> T {
-    take_inner(Some((max, max_elt)))
+    shadow::take::<T, _, _>(|| take_inner(Some((max, max_elt))), |t| t.capacity())
+}
+
+/// Take `n` `T`s, as if by calling [take] `n` times, but looking up this
+/// thread's pool only once.
+///
+/// The pooled objects are popped up front, and the rest allocated as the
+/// iterator is advanced. Pooled objects the iterator didn't get to go back
+/// to the pool when it is dropped.
+///
+/// ```
+/// use poolshark::local::{self, LPooled};
+///
+/// let batch: Vec<LPooled<String>> =
+///     local::take_many::<String>(100).map(LPooled::from).collect();
+/// assert_eq!(batch.len(), 100);
+/// ```
+pub fn take_many<T: IsoPoolable>(n: usize) -> TakeMany<T> {
+    let mut popped: LPooled<Vec<T>> = LPooled::take();
+    let initial_capacity = with_pool(None, |pool: Option<&mut Pool<T>>| match pool {
+        None => 0,
+        Some(p) => {
+            p.pop_many(n, &mut popped);
+            p.initial_capacity
+        }
+    });
+    TakeMany { popped, remaining: n, initial_capacity }
+}
+
+/// The objects taken by [take_many]
+pub struct TakeMany<T: IsoPoolable> {
+    popped: LPooled<Vec<T>>,
+    remaining: usize,
+    initial_capacity: usize,
+}
+
+impl<T: IsoPoolable> fmt::Debug for TakeMany<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TakeMany").field("remaining", &self.remaining).finish()
+    }
+}
+
+impl<T: IsoPoolable> Iterator for TakeMany<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.popped.pop().unwrap_or_else(|| alloc(self.initial_capacity)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: IsoPoolable> ExactSizeIterator for TakeMany<T> {}
+
+impl<T: IsoPoolable> Drop for TakeMany<T> {
+    fn drop(&mut self) {
+        for t in self.popped.drain(..) {
+            // they came out of the pool, so they are already reset
+            drop(unsafe { insert_raw(t) })
+        }
+    }
+}
+
+/// Take a T with room for at least `capacity` elements from the pool.
+///
+/// The most recently pooled few objects are checked for one that already
+/// has enough room, the others stay in the pool. If none of them is big
+/// enough a new T is allocated with room for `capacity`.
+///
+/// ```
+/// use poolshark::local;
+///
+/// local::insert(Vec::<u8>::with_capacity(512));
+/// local::insert(Vec::<u8>::with_capacity(16));
+/// assert!(local::take_with_capacity::<Vec<u8>>(512).capacity() >= 512);
+/// assert_eq!(local::take::<Vec<u8>>().capacity(), 16);
+/// ```
+///
+/// # Panics
+///
+/// If the allocation fails, use [try_take_or_alloc] to handle that.
+pub fn take_with_capacity<T: IsoPoolable>(capacity: usize) -> T {
+    match try_take_or_alloc(capacity) {
+        Ok(t) => t,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Take a T with room for at least `capacity` elements from the pool.
+///
+/// Like [take_with_capacity], but the capacity is reserved with fallible
+/// allocation, so if the allocator fails an error is returned instead of
+/// aborting the process.
+pub fn try_take_or_alloc<T: IsoPoolable>(capacity: usize) -> Result<T, AllocError> {
+    let mut t = with_pool(None, |pool| match pool {
+        None => Err(0),
+        Some(p) => p.pop_with_capacity(capacity).ok_or(p.initial_capacity),
+    })
+    .unwrap_or_else(alloc::<T>);
+    match t.try_reserve(capacity) {
+        Ok(()) => Ok(t),
+        Err(e) => {
+            insert(t);
+            Err(e)
+        }
+    }
+}
+
+/// Take a `T` from the pool of its [AllocFamily] base.
+///
+/// For example `take_family::<VecDeque<u8>>()` takes a `Vec<u8>` and turns
+/// it into a deque. If there is no pool or it is empty a new `T` is made from
+/// an empty base.
+pub fn take_family<T: AllocFamily>() -> T {
+    T::from_base(take::<T::Base>())
+}
+
+/// Insert a `T` into the pool of its [AllocFamily] base.
+///
+/// `t` is reset, converted, and inserted with [insert]. If the pool won't
+/// hold it the converted base is returned.
+pub fn insert_family<T: AllocFamily>(mut t: T) -> Option<T::Base> {
+    if t.try_reset().is_err() {
+        return Some(t.into_base());
+    }
+    insert(t.into_base())
 }
 
 unsafe fn insert_raw_inner<T: IsoPoolable>(
@@ -200,11 +622,8 @@ unsafe fn insert_raw_inner<T: IsoPoolable>(
     t: T,
 ) -> Option<T> {
     with_pool(sizes, |pool| match pool {
-        Some(pool) if pool.data.len() < pool.max && t.capacity() <= pool.max_capacity => {
-            pool.data.push(t);
-            None
-        }
-        None | Some(_) => Some(t),
+        Some(pool) => pool.push(t),
+        None => Some(t),
     })
 }
 
@@ -241,17 +660,48 @@ pub unsafe fn insert_raw_sz<T: IsoPoolable>(
 ///
 /// If there is no space in the pool available to hold T then return it, otherwise return None.
 /// T will be reset before it is inserted into the pool. Reset must ensure that T is EMPTY.
+/// If the reset fails T is returned.
 pub fn insert<T: IsoPoolable>(mut t: T) -> Option<T> {
-    t.reset();
+    if t.try_reset().is_err() {
+        return Some(t);
+    }
     unsafe { insert_raw(t) }
 }
 
+/// Insert every `T` in `iter` into the pool, as if by calling [insert] on
+/// each, looking up the thread local pool once for the whole batch.
+///
+/// Objects that the pool won't hold are dropped. The objects are reset
+/// while the pool is in use, so pooled objects they own are dropped
+/// instead of going back to their own pools, use [insert] for those.
+///
+/// ```
+/// use poolshark::local;
+///
+/// let batch: Vec<Vec<u8>> = (0..4).map(|_| Vec::with_capacity(64)).collect();
+/// local::insert_many(batch);
+/// assert_eq!(local::take::<Vec<u8>>().capacity(), 64);
+/// ```
+pub fn insert_many<T: IsoPoolable, I: IntoIterator<Item = T>>(iter: I) {
+    with_pool(None, |pool: Option<&mut Pool<T>>| {
+        if let Some(pool) = pool {
+            for mut t in iter {
+                if t.try_reset().is_ok() {
+                    pool.push(t);
+                }
+            }
+        }
+    })
+}
+
 /// Insert a T into the pool with custom pool sizes.
 ///
 /// If there is no space in the pool available to hold T then return it, otherwise return None.
 /// T will be reset before it is inserted into the pool. Reset must ensure that T is EMPTY.
 pub fn insert_sz<T: IsoPoolable>(max: usize, max_elt: usize, mut t: T) -> Option<T> {
-    t.reset();
+    if t.try_reset().is_err() {
+        return Some(t);
+    }
     unsafe { insert_raw_inner(Some((max, max_elt)), t) }
 }
 
@@ -312,6 +762,18 @@ impl Borrow<str> for LPooled<String> {
     }
 }
 
+impl Borrow<Path> for LPooled<PathBuf> {
+    fn borrow(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl<T> Borrow<[T]> for LPooled<Vec<T>> {
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
 impl<T: IsoPoolable> Default for LPooled<T> {
     fn default() -> Self {
         Self::take()
@@ -333,6 +795,57 @@ impl<T: IsoPoolable> LPooled<T> {
         Self(ManuallyDrop::new(take_sz(max, max_elements)))
     }
 
+    /// Take an object with room for at least `capacity` elements from the
+    /// pool, see [take_with_capacity](super::take_with_capacity).
+    ///
+    /// # Panics
+    ///
+    /// If the allocation fails, use [try_take_or_alloc](Self::try_take_or_alloc)
+    /// to handle that.
+    pub fn take_with_capacity(capacity: usize) -> Self {
+        Self(ManuallyDrop::new(take_with_capacity(capacity)))
+    }
+
+    /// Take an object with room for at least `capacity` elements from the pool.
+    ///
+    /// Returns an error instead of aborting if the allocation fails.
+    pub fn try_take_or_alloc(capacity: usize) -> Result<Self, AllocError> {
+        Ok(Self(ManuallyDrop::new(try_take_or_alloc(capacity)?)))
+    }
+
+    /// Reserve room for `additional` more elements, aborting if the
+    /// allocation fails.
+    ///
+    /// Unlike reserving through the inner object, this knows about this
+    /// thread's pool, and calls the
+    /// [oversized reserve hook](crate::set_oversized_reserve_hook) if the
+    /// object grew too big to be pooled again.
+    pub fn reserve_pooled(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve_pooled(additional) {
+            panic!("{e}")
+        }
+    }
+
+    /// Like [reserve_pooled](Self::reserve_pooled), but returns an error if
+    /// the allocation fails.
+    pub fn try_reserve_pooled(&mut self, additional: usize) -> Result<(), AllocError> {
+        reserve_pooled(&mut *self.0, additional, || {
+            with_pool(None, |p: Option<&mut Pool<T>>| p.map(|p| p.max_capacity))
+        })
+    }
+
+    /// Detach the object from the local pool and attach it to the global
+    /// `pool`, so it is returned there when dropped, from any thread.
+    pub fn into_global(self, pool: &global::Pool<T>) -> GPooled<T> {
+        GPooled::with_weak(pool.downgrade(), self.detach())
+    }
+
+    /// Detach the object from the local pool and wrap it as an orphaned
+    /// [GPooled], which isn't returned to any pool when dropped.
+    pub fn into_gpooled_orphan(self) -> GPooled<T> {
+        GPooled::orphan(self.detach())
+    }
+
     /// Detach the object from the pool, returning the inner value.
     ///
     /// The detached object will not be returned to the pool when dropped.
@@ -343,6 +856,16 @@ impl<T: IsoPoolable> LPooled<T> {
     }
 }
 
+impl<T: IsoPoolable + Deref> LPooled<T> {
+    /// Freeze the object into an immutable handle to its contents.
+    ///
+    /// The allocation still goes back to the pool when the handle is
+    /// dropped, see [Frozen].
+    pub fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self)
+    }
+}
+
 impl<T: IsoPoolable> From<T> for LPooled<T> {
     fn from(t: T) -> Self {
         Self(ManuallyDrop::new(t))
@@ -425,3 +948,48 @@ impl<T: IsoPoolable + Extend<E>, E> FromIterator<E> for LPooled<T> {
         t
     }
 }
+
+/// Format into an [LPooled]`<String>` taken from the local pool.
+///
+/// This is a drop in replacement for [format!] in hot paths. The string is
+/// returned to the pool when it is dropped, so once the pool is warm
+/// formatting doesn't allocate.
+///
+/// ```
+/// use poolshark::pformat;
+///
+/// let s = pformat!("{} + {} = {}", 1, 2, 1 + 2);
+/// assert_eq!(&*s, "1 + 2 = 3");
+/// ```
+#[macro_export]
+macro_rules! pformat {
+    ($($arg:tt)*) => {{
+        let mut s = $crate::local::LPooled::<::std::string::String>::take();
+        ::std::fmt::Write::write_fmt(&mut *s, ::std::format_args!($($arg)*))
+            .expect("a formatting trait implementation returned an error");
+        s
+    }};
+}
+
+/// Format into a pooled string and write it to an [std::io::Write] with one
+/// `write_all` call.
+///
+/// [write!] on an `io::Write` issues one write per formatted piece, which is
+/// slow for unbuffered writers such as sockets. This formats the whole
+/// message with [pformat!] first, and evaluates to the `io::Result<()>` of
+/// the `write_all`.
+///
+/// ```
+/// use poolshark::pwrite;
+///
+/// let mut out = Vec::new();
+/// pwrite!(&mut out, "{}:{}", "key", 42).unwrap();
+/// assert_eq!(out, b"key:42");
+/// ```
+#[macro_export]
+macro_rules! pwrite {
+    ($dst:expr, $($arg:tt)*) => {{
+        let s = $crate::pformat!($($arg)*);
+        ::std::io::Write::write_all($dst, s.as_bytes())
+    }};
+}
@@ -0,0 +1,214 @@
+//! A locally pooled reference counted pointer.
+//!
+//! [LocalShared] is an `Rc` whose allocation, including the shared value, is
+//! recycled through a thread local pool when the last reference is dropped.
+//! It gives single threaded code, such as a game loop, shared scratch
+//! structures without going through the global pools.
+//!
+//! Unlike the other local pools, these pools are keyed by [TypeId], not by
+//! [Discriminant](crate::Discriminant), so `T` must be `'static` but doesn't
+//! have to be [IsoPoolable](crate::IsoPoolable). An `Rc` allocation holds the
+//! reference counts next to the value, so it can only ever be reused for the
+//! exact same type.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::local::shared::LocalShared;
+//!
+//! let mut a: LocalShared<Vec<u32>> = LocalShared::take();
+//! a.get_mut().unwrap().extend([1, 2, 3]);
+//! let b = a.clone(); // share it
+//! assert_eq!(&**b, &[1, 2, 3]);
+//! drop(a);
+//! drop(b); // the last reference resets the vec and pools the allocation
+//! ```
+use crate::Poolable;
+use fxhash::FxHashMap;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    mem::ManuallyDrop,
+    ops::Deref,
+    rc::Rc,
+};
+
+struct Pool<T> {
+    max: usize,
+    max_capacity: usize,
+    data: Vec<Rc<T>>,
+}
+
+thread_local! {
+    static POOLS: RefCell<FxHashMap<TypeId, Box<dyn Any>>> =
+        RefCell::new(HashMap::default());
+}
+
+fn with_pool<T, R, F>(sizes: Option<(usize, usize)>, f: F) -> R
+where
+    T: Poolable + 'static,
+    F: FnOnce(Option<&mut Pool<T>>) -> R,
+{
+    let mut f = Some(f);
+    // resetting or dropping a T may drop other LocalShareds, so like the
+    // other local pools we must tolerate being called recursively
+    let res = POOLS.try_with(|pools| match pools.try_borrow_mut() {
+        Err(_) => (f.take().unwrap())(None),
+        Ok(mut pools) => {
+            let pool = pools.entry(TypeId::of::<T>()).or_insert_with(|| {
+                let (max, max_capacity) = sizes.unwrap_or((1024, 1024));
+                Box::new(Pool::<T> { max, max_capacity, data: Vec::with_capacity(max) })
+            });
+            (f.take().unwrap())(pool.downcast_mut::<Pool<T>>())
+        }
+    });
+    match res {
+        Err(_) => (f.take().unwrap())(None),
+        Ok(r) => r,
+    }
+}
+
+/// Delete the shared pool for `T` on this thread.
+///
+/// Note this will happen automatically when the current thread dies.
+pub fn clear_type<T: Poolable + 'static>() {
+    POOLS.with_borrow_mut(|pools| {
+        pools.remove(&TypeId::of::<T>());
+    })
+}
+
+/// A locally pooled `Rc<T>`, see the [module level documentation](self).
+pub struct LocalShared<T: Poolable + 'static>(ManuallyDrop<Rc<T>>);
+
+impl<T: Poolable + 'static> LocalShared<T> {
+    fn take_inner(sizes: Option<(usize, usize)>) -> Self {
+        let rc = with_pool(sizes, |pool| pool.and_then(|p| p.data.pop()))
+            .unwrap_or_else(|| Rc::new(T::empty()));
+        Self(ManuallyDrop::new(rc))
+    }
+
+    /// Take an empty `T` from the pool, or allocate one if the pool is empty.
+    pub fn take() -> Self {
+        Self::take_inner(None)
+    }
+
+    /// Take an empty `T` from the pool with custom pool sizes.
+    ///
+    /// The sizes are only used if the pool for `T` on this thread has not
+    /// been created yet.
+    pub fn take_sz(max: usize, max_elements: usize) -> Self {
+        Self::take_inner(Some((max, max_elements)))
+    }
+
+    /// Take an allocation from the pool and move `t` into it.
+    pub fn new(t: T) -> Self {
+        let mut s = Self::take();
+        // values in the pool are guaranteed to be unique
+        *s.get_mut().unwrap() = t;
+        s
+    }
+
+    /// Get a mutable reference to the value if this is the only reference.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        Rc::get_mut(&mut self.0)
+    }
+
+    /// Return the number of references to the value
+    pub fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+
+    /// Return true if `a` and `b` point to the same allocation
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Rc::ptr_eq(&a.0, &b.0)
+    }
+
+    /// Return a pointer to the shared value
+    pub fn as_ptr(&self) -> *const T {
+        Rc::as_ptr(&self.0)
+    }
+}
+
+impl<T: Poolable + Clone + 'static> LocalShared<T> {
+    /// Get a mutable reference to the value, cloning it into a new pooled
+    /// allocation if there are other references.
+    pub fn make_mut(&mut self) -> &mut T {
+        if Rc::get_mut(&mut self.0).is_none() {
+            *self = Self::new((**self.0).clone());
+        }
+        Rc::get_mut(&mut self.0).unwrap()
+    }
+}
+
+impl<T: Poolable + 'static> Drop for LocalShared<T> {
+    fn drop(&mut self) {
+        let mut rc = unsafe { ManuallyDrop::take(&mut self.0) };
+        if let Some(t) = Rc::get_mut(&mut rc)
+            && t.try_reset().is_ok()
+        {
+            let cap = t.capacity();
+            with_pool(None, |pool: Option<&mut Pool<T>>| match pool {
+                Some(p) if p.data.len() < p.max && cap <= p.max_capacity => {
+                    p.data.push(rc);
+                    None
+                }
+                None | Some(_) => Some(rc),
+            });
+        }
+    }
+}
+
+impl<T: Poolable + 'static> Clone for LocalShared<T> {
+    fn clone(&self) -> Self {
+        Self(ManuallyDrop::new(Rc::clone(&self.0)))
+    }
+}
+
+impl<T: Poolable + 'static> Default for LocalShared<T> {
+    fn default() -> Self {
+        Self::take()
+    }
+}
+
+impl<T: Poolable + 'static> Deref for LocalShared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Poolable + 'static> AsRef<T> for LocalShared<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Poolable + Debug + 'static> Debug for LocalShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self.0).fmt(f)
+    }
+}
+
+impl<T: Poolable + Display + 'static> Display for LocalShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self.0).fmt(f)
+    }
+}
+
+impl<T: Poolable + PartialEq + 'static> PartialEq for LocalShared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self.0 == **other.0
+    }
+}
+
+impl<T: Poolable + Eq + 'static> Eq for LocalShared<T> {}
+
+impl<T: Poolable + Hash + 'static> Hash for LocalShared<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self.0).hash(state)
+    }
+}
@@ -0,0 +1,201 @@
+//! Writing log records through pooled buffers.
+//!
+//! High volume logging formats every record into a freshly allocated
+//! buffer before writing it. A [RecordSink] wraps the destination, and
+//! each [RecordWriter] it hands out collects one record in a local pooled
+//! `Vec<u8>`, and writes it to the destination with one `write_all` under
+//! the sink's lock when it is dropped, so records from different threads
+//! never interleave and formatting them doesn't allocate.
+//!
+//! A `RecordSink` in a static plugs into `tracing-subscriber`, whose
+//! `with_writer` takes any closure returning an `io::Write`, e.g.
+//! `tracing_subscriber::fmt().with_writer(|| SINK.writer())`. With the
+//! `log` feature [PooledLogger] is a complete `log::Log` implementation
+//! built on it.
+//!
+//! Records longer than the local pool's maximum element capacity for
+//! `Vec<u8>`, 1024 bytes by default, are written but not pooled, see
+//! [local::set_size](crate::local::set_size).
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::log::RecordSink;
+//! use std::io::Write;
+//!
+//! let sink = RecordSink::new(Vec::new());
+//! write!(sink.writer(), "INFO {}: {}\n", "app", "started").unwrap();
+//! write!(sink.writer(), "WARN {}: {}\n", "app", "slow").unwrap();
+//! assert_eq!(sink.into_inner(), b"INFO app: started\nWARN app: slow\n");
+//! ```
+use crate::local::LPooled;
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::Mutex,
+};
+
+/// A destination for log records, see the
+/// [module level documentation](self).
+pub struct RecordSink<W>(Mutex<W>);
+
+impl<W> fmt::Debug for RecordSink<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordSink").finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> RecordSink<W> {
+    /// Write records to `w`
+    pub const fn new(w: W) -> Self {
+        Self(Mutex::new(w))
+    }
+
+    /// Start a record, it is written when the writer is dropped or flushed
+    pub fn writer(&self) -> RecordWriter<'_, W> {
+        RecordWriter { sink: self, buf: LPooled::take() }
+    }
+
+    /// Flush the destination
+    pub fn flush(&self) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
+
+    /// Return the destination
+    pub fn into_inner(self) -> W {
+        self.0.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, record: &[u8]) -> io::Result<()> {
+        // a panic while writing a record doesn't make the destination unusable
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).write_all(record)
+    }
+}
+
+/// One log record being written to a [RecordSink].
+///
+/// Everything written is collected in a pooled buffer, and written to the
+/// sink when the writer is dropped, ignoring errors, or when it is flushed,
+/// reporting them.
+pub struct RecordWriter<'a, W: Write> {
+    sink: &'a RecordSink<W>,
+    buf: LPooled<Vec<u8>>,
+}
+
+impl<W: Write> fmt::Debug for RecordWriter<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordWriter").field("len", &self.buf.len()).finish()
+    }
+}
+
+impl<W: Write> Write for RecordWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.buf.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let res = self.sink.emit(&self.buf);
+        self.buf.clear();
+        res
+    }
+}
+
+impl<W: Write> Drop for RecordWriter<'_, W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Format `record` as `LEVEL target: message` followed by a newline, the
+/// default format of [PooledLogger]
+#[cfg(feature = "log")]
+pub fn default_format(w: &mut dyn Write, record: &::log::Record) -> io::Result<()> {
+    writeln!(w, "{:<5} {}: {}", record.level(), record.target(), record.args())
+}
+
+/// A `log::Log` that formats each record into a pooled buffer and writes
+/// it to a [RecordSink].
+///
+/// ```
+/// use poolshark::log::PooledLogger;
+///
+/// PooledLogger::new(std::io::stderr(), log::LevelFilter::Info).install().unwrap();
+/// log::info!("started");
+/// ```
+#[cfg(feature = "log")]
+pub struct PooledLogger<W> {
+    sink: RecordSink<W>,
+    level: ::log::LevelFilter,
+    format: fn(&mut dyn Write, &::log::Record) -> io::Result<()>,
+}
+
+#[cfg(feature = "log")]
+impl<W> fmt::Debug for PooledLogger<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledLogger").field("level", &self.level).finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "log")]
+impl<W: Write + Send + 'static> PooledLogger<W> {
+    /// Log records at `level` and above to `w` in the [default_format]
+    pub fn new(w: W, level: ::log::LevelFilter) -> Self {
+        Self { sink: RecordSink::new(w), level, format: default_format }
+    }
+
+    /// Format records with `format` instead of the [default_format]
+    pub fn with_format(
+        mut self,
+        format: fn(&mut dyn Write, &::log::Record) -> io::Result<()>,
+    ) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Return the sink the logger writes to
+    pub fn sink(&self) -> &RecordSink<W> {
+        &self.sink
+    }
+
+    /// Return the destination
+    pub fn into_inner(self) -> W {
+        self.sink.into_inner()
+    }
+
+    /// Make this the global logger, and set the maximum log level to the
+    /// logger's level. The logger is leaked, it lives for the rest of the
+    /// program.
+    pub fn install(self) -> Result<(), ::log::SetLoggerError> {
+        let level = self.level;
+        ::log::set_logger(Box::leak(Box::new(self)))?;
+        ::log::set_max_level(level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log")]
+impl<W: Write + Send> ::log::Log for PooledLogger<W> {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &::log::Record) {
+        if self.enabled(record.metadata()) {
+            let mut w = self.sink.writer();
+            let _ = (self.format)(&mut w, record);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = self.sink.flush();
+    }
+}
@@ -3,20 +3,44 @@
 //! This module provides pooling support for standard library types:
 //!
 //! - **Containers**: `Vec<T>`, `VecDeque<T>`, `HashMap<K, V>`, `HashSet<K>`
-//! - **Strings**: `String`
+//! - **Strings**: `String`, `PathBuf`
 //! - **Optional containers**: `Option<T>` where `T: Poolable`
-//! - **IndexMap types** (with `indexmap` feature): `IndexMap<K, V>`, `IndexSet<K>`
+//! - **Trait object vectors**: [`ErasedVec`], a `Vec<Box<dyn Trait>>`
+//! - **Fixed capacity buffers**: [`FixedVec`], a vec that never grows
+//! - **Pinned objects** (global pools only): [`Pinned`], a [`PinPoolable`]
+//!   object that is never moved
+//! - **Linked lists**: [`NodeList`], a linked list that keeps its nodes
+//! - **Node based containers** (global pools only, best effort): `LinkedList<T>`,
+//!   `BTreeMap<K, V>`, `BTreeSet<K>`
+//! - **Frozen buffers**: [`Frozen`], a pooled stand in for `Box<str>` and `Box<[u8]>`
 //!
-//! You don't need to import anything from this module - the implementations are
-//! automatically available when you use the pooled types.
-use super::{location_id, Discriminant, IsoPoolable, Poolable};
+//! It also provides [`MapExt`], entry style helpers for maps of pooled values.
+//! `VecDeque<T>` and `String` implement [`AllocFamily`], so they can share the
+//! pools of `Vec<T>` and `Vec<u8>`.
+//! - **IndexMap types** (with `indexmap` feature): `IndexMap<K, V>`, `IndexSet<K>`,
+//!   and the slice oriented scratch wrappers [`IndexMapScratch`] and [`IndexSetScratch`]
+//!
+//! You don't need to import anything from this module to pool these types - the
+//! implementations are automatically available when you use the pooled types.
+use super::{
+    global::GPooled, local::LPooled, location_id, AllocError, AllocFamily, Discriminant,
+    IsoPoolable, PinPoolable, Poolable, ResetError,
+};
 #[cfg(feature = "indexmap")]
-use indexmap::{IndexMap, IndexSet};
+use indexmap::{map, set, IndexMap, IndexSet};
+#[cfg(feature = "indexmap")]
+use std::hash::RandomState;
 use std::{
-    cmp::Eq,
-    collections::{HashMap, HashSet, VecDeque},
+    borrow::Borrow,
+    cmp::{self, Eq},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
     default::Default,
-    hash::{BuildHasher, Hash},
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+    pin::Pin,
 };
 
 impl<K, V, R> Poolable for HashMap<K, V, R>
@@ -35,6 +59,14 @@ where
     fn capacity(&self) -> usize {
         HashMap::capacity(self)
     }
+
+    fn allocated_bytes(&self) -> usize {
+        HashMap::capacity(self) * (mem::size_of::<(K, V)>() + 1)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        HashMap::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
 }
 
 unsafe impl<K, V, R> IsoPoolable for HashMap<K, V, R>
@@ -63,6 +95,15 @@ where
     fn capacity(&self) -> usize {
         IndexMap::capacity(self)
     }
+
+    fn allocated_bytes(&self) -> usize {
+        IndexMap::capacity(self)
+            * (mem::size_of::<(K, V)>() + 2 * mem::size_of::<usize>())
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        IndexMap::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
 }
 
 #[cfg(feature = "indexmap")]
@@ -91,6 +132,14 @@ where
     fn capacity(&self) -> usize {
         HashSet::capacity(self)
     }
+
+    fn allocated_bytes(&self) -> usize {
+        HashSet::capacity(self) * (mem::size_of::<K>() + 1)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        HashSet::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
 }
 
 unsafe impl<K, R> IsoPoolable for HashSet<K, R>
@@ -119,6 +168,14 @@ where
     fn capacity(&self) -> usize {
         IndexSet::capacity(self)
     }
+
+    fn allocated_bytes(&self) -> usize {
+        IndexSet::capacity(self) * (mem::size_of::<K>() + 2 * mem::size_of::<usize>())
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        IndexSet::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
 }
 
 #[cfg(feature = "indexmap")]
@@ -131,6 +188,216 @@ where
         Discriminant::new_p2::<K, R>(location_id!());
 }
 
+/// An [IndexMap] intended to be handed out as a [map::Slice].
+///
+/// Scratch code often builds an ordered, index addressable result in an
+/// `IndexMap` and then only exposes it as a `&Slice<K, V>`. This wrapper
+/// derefs to the map for building, borrows as the slice for consumers, and
+/// resets with `truncate(0)` rather than `clear()`. `truncate` is the
+/// primitive indexmap defines the slice range operations in terms of, and it
+/// is documented to retain the capacity of both the index table and the entry
+/// vector, so the reset behavior stays the same as the std containers even if
+/// `clear` changes upstream.
+///
+/// It has its own discriminant, so it never shares a local pool with plain
+/// `IndexMap`s.
+#[cfg(feature = "indexmap")]
+#[derive(Debug, Clone)]
+pub struct IndexMapScratch<K, V, S = RandomState>(IndexMap<K, V, S>);
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> IndexMapScratch<K, V, S> {
+    /// Return the contents as a slice of key value pairs
+    pub fn as_slice(&self) -> &map::Slice<K, V> {
+        self.0.as_slice()
+    }
+
+    /// Return the contents as a mutable slice of key value pairs
+    pub fn as_mut_slice(&mut self) -> &mut map::Slice<K, V> {
+        self.0.as_mut_slice()
+    }
+
+    /// Unwrap the inner map
+    pub fn into_inner(self) -> IndexMap<K, V, S> {
+        self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> From<IndexMap<K, V, S>> for IndexMapScratch<K, V, S> {
+    fn from(m: IndexMap<K, V, S>) -> Self {
+        Self(m)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> Deref for IndexMapScratch<K, V, S> {
+    type Target = IndexMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> DerefMut for IndexMapScratch<K, V, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> Borrow<map::Slice<K, V>> for IndexMapScratch<K, V, S> {
+    fn borrow(&self) -> &map::Slice<K, V> {
+        self.0.as_slice()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S, E> Extend<E> for IndexMapScratch<K, V, S>
+where
+    IndexMap<K, V, S>: Extend<E>,
+{
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, R> Poolable for IndexMapScratch<K, V, R>
+where
+    K: Hash + Eq,
+    R: Default + BuildHasher,
+{
+    fn empty() -> Self {
+        Self(IndexMap::default())
+    }
+
+    fn reset(&mut self) {
+        self.0.truncate(0)
+    }
+
+    fn capacity(&self) -> usize {
+        IndexMap::capacity(&self.0)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        Poolable::allocated_bytes(&self.0)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        Poolable::try_reserve(&mut self.0, additional)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+unsafe impl<K, V, R> IsoPoolable for IndexMapScratch<K, V, R>
+where
+    K: Hash + Eq,
+    R: Default + BuildHasher,
+{
+    const DISCRIMINANT: Option<Discriminant> =
+        { Discriminant::new_p3::<K, V, R>(location_id!()) };
+}
+
+/// An [IndexSet] intended to be handed out as a [set::Slice].
+///
+/// This is the set version of [IndexMapScratch], see its documentation for
+/// details.
+#[cfg(feature = "indexmap")]
+#[derive(Debug, Clone)]
+pub struct IndexSetScratch<K, S = RandomState>(IndexSet<K, S>);
+
+#[cfg(feature = "indexmap")]
+impl<K, S> IndexSetScratch<K, S> {
+    /// Return the contents as a slice
+    pub fn as_slice(&self) -> &set::Slice<K> {
+        self.0.as_slice()
+    }
+
+    /// Unwrap the inner set
+    pub fn into_inner(self) -> IndexSet<K, S> {
+        self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S> From<IndexSet<K, S>> for IndexSetScratch<K, S> {
+    fn from(s: IndexSet<K, S>) -> Self {
+        Self(s)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S> Deref for IndexSetScratch<K, S> {
+    type Target = IndexSet<K, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S> DerefMut for IndexSetScratch<K, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S> Borrow<set::Slice<K>> for IndexSetScratch<K, S> {
+    fn borrow(&self) -> &set::Slice<K> {
+        self.0.as_slice()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S, E> Extend<E> for IndexSetScratch<K, S>
+where
+    IndexSet<K, S>: Extend<E>,
+{
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, R> Poolable for IndexSetScratch<K, R>
+where
+    K: Hash + Eq,
+    R: Default + BuildHasher,
+{
+    fn empty() -> Self {
+        Self(IndexSet::default())
+    }
+
+    fn reset(&mut self) {
+        self.0.truncate(0)
+    }
+
+    fn capacity(&self) -> usize {
+        IndexSet::capacity(&self.0)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        Poolable::allocated_bytes(&self.0)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        Poolable::try_reserve(&mut self.0, additional)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+unsafe impl<K, R> IsoPoolable for IndexSetScratch<K, R>
+where
+    K: Hash + Eq,
+    R: Default + BuildHasher,
+{
+    const DISCRIMINANT: Option<Discriminant> =
+        Discriminant::new_p2::<K, R>(location_id!());
+}
+
 impl<T> Poolable for Vec<T> {
     fn empty() -> Self {
         Vec::new()
@@ -143,64 +410,1055 @@ impl<T> Poolable for Vec<T> {
     fn capacity(&self) -> usize {
         Vec::capacity(self)
     }
+
+    fn allocated_bytes(&self) -> usize {
+        Vec::capacity(self) * mem::size_of::<T>()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        Vec::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
 }
 
 unsafe impl<T> IsoPoolable for Vec<T> {
     const DISCRIMINANT: Option<Discriminant> = Discriminant::new_p1::<T>(location_id!());
 }
 
-impl<T> Poolable for VecDeque<T> {
-    fn empty() -> Self {
-        VecDeque::new()
+/// Empty `v` and reuse its allocation as room for uninitialized `T`s.
+///
+/// `Vec<MaybeUninit<T>>` has the same layout as `Vec<T>`, so the two share
+/// a pool, and codecs that write into uninitialized memory can take a
+/// pooled `Vec<T>` and convert it, or take `Vec<MaybeUninit<T>>` directly.
+pub fn vec_into_uninit<T>(mut v: Vec<T>) -> Vec<MaybeUninit<T>> {
+    v.clear();
+    let mut v = mem::ManuallyDrop::new(v);
+    // MaybeUninit<T> has the same layout as T, and the vec is empty
+    unsafe { Vec::from_raw_parts(v.as_mut_ptr().cast(), 0, v.capacity()) }
+}
+
+/// Empty `v` and reuse its allocation for `T`s, the reverse of
+/// [vec_into_uninit]. Nothing is dropped, since the elements may not be
+/// initialized.
+pub fn vec_from_uninit<T>(mut v: Vec<MaybeUninit<T>>) -> Vec<T> {
+    v.clear();
+    let mut v = mem::ManuallyDrop::new(v);
+    // MaybeUninit<T> has the same layout as T, and the vec is empty
+    unsafe { Vec::from_raw_parts(v.as_mut_ptr().cast(), 0, v.capacity()) }
+}
+
+impl<T> LPooled<Vec<T>> {
+    /// Empty the vec and reuse it for uninitialized `T`s, see
+    /// [vec_into_uninit]. It goes back to the same pool.
+    ///
+    /// ```
+    /// use poolshark::local::LPooled;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut v: LPooled<Vec<u32>> = LPooled::take();
+    /// v.reserve(16);
+    /// let mut u = v.into_uninit();
+    /// u.resize(4, MaybeUninit::uninit());
+    /// for (i, x) in u.iter_mut().enumerate() {
+    ///     x.write(i as u32);
+    /// }
+    /// // every element was written above
+    /// let v = unsafe { u.assume_init() };
+    /// assert_eq!(&*v, &[0, 1, 2, 3]);
+    /// ```
+    pub fn into_uninit(self) -> LPooled<Vec<MaybeUninit<T>>> {
+        LPooled::from(vec_into_uninit(self.detach()))
     }
+}
 
-    fn reset(&mut self) {
-        self.clear()
+impl<T> LPooled<Vec<MaybeUninit<T>>> {
+    /// Empty the vec and reuse it for `T`s, see [vec_from_uninit]
+    pub fn into_init(self) -> LPooled<Vec<T>> {
+        LPooled::from(vec_from_uninit(self.detach()))
     }
 
-    fn capacity(&self) -> usize {
-        VecDeque::capacity(self)
+    /// Reuse the vec for `T`s, keeping its elements.
+    ///
+    /// # Safety
+    ///
+    /// Every element must be initialized, see `MaybeUninit::assume_init`.
+    pub unsafe fn assume_init(self) -> LPooled<Vec<T>> {
+        let mut v = mem::ManuallyDrop::new(self.detach());
+        let (len, cap) = (v.len(), v.capacity());
+        LPooled::from(unsafe { Vec::from_raw_parts(v.as_mut_ptr().cast(), len, cap) })
     }
 }
 
-unsafe impl<T> IsoPoolable for VecDeque<T> {
-    const DISCRIMINANT: Option<Discriminant> = Discriminant::new_p1::<T>(location_id!());
+/// A `Vec` of boxed trait objects, e.g. `ErasedVec<dyn Display>`.
+///
+/// `Vec<Box<dyn Trait>>` already implements [IsoPoolable] through the `Vec`
+/// implementation, because a `Box<dyn Trait>` is always a two word fat
+/// pointer with a fixed layout. This wrapper is the supported way to spell
+/// that out. It has its own discriminant built from the fat pointer layout,
+/// so every `ErasedVec` shares one local pool no matter which trait it holds,
+/// and none of them share with ordinary vectors of two word elements. The
+/// boxed objects are dropped when the vec is reset, only the buffer of
+/// pointers is reused.
+#[derive(Debug)]
+pub struct ErasedVec<T: ?Sized>(Vec<Box<T>>);
+
+impl<T: ?Sized> ErasedVec<T> {
+    /// Unwrap the inner vec
+    pub fn into_inner(self) -> Vec<Box<T>> {
+        self.0
+    }
 }
 
-impl Poolable for String {
+impl<T: ?Sized> Default for ErasedVec<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: ?Sized> From<Vec<Box<T>>> for ErasedVec<T> {
+    fn from(v: Vec<Box<T>>) -> Self {
+        Self(v)
+    }
+}
+
+impl<T: ?Sized> Deref for ErasedVec<T> {
+    type Target = Vec<Box<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> DerefMut for ErasedVec<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: ?Sized> Extend<Box<T>> for ErasedVec<T> {
+    fn extend<I: IntoIterator<Item = Box<T>>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<T: ?Sized> Poolable for ErasedVec<T> {
     fn empty() -> Self {
-        String::new()
+        Self(Vec::new())
     }
 
     fn reset(&mut self) {
-        self.clear()
+        self.0.clear()
     }
 
     fn capacity(&self) -> usize {
-        self.capacity()
+        self.0.capacity()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        Poolable::allocated_bytes(&self.0)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        Poolable::try_reserve(&mut self.0, additional)
     }
 }
 
-unsafe impl IsoPoolable for String {
-    const DISCRIMINANT: Option<Discriminant> = Discriminant::new(location_id!());
+unsafe impl<T: ?Sized> IsoPoolable for ErasedVec<T> {
+    const DISCRIMINANT: Option<Discriminant> =
+        Discriminant::new_p1::<Box<T>>(location_id!());
 }
 
-impl<T: Poolable> Poolable for Option<T> {
+/// A vec whose allocation is exactly `N` elements and never grows.
+///
+/// The buffer is allocated once, when the `FixedVec` is created, and every
+/// operation that would need more room fails instead of reallocating, so a
+/// pool of `FixedVec`s only ever hands out buffers of one predictable size.
+/// This is meant for real time code, such as audio processing, that can't
+/// tolerate surprise allocations or mismatched buffer sizes.
+///
+/// `N` is part of the discriminant, so each size gets its own local pool. It
+/// must be at most `0x7FFF` to be locally pooled.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct FixedVec<T, const N: usize>(Vec<T>);
+
+// not derived, a derived clone would only allocate room for the elements
+// it copies, and then grow on the next push
+impl<T: Clone, const N: usize> Clone for FixedVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut t = Self::new();
+        t.0.extend_from_slice(&self.0);
+        t
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.0.clone_from(&source.0)
+    }
+}
+
+impl<T, const N: usize> FixedVec<T, N> {
+    /// Allocate a new empty `FixedVec` with room for `N` elements
+    pub fn new() -> Self {
+        Self(Vec::with_capacity(N))
+    }
+
+    /// The number of elements the vec can hold, always `N`
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Return true if the vec holds `N` elements
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= N
+    }
+
+    /// Append `t`, or return it if the vec is full
+    pub fn push(&mut self, t: T) -> Result<(), T> {
+        if self.is_full() {
+            Err(t)
+        } else {
+            self.0.push(t);
+            Ok(())
+        }
+    }
+
+    /// Remove and return the last element
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Shorten the vec to `len` elements
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+
+    /// Remove all the elements
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Return the elements as a slice
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Return the elements as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+impl<T: Clone, const N: usize> FixedVec<T, N> {
+    /// Append all of `s`.
+    ///
+    /// If `s` doesn't fit nothing is appended and the number of elements that
+    /// would not fit is returned.
+    pub fn extend_from_slice(&mut self, s: &[T]) -> Result<(), usize> {
+        let room = N - self.0.len();
+        if s.len() > room {
+            Err(s.len() - room)
+        } else {
+            self.0.extend_from_slice(s);
+            Ok(())
+        }
+    }
+
+    /// Resize the vec to `len` elements, filling new slots with `t`.
+    ///
+    /// Returns `Err(len)` without changing anything if `len > N`.
+    pub fn resize(&mut self, len: usize, t: T) -> Result<(), usize> {
+        if len > N {
+            Err(len)
+        } else {
+            self.0.resize(len, t);
+            Ok(())
+        }
+    }
+}
+
+impl<T, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for FixedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> DerefMut for FixedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const N: usize> Poolable for FixedVec<T, N> {
     fn empty() -> Self {
-        None
+        Self::new()
     }
 
     fn reset(&mut self) {
-        if let Some(inner) = self {
-            inner.reset()
-        }
+        self.0.clear()
     }
 
     fn capacity(&self) -> usize {
-        self.as_ref().map(|i| i.capacity()).unwrap_or(0)
+        N
     }
 
-    fn really_dropped(&mut self) -> bool {
-        self.as_mut().map(|i| i.really_dropped()).unwrap_or(true)
+    fn allocated_bytes(&self) -> usize {
+        N * mem::size_of::<T>()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        if additional > N - self.0.len() {
+            Err(AllocError { additional })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+unsafe impl<T, const N: usize> IsoPoolable for FixedVec<T, N> {
+    const DISCRIMINANT: Option<Discriminant> =
+        Discriminant::new_p1_size::<T, N>(location_id!());
+}
+
+/// A [PinPoolable] object in its own heap allocation, so it can be pooled
+/// without ever being moved.
+///
+/// [GPooled] stores its object inline, so moving the handle moves the
+/// object, and pools reset objects through `&mut`, which would let them be
+/// moved too. A `Pinned` object lives in a pinned box that the pool only
+/// ever moves the pointer to. It is reset in place by
+/// [PinPoolable::reset], and dropped in place if the pool rejects it, so it
+/// keeps its address from when it is allocated until it is really dropped,
+/// no matter how many times it is taken. Use [as_mut](Pinned::as_mut) to
+/// get at it mutably. `Unpin` objects don't need this, see
+/// [GPooled::pin].
+///
+/// Pinned objects can only be pooled globally, local pools share one pool
+/// between types with the same layout, which would need them to be empty.
+pub struct Pinned<T>(Pin<Box<T>>);
+
+impl<T> Pinned<T> {
+    /// Return a pinned mutable reference to the object
+    pub fn as_mut(&mut self) -> Pin<&mut T> {
+        self.0.as_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pinned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> Deref for Pinned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: PinPoolable> Poolable for Pinned<T> {
+    fn empty() -> Self {
+        Self(Box::pin(T::empty()))
+    }
+
+    fn reset(&mut self) {
+        PinPoolable::reset(self.0.as_mut())
+    }
+
+    fn capacity(&self) -> usize {
+        PinPoolable::capacity(&*self.0)
+    }
+}
+
+impl<T> Poolable for VecDeque<T> {
+    fn empty() -> Self {
+        VecDeque::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        VecDeque::capacity(self)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        VecDeque::capacity(self) * mem::size_of::<T>()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        VecDeque::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
+}
+
+unsafe impl<T> IsoPoolable for VecDeque<T> {
+    const DISCRIMINANT: Option<Discriminant> = Discriminant::new_p1::<T>(location_id!());
+}
+
+impl<T> AllocFamily for VecDeque<T> {
+    type Base = Vec<T>;
+
+    fn into_base(self) -> Vec<T> {
+        Vec::from(self)
+    }
+
+    fn from_base(base: Vec<T>) -> Self {
+        VecDeque::from(base)
+    }
+}
+
+// the size of one node of a node based collection holding `T`, two links
+// and the element, ignoring allocator overhead
+fn node_bytes<T>() -> usize {
+    2 * mem::size_of::<usize>() + mem::size_of::<T>()
+}
+
+/// Best effort pooling of legacy linked lists, global pools only.
+///
+/// `LinkedList::clear` frees every node, so a pooled list keeps none of
+/// its spine, it only saves moving the list header around. Use [NodeList]
+/// to really reuse nodes. The capacity is the number of nodes the list
+/// held when it was returned, so the pool's `max_elt_capacity` still keeps
+/// out lists that grew huge.
+impl<T> Poolable for LinkedList<T> {
+    fn empty() -> Self {
+        LinkedList::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.len() * node_bytes::<T>()
+    }
+}
+
+/// Best effort pooling of b-tree maps, global pools only.
+///
+/// The standard b-tree has no way to keep its nodes when it is cleared, so
+/// like [LinkedList] a pooled map reuses none of its allocations. There is
+/// no node recycling shim for b-trees, code that can be changed should
+/// use a sorted `Vec` or an `IndexMap` sorted after it is built.
+impl<K, V> Poolable for BTreeMap<K, V> {
+    fn empty() -> Self {
+        BTreeMap::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.len() * mem::size_of::<(K, V)>()
+    }
+}
+
+/// Best effort pooling of b-tree sets, global pools only, see
+/// [BTreeMap]'s implementation
+impl<K> Poolable for BTreeSet<K> {
+    fn empty() -> Self {
+        BTreeSet::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.len() * mem::size_of::<K>()
+    }
+}
+
+/// A doubly linked list that keeps its nodes when elements are removed.
+///
+/// Removed elements leave their node on a spare list, and new elements
+/// reuse spare nodes before allocating, so a pooled `NodeList` keeps its
+/// whole spine between uses, the way a pooled `Vec` keeps its buffer. It
+/// is a shim for code built around linked lists that can't be rewritten to
+/// use a `Vec` or `VecDeque`; every operation is O(1) like the
+/// `LinkedList` it wraps.
+///
+/// The capacity is the number of nodes, in use or spare. Spare nodes are
+/// only freed when the list is dropped or [shrunk](Self::shrink_to_fit).
+pub struct NodeList<T> {
+    live: LinkedList<Option<T>>,
+    spare: LinkedList<Option<T>>,
+}
+
+impl<T> NodeList<T> {
+    /// Create an empty list with no nodes
+    pub const fn new() -> Self {
+        Self { live: LinkedList::new(), spare: LinkedList::new() }
+    }
+
+    /// The number of elements in the list
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Return true if the list has no elements
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    /// The number of nodes the list owns, in use or spare
+    pub fn capacity(&self) -> usize {
+        self.live.len() + self.spare.len()
+    }
+
+    // a one node list holding t, from the spare list if possible
+    fn node(&mut self, t: T) -> LinkedList<Option<T>> {
+        match self.spare.len() {
+            0 => LinkedList::from([Some(t)]),
+            n => {
+                let mut node = self.spare.split_off(n - 1);
+                *node.front_mut().unwrap() = Some(t);
+                node
+            }
+        }
+    }
+
+    // move the value out of a one node list, and keep the node
+    fn recycle(&mut self, mut node: LinkedList<Option<T>>) -> Option<T> {
+        let t = node.front_mut().and_then(Option::take);
+        self.spare.append(&mut node);
+        t
+    }
+
+    /// Append `t` to the back of the list
+    pub fn push_back(&mut self, t: T) {
+        let mut node = self.node(t);
+        self.live.append(&mut node)
+    }
+
+    /// Prepend `t` to the front of the list
+    pub fn push_front(&mut self, t: T) {
+        let mut node = self.node(t);
+        node.append(&mut self.live);
+        self.live = node
+    }
+
+    /// Remove and return the first element
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.live.is_empty() {
+            return None;
+        }
+        let rest = self.live.split_off(1);
+        let node = mem::replace(&mut self.live, rest);
+        self.recycle(node)
+    }
+
+    /// Remove and return the last element
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.live.len() {
+            0 => None,
+            n => {
+                let node = self.live.split_off(n - 1);
+                self.recycle(node)
+            }
+        }
+    }
+
+    /// Return the first element
+    pub fn front(&self) -> Option<&T> {
+        self.live.front().and_then(Option::as_ref)
+    }
+
+    /// Return the first element mutably
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.live.front_mut().and_then(Option::as_mut)
+    }
+
+    /// Return the last element
+    pub fn back(&self) -> Option<&T> {
+        self.live.back().and_then(Option::as_ref)
+    }
+
+    /// Return the last element mutably
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.live.back_mut().and_then(Option::as_mut)
+    }
+
+    /// Iterate over the elements front to back
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.live.iter().filter_map(Option::as_ref)
+    }
+
+    /// Iterate mutably over the elements front to back
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        self.live.iter_mut().filter_map(Option::as_mut)
+    }
+
+    /// Remove all the elements, keeping their nodes
+    pub fn clear(&mut self) {
+        for t in self.live.iter_mut() {
+            *t = None
+        }
+        self.spare.append(&mut self.live)
+    }
+
+    /// Allocate spare nodes until `additional` more elements can be pushed
+    /// without allocating
+    pub fn reserve(&mut self, additional: usize) {
+        while self.spare.len() < additional {
+            self.spare.push_back(None)
+        }
+    }
+
+    /// Free the spare nodes
+    pub fn shrink_to_fit(&mut self) {
+        self.spare.clear()
+    }
+}
+
+impl<T> Default for NodeList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NodeList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for NodeList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for t in iter {
+            self.push_back(t)
+        }
+    }
+}
+
+impl<T> FromIterator<T> for NodeList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl<T> Poolable for NodeList<T> {
+    fn empty() -> Self {
+        Self::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        NodeList::capacity(self)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        NodeList::capacity(self) * node_bytes::<Option<T>>()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.reserve(additional);
+        Ok(())
+    }
+}
+
+unsafe impl<T> IsoPoolable for NodeList<T> {
+    const DISCRIMINANT: Option<Discriminant> = Discriminant::new_p1::<T>(location_id!());
+}
+
+impl Poolable for String {
+    fn empty() -> Self {
+        String::new()
+    }
+
+    fn reset(&mut self) {
+        self.clear()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        String::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
+}
+
+unsafe impl IsoPoolable for String {
+    const DISCRIMINANT: Option<Discriminant> = Discriminant::new(location_id!());
+}
+
+impl AllocFamily for String {
+    type Base = Vec<u8>;
+
+    fn into_base(self) -> Vec<u8> {
+        self.into_bytes()
+    }
+
+    fn from_base(mut base: Vec<u8>) -> Self {
+        // it should already be empty, but don't trust it with utf8
+        base.clear();
+        unsafe { String::from_utf8_unchecked(base) }
+    }
+}
+
+impl Poolable for PathBuf {
+    fn empty() -> Self {
+        PathBuf::new()
+    }
+
+    fn reset(&mut self) {
+        self.as_mut_os_string().clear()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        PathBuf::try_reserve(self, additional).map_err(|_| AllocError { additional })
+    }
+}
+
+unsafe impl IsoPoolable for PathBuf {
+    const DISCRIMINANT: Option<Discriminant> = Discriminant::new(location_id!());
+}
+
+impl<T: Poolable> Poolable for Option<T> {
+    fn empty() -> Self {
+        None
+    }
+
+    fn reset(&mut self) {
+        if let Some(inner) = self {
+            inner.reset()
+        }
+    }
+
+    fn try_reset(&mut self) -> Result<(), ResetError> {
+        match self {
+            Some(inner) => inner.try_reset(),
+            None => Ok(()),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.as_ref().map(|i| i.capacity()).unwrap_or(0)
+    }
+
+    fn really_dropped(&mut self) -> bool {
+        self.as_mut().map(|i| i.really_dropped()).unwrap_or(true)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.as_ref().map(|i| i.allocated_bytes()).unwrap_or(0)
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        match self {
+            Some(i) => i.try_reserve(additional),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Entry style helpers for maps of pooled values.
+///
+/// These are implemented for the map types, so they can be called directly on
+/// an `LPooled<HashMap<..>>` or `GPooled<HashMap<..>>` through deref. Unlike
+/// the entry API they look the key up by reference, and only clone it when
+/// it is missing, so a map of `String` keys doesn't allocate a key on every
+/// call.
+///
+/// ```
+/// use poolshark::{local::LPooled, pooled::MapExt};
+/// use std::collections::HashMap;
+///
+/// let mut groups: LPooled<HashMap<String, LPooled<Vec<u32>>>> = LPooled::take();
+/// for (k, v) in [("a", 1), ("b", 2), ("a", 3)] {
+///     // missing values are taken from the local pool
+///     groups.get_or_take(k).push(v);
+/// }
+/// assert_eq!(&**groups["a"], &[1, 3]);
+/// ```
+pub trait MapExt<K, V> {
+    /// Return the value of `key`, inserting `f()` if it is missing.
+    fn get_or_insert_pooled<Q, F>(&mut self, key: &Q, f: F) -> &mut V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        F: FnOnce() -> V;
+
+    /// Return the value of `key`, inserting `V::default()` if it is missing.
+    ///
+    /// For `LPooled` and `GPooled` values the default is taken from the
+    /// thread local pool.
+    fn get_or_take<Q>(&mut self, key: &Q) -> &mut V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        V: Default,
+    {
+        self.get_or_insert_pooled(key, V::default)
+    }
+}
+
+impl<K, V, S> MapExt<K, V> for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn get_or_insert_pooled<Q, F>(&mut self, key: &Q, f: F) -> &mut V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        F: FnOnce() -> V,
+    {
+        if !self.contains_key(key) {
+            self.insert(key.to_owned(), f());
+        }
+        self.get_mut(key).unwrap()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> MapExt<K, V> for IndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn get_or_insert_pooled<Q, F>(&mut self, key: &Q, f: F) -> &mut V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        F: FnOnce() -> V,
+    {
+        let i = match self.get_index_of(key) {
+            Some(i) => i,
+            None => self.insert_full(key.to_owned(), f()).0,
+        };
+        &mut self[i]
+    }
+}
+
+/// An immutable view of a pooled buffer, the pooled equivalent of
+/// `Box<str>` or `Box<[u8]>`.
+///
+/// A `Box<str>` can't be pooled because it can't be emptied and refilled.
+/// Instead build the value in a pooled `String` or `Vec<u8>` and freeze it
+/// with [GPooled::freeze](crate::global::GPooled::freeze) or
+/// [LPooled::freeze](crate::local::LPooled::freeze). The frozen handle only
+/// gives out shared references to the contents, and when it is dropped the
+/// allocation goes back to the pool it came from to be filled again.
+///
+/// ```
+/// use poolshark::{global::GPooled, pooled::Frozen};
+/// use std::collections::HashMap;
+///
+/// let mut s: GPooled<String> = GPooled::take();
+/// s.push_str("hello");
+/// let key: Frozen<GPooled<String>> = s.freeze();
+/// let mut m = HashMap::new();
+/// m.insert(key, 42);
+/// assert_eq!(m["hello"], 42);
+/// drop(m); // the string goes back to the pool
+/// ```
+pub struct Frozen<P>(P);
+
+impl<P> Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref,
+{
+    /// Freeze `p`
+    pub fn new(p: P) -> Self {
+        Self(p)
+    }
+
+    /// Unwrap the mutable pooled buffer
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P> Deref for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref,
+{
+    type Target = <P::Target as Deref>::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P> AsRef<<P::Target as Deref>::Target> for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref,
+{
+    fn as_ref(&self) -> &<P::Target as Deref>::Target {
+        self
+    }
+}
+
+// a blanket Borrow impl would conflict with Borrow<T> for T, so spell out
+// the buffers that are useful as map keys
+macro_rules! frozen_borrow {
+    ($p:ident) => {
+        impl Borrow<str> for Frozen<$p<String>> {
+            fn borrow(&self) -> &str {
+                self
+            }
+        }
+
+        impl<T> Borrow<[T]> for Frozen<$p<Vec<T>>> {
+            fn borrow(&self) -> &[T] {
+                self
+            }
+        }
+    };
+}
+
+frozen_borrow!(GPooled);
+frozen_borrow!(LPooled);
+
+// Pooled keys are already looked up by unpooled queries, e.g. a `&str` in
+// a map of `GPooled<String>`, through the `Borrow` impls and the blanket
+// `Equivalent` impl, and a blanket impl here would conflict with it. These
+// go the other way, so a pooled key, e.g. one just decoded, can be looked
+// up in a map of unpooled keys without copying it.
+#[cfg(feature = "equivalent")]
+macro_rules! pooled_equivalent {
+    ($p:ident) => {
+        impl equivalent::Equivalent<String> for $p<String> {
+            fn equivalent(&self, key: &String) -> bool {
+                **self == *key
+            }
+        }
+
+        impl equivalent::Comparable<String> for $p<String> {
+            fn compare(&self, key: &String) -> cmp::Ordering {
+                (**self).cmp(key)
+            }
+        }
+
+        impl equivalent::Equivalent<PathBuf> for $p<PathBuf> {
+            fn equivalent(&self, key: &PathBuf) -> bool {
+                **self == *key
+            }
+        }
+
+        impl equivalent::Comparable<PathBuf> for $p<PathBuf> {
+            fn compare(&self, key: &PathBuf) -> cmp::Ordering {
+                (**self).cmp(key)
+            }
+        }
+
+        impl<T: Eq> equivalent::Equivalent<Vec<T>> for $p<Vec<T>> {
+            fn equivalent(&self, key: &Vec<T>) -> bool {
+                **self == *key
+            }
+        }
+
+        impl<T: Ord> equivalent::Comparable<Vec<T>> for $p<Vec<T>> {
+            fn compare(&self, key: &Vec<T>) -> cmp::Ordering {
+                (**self).cmp(key)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "equivalent")]
+pooled_equivalent!(GPooled);
+#[cfg(feature = "equivalent")]
+pooled_equivalent!(LPooled);
+
+impl<P> fmt::Debug for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: fmt::Debug>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<P> fmt::Display for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: fmt::Display>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<P> PartialEq for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: PartialEq>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<P> Eq for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: Eq>,
+{
+}
+
+impl<P> PartialOrd for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: PartialOrd>,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<P> Ord for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: Ord>,
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<P> Hash for Frozen<P>
+where
+    P: Deref,
+    P::Target: Deref<Target: Hash>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
     }
 }
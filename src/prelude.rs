@@ -0,0 +1,59 @@
+//! Everything needed to start pooling, in one import.
+//!
+//! The pooled wrappers, the traits for making types poolable, and short
+//! names for the locally pooled containers that are used most, so code
+//! written by different people spells the same pooled type the same way.
+//! The aliases are all [LPooled], for objects that are taken and dropped on
+//! the same thread, see the [crate documentation](crate) for when to use
+//! [GPooled] instead.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::prelude::*;
+//!
+//! let mut ids: PooledFxMap<u64, PooledString> = PooledFxMap::take();
+//! let mut name = PooledString::take();
+//! name.push_str("shark");
+//! ids.insert(42, name);
+//! let mut batch = PooledVec::<u64>::take();
+//! batch.extend(ids.keys().copied());
+//! assert_eq!(&*batch, &[42]);
+//! ```
+pub use crate::{
+    global::{GPooled, Pool},
+    local::LPooled,
+    IsoPoolable, Poolable,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A locally pooled `Vec`
+pub type PooledVec<T> = LPooled<Vec<T>>;
+
+/// A locally pooled `VecDeque`
+pub type PooledVecDeque<T> = LPooled<VecDeque<T>>;
+
+/// A locally pooled `String`
+pub type PooledString = LPooled<String>;
+
+/// A locally pooled `HashMap` with the standard hasher
+pub type PooledMap<K, V> = LPooled<HashMap<K, V>>;
+
+/// A locally pooled `HashSet` with the standard hasher
+pub type PooledSet<K> = LPooled<HashSet<K>>;
+
+/// A locally pooled `HashMap` with the fx hasher, which is much faster for
+/// small keys but not resistant to hash flooding
+pub type PooledFxMap<K, V> = LPooled<FxHashMap<K, V>>;
+
+/// A locally pooled `HashSet` with the fx hasher
+pub type PooledFxSet<K> = LPooled<FxHashSet<K>>;
+
+/// A locally pooled `IndexMap` with the standard hasher
+#[cfg(feature = "indexmap")]
+pub type PooledIndexMap<K, V> = LPooled<indexmap::IndexMap<K, V>>;
+
+/// A locally pooled `IndexSet` with the standard hasher
+#[cfg(feature = "indexmap")]
+pub type PooledIndexSet<K> = LPooled<indexmap::IndexSet<K>>;
@@ -0,0 +1,59 @@
+//! Vec operations that need temporary buffers, using pooled scratch.
+//!
+//! Some operations on a vec need working memory of their own, e.g. the
+//! set of items already seen while deduplicating, or the cached keys while
+//! sorting. Written the obvious way they allocate on every call. These
+//! take their scratch from the local pools, so after the first call they
+//! only allocate to grow it. They work on anything that derefs to a `Vec`,
+//! including [GPooled](crate::global::GPooled) and
+//! [LPooled](crate::local::LPooled) vecs.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::{global::GPooled, scratch};
+//!
+//! let mut words: GPooled<Vec<&str>> = GPooled::take();
+//! words.extend(["one", "two", "one", "five", "three", "five"]);
+//! scratch::dedup_pooled(&mut words);
+//! assert_eq!(&*words, &["one", "two", "five", "three"]);
+//! scratch::sort_unstable_pooled(&mut words, |w| w.len());
+//! assert_eq!(words[3], "three");
+//! ```
+use crate::local::LPooled;
+use std::{collections::HashSet, hash::Hash};
+
+/// Remove duplicates from `v`, which need not be sorted, keeping the first
+/// of each and the order of the survivors
+pub fn dedup_pooled<T: Hash + Eq>(v: &mut Vec<T>) {
+    let mut retain: LPooled<Vec<bool>> = LPooled::take();
+    {
+        let mut seen: LPooled<HashSet<&T>> = LPooled::take();
+        retain.extend(v.iter().map(|t| seen.insert(t)));
+    }
+    let mut i = 0;
+    v.retain(|_| {
+        i += 1;
+        retain[i - 1]
+    })
+}
+
+/// Sort `v` by the key `f` returns, calling `f` once per element.
+///
+/// This is `slice::sort_by_cached_key`, without the allocation for the
+/// keys. Like `sort_unstable` equal elements may be reordered.
+pub fn sort_unstable_pooled<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut f: F) {
+    let mut keys: LPooled<Vec<(K, usize)>> = LPooled::take();
+    keys.extend(v.iter().enumerate().map(|(i, t)| (f(t), i)));
+    keys.sort_unstable();
+    // apply the permutation, following the chain of swaps each element has
+    // already been through, as the standard library does
+    for i in 0..v.len() {
+        let mut index = keys[i].1;
+        while index < i {
+            index = keys[index].1;
+        }
+        keys[i].1 = index;
+        v.swap(i, index);
+    }
+}
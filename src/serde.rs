@@ -0,0 +1,129 @@
+//! `#[serde(with = "...")]` adapters for deserializing into pooled containers.
+//!
+//! Wrapping every field in [LPooled](crate::local::LPooled) or
+//! [GPooled](crate::global::GPooled) isn't always practical. These modules let
+//! you keep plain field types while still deserializing into an allocation
+//! taken from the thread local pool. The value is deserialized in place, so the
+//! pooled capacity is reused. Since the field is a plain type it won't go back
+//! to the pool by itself, you can return it with [local::insert] when you are
+//! done with it, or just let it be freed.
+//!
+//! ```
+//! use poolshark::local;
+//! use serde_derive::Deserialize;
+//! use std::collections::HashMap;
+//!
+//! #[derive(Deserialize)]
+//! struct Message {
+//!     #[serde(with = "poolshark::serde::pooled_vec")]
+//!     ids: Vec<u64>,
+//!     #[serde(with = "poolshark::serde::pooled_map")]
+//!     tags: HashMap<String, String>,
+//! }
+//!
+//! fn done(m: Message) {
+//!     // give the allocations back for the next message
+//!     local::insert(m.ids);
+//!     local::insert(m.tags);
+//! }
+//! ```
+use crate::{local, IsoPoolable};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+fn serialize_inner<T, S>(t: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    t.serialize(serializer)
+}
+
+fn deserialize_inner<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: IsoPoolable + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let mut t = local::take::<T>();
+    match T::deserialize_in_place(deserializer, &mut t) {
+        Ok(()) => Ok(t),
+        Err(e) => {
+            local::insert(t);
+            Err(e)
+        }
+    }
+}
+
+/// Deserialize any [IsoPoolable] type from the thread local pool
+pub mod pooled {
+    use super::*;
+
+    pub fn serialize<T, S>(t: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serialize_inner(t, serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: IsoPoolable + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserialize_inner(deserializer)
+    }
+}
+
+/// Deserialize a `Vec<T>` from the thread local pool
+pub mod pooled_vec {
+    use super::*;
+
+    pub fn serialize<T, S>(t: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serialize_inner(t, serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserialize_inner(deserializer)
+    }
+}
+
+/// Deserialize a `HashMap<K, V, S>` from the thread local pool
+pub mod pooled_map {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        hash::{BuildHasher, Hash},
+    };
+
+    pub fn serialize<K, V, R, S>(
+        t: &HashMap<K, V, R>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serialize_inner(t, serializer)
+    }
+
+    pub fn deserialize<'de, K, V, R, D>(
+        deserializer: D,
+    ) -> Result<HashMap<K, V, R>, D::Error>
+    where
+        K: Hash + Eq + Deserialize<'de>,
+        V: Deserialize<'de>,
+        R: Default + BuildHasher,
+        D: Deserializer<'de>,
+    {
+        deserialize_inner(deserializer)
+    }
+}
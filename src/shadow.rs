@@ -0,0 +1,172 @@
+//! Measuring what pooling saves, in production.
+//!
+//! Benchmarks say whether pooling a type helps on the benchmark, but the
+//! answer in production depends on the allocator, the object sizes, and
+//! how warm the pools are. In shadow mode a sample of the takes from the
+//! thread local pools, global and local, are timed, and each sampled take
+//! is followed by the equivalent fresh allocation, an empty object grown
+//! to the capacity of the one the pool handed out, which is timed and
+//! freed. [report] sums both per pooled type.
+//!
+//! The pooled time covers the take only, while the fresh time covers the
+//! allocation and the free, since an unpooled object is freed where a
+//! pooled one is returned. Sampling is off by default, and costs one
+//! relaxed atomic load per take while it is off.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::{local::LPooled, shadow};
+//!
+//! shadow::set_sample_every(1);
+//! for _ in 0..100 {
+//!     let mut v: LPooled<Vec<u64>> = LPooled::take();
+//!     v.extend(0..1000);
+//! }
+//! shadow::set_sample_every(0);
+//! for r in shadow::report() {
+//!     // e.g. alloc::vec::Vec<u64> 100 samples, pooled 35ns, fresh 412ns
+//!     println!("{r}")
+//! }
+//! # let r = shadow::report().into_iter().find(|r| r.type_name.contains("u64")).unwrap();
+//! # assert_eq!(r.samples, 100);
+//! ```
+use crate::{Discriminant, IsoPoolable};
+use fxhash::FxHashMap;
+use std::{
+    cell::Cell,
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        LazyLock, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+static SAMPLE_EVERY: AtomicU32 = AtomicU32::new(0);
+
+static RESULTS: LazyLock<Mutex<FxHashMap<Discriminant, ShadowReport>>> =
+    LazyLock::new(|| Mutex::new(FxHashMap::default()));
+
+thread_local! {
+    static COUNTDOWN: Cell<u32> = const { Cell::new(0) };
+}
+
+/// The shadow measurements of one pooled type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowReport {
+    /// The name of the pooled type. Types that share a pool are counted
+    /// together, under the name of the first one sampled.
+    pub type_name: &'static str,
+    /// The number of sampled takes
+    pub samples: u64,
+    /// The total time spent in the sampled takes
+    pub pooled: Duration,
+    /// The total time spent allocating and freeing the fresh equivalents
+    pub fresh: Duration,
+}
+
+impl ShadowReport {
+    /// The mean time of a pooled take
+    pub fn pooled_mean(&self) -> Duration {
+        self.pooled / self.samples.max(1) as u32
+    }
+
+    /// The mean time of a fresh allocation and free
+    pub fn fresh_mean(&self) -> Duration {
+        self.fresh / self.samples.max(1) as u32
+    }
+
+    /// The mean nanoseconds pooling saved per take, negative if pooling
+    /// was slower
+    pub fn saved_ns(&self) -> f64 {
+        (self.fresh.as_nanos() as f64 - self.pooled.as_nanos() as f64)
+            / self.samples.max(1) as f64
+    }
+}
+
+impl fmt::Display for ShadowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} samples, pooled {:?}, fresh {:?}",
+            self.type_name,
+            self.samples,
+            self.pooled_mean(),
+            self.fresh_mean()
+        )
+    }
+}
+
+/// Sample one take in every `n`, or stop sampling if `n` is 0
+pub fn set_sample_every(n: u32) {
+    SAMPLE_EVERY.store(n, Ordering::Relaxed)
+}
+
+/// Return the sampling interval, 0 if sampling is off
+pub fn sample_every() -> u32 {
+    SAMPLE_EVERY.load(Ordering::Relaxed)
+}
+
+/// Return the measurements so far, sorted by type name
+pub fn report() -> Vec<ShadowReport> {
+    let mut res: Vec<ShadowReport> = RESULTS.lock().unwrap().values().cloned().collect();
+    res.sort_by(|a, b| a.type_name.cmp(b.type_name));
+    res
+}
+
+/// Forget the measurements so far
+pub fn reset() {
+    RESULTS.lock().unwrap().clear()
+}
+
+fn sampled() -> bool {
+    match SAMPLE_EVERY.load(Ordering::Relaxed) {
+        0 => false,
+        n => COUNTDOWN
+            .try_with(|c| match c.get() {
+                0 => {
+                    c.set(n - 1);
+                    true
+                }
+                i => {
+                    c.set(i - 1);
+                    false
+                }
+            })
+            .unwrap_or(false),
+    }
+}
+
+/// Run the take `f`, and if it is sampled, time it against a fresh `T`
+/// grown to the capacity `capacity` returns for the taken object
+#[inline]
+pub(crate) fn take<T: IsoPoolable, R, F: FnOnce() -> R>(
+    f: F,
+    capacity: fn(&R) -> usize,
+) -> R {
+    if !sampled() {
+        return f();
+    }
+    let start = Instant::now();
+    let r = f();
+    let pooled = start.elapsed();
+    let start = Instant::now();
+    let mut t = T::empty();
+    let _ = t.try_reserve(capacity(&r));
+    drop(t);
+    let fresh = start.elapsed();
+    if let Some(d) = T::DISCRIMINANT {
+        let mut results = RESULTS.lock().unwrap();
+        let e = results.entry(d).or_insert_with(|| ShadowReport {
+            type_name: std::any::type_name::<T>(),
+            samples: 0,
+            pooled: Duration::ZERO,
+            fresh: Duration::ZERO,
+        });
+        e.samples += 1;
+        e.pooled += pooled;
+        e.fresh += fresh;
+    }
+    r
+}
@@ -0,0 +1,177 @@
+//! Deep size estimates for pool limits.
+//!
+//! A pool decides whether an object is too big to keep by comparing its
+//! [capacity](crate::Poolable::capacity) to the pool's `max_elt_capacity`.
+//! For a `Vec<String>` that counts element slots, so a vec of 16 one
+//! megabyte strings looks as small as a vec of 16 empty ones. Types that
+//! implement [SizeOf] can report the heap memory they own, including what
+//! their elements own, and a pool told to
+//! [measure deep size](crate::global::RawPool::set_deep_size) compares that
+//! to its limits instead, so they bound bytes rather than slots.
+//!
+//! Measuring walks every element, so it costs more than reading the
+//! capacity, and it happens on every insert. The numbers are estimates,
+//! e.g. the bookkeeping of hash tables and b-trees isn't counted.
+//!
+//! # Example
+//!
+//! ```
+//! use poolshark::{global::Pool, size_of::SizeOf};
+//!
+//! let pool: Pool<Vec<String>> = Pool::new(16, 4096);
+//! pool.set_deep_size(true);
+//! let mut v = pool.take();
+//! v.push("x".repeat(8192));
+//! assert!(v.deep_size_of() > 8192);
+//! drop(v); // too big, really dropped
+//! assert_eq!(pool.len(), 0);
+//! ```
+use crate::{global::GPooled, local::LPooled, IsoPoolable, Poolable};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    mem,
+};
+
+/// An estimate of the heap memory owned by a value.
+pub trait SizeOf {
+    /// return the bytes of heap memory owned by `self`, including memory
+    /// owned by anything it contains, but not `size_of::<Self>()` itself
+    fn deep_size_of(&self) -> usize;
+}
+
+macro_rules! impl_no_heap {
+    ($($t:ty),*) => {
+        $(impl SizeOf for $t {
+            fn deep_size_of(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+impl_no_heap!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str
+);
+
+impl SizeOf for String {
+    fn deep_size_of(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: SizeOf> SizeOf for Box<T> {
+    fn deep_size_of(&self) -> usize {
+        mem::size_of::<T>() + (**self).deep_size_of()
+    }
+}
+
+impl<T: SizeOf> SizeOf for Option<T> {
+    fn deep_size_of(&self) -> usize {
+        self.as_ref().map_or(0, T::deep_size_of)
+    }
+}
+
+impl<T: SizeOf, U: SizeOf> SizeOf for (T, U) {
+    fn deep_size_of(&self) -> usize {
+        self.0.deep_size_of() + self.1.deep_size_of()
+    }
+}
+
+impl<T: SizeOf, U: SizeOf, V: SizeOf> SizeOf for (T, U, V) {
+    fn deep_size_of(&self) -> usize {
+        self.0.deep_size_of() + self.1.deep_size_of() + self.2.deep_size_of()
+    }
+}
+
+impl<T: SizeOf, const N: usize> SizeOf for [T; N] {
+    fn deep_size_of(&self) -> usize {
+        self.iter().map(T::deep_size_of).sum()
+    }
+}
+
+impl<T: SizeOf> SizeOf for Vec<T> {
+    fn deep_size_of(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(T::deep_size_of).sum::<usize>()
+    }
+}
+
+impl<T: SizeOf> SizeOf for VecDeque<T> {
+    fn deep_size_of(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(T::deep_size_of).sum::<usize>()
+    }
+}
+
+impl<K: SizeOf, V: SizeOf, S> SizeOf for HashMap<K, V, S> {
+    fn deep_size_of(&self) -> usize {
+        self.capacity() * mem::size_of::<(K, V)>()
+            + self.iter().map(|(k, v)| k.deep_size_of() + v.deep_size_of()).sum::<usize>()
+    }
+}
+
+impl<K: SizeOf, S> SizeOf for HashSet<K, S> {
+    fn deep_size_of(&self) -> usize {
+        self.capacity() * mem::size_of::<K>()
+            + self.iter().map(K::deep_size_of).sum::<usize>()
+    }
+}
+
+impl<K: SizeOf, V: SizeOf> SizeOf for BTreeMap<K, V> {
+    fn deep_size_of(&self) -> usize {
+        self.len() * mem::size_of::<(K, V)>()
+            + self.iter().map(|(k, v)| k.deep_size_of() + v.deep_size_of()).sum::<usize>()
+    }
+}
+
+impl<K: SizeOf> SizeOf for BTreeSet<K> {
+    fn deep_size_of(&self) -> usize {
+        self.len() * mem::size_of::<K>() + self.iter().map(K::deep_size_of).sum::<usize>()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: SizeOf, V: SizeOf, S> SizeOf for indexmap::IndexMap<K, V, S> {
+    fn deep_size_of(&self) -> usize {
+        // entries hold the hash too, and the index table a usize per slot
+        self.capacity() * (mem::size_of::<(usize, K, V)>() + mem::size_of::<usize>())
+            + self.iter().map(|(k, v)| k.deep_size_of() + v.deep_size_of()).sum::<usize>()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: SizeOf, S> SizeOf for indexmap::IndexSet<K, S> {
+    fn deep_size_of(&self) -> usize {
+        self.capacity() * (mem::size_of::<(usize, K)>() + mem::size_of::<usize>())
+            + self.iter().map(K::deep_size_of).sum::<usize>()
+    }
+}
+
+impl<T: Poolable + SizeOf> SizeOf for GPooled<T> {
+    fn deep_size_of(&self) -> usize {
+        (**self).deep_size_of()
+    }
+}
+
+impl<T: IsoPoolable + SizeOf> SizeOf for LPooled<T> {
+    fn deep_size_of(&self) -> usize {
+        (**self).deep_size_of()
+    }
+}
@@ -0,0 +1,834 @@
+//! Pool occupancy and usage statistics.
+//!
+//! Every global pool can produce a [PoolStats] snapshot, either directly or
+//! through the type erased [PoolControl](crate::global::PoolControl) trait, so
+//! management code can inspect all of its pools uniformly. The calling
+//! thread's local pools produce one with [local::stats](crate::local::stats).
+//!
+//! Occupancy (length and limits) is always available. Retained bytes and
+//! usage statistics, such as the [UsageCounts], [CapacityHistogram]s and
+//! [LatencyStats], are only recorded when the `stats` feature is enabled,
+//! otherwise they are always empty. Recording them costs a few clock reads
+//! and thread local updates per take and insert, each thread keeps its own
+//! counts and adds them to the pool's shared counters in batches, see
+//! [flush], so threads using the same pool don't contend on them. The
+//! number of outstanding objects is the exception, it is kept exactly with
+//! a shared atomic. Local pools only record the [UsageCounts].
+
+/// The number of buckets in a [Histogram]
+pub const HISTOGRAM_BUCKETS: usize = 64;
+
+/// A log2 histogram.
+///
+/// Bucket 0 counts the value 0, and bucket `i` counts values in
+/// `2^(i - 1)..2^i`. The last bucket also counts everything larger than its
+/// range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+}
+
+/// A [Histogram] of object capacities
+pub type CapacityHistogram = Histogram;
+
+/// A [Histogram] of durations in nanoseconds
+pub type LatencyHistogram = Histogram;
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self { buckets: [0; HISTOGRAM_BUCKETS] }
+    }
+}
+
+impl Histogram {
+    /// Return the bucket index that `value` is counted in
+    pub fn bucket(value: usize) -> usize {
+        let i = (usize::BITS - value.leading_zeros()) as usize;
+        std::cmp::min(i, HISTOGRAM_BUCKETS - 1)
+    }
+
+    /// Return the range of values counted in bucket `i`
+    ///
+    /// On targets where `usize` is narrower than the histogram, bounds past
+    /// `usize::MAX` saturate to it, those buckets are never counted in.
+    pub fn bucket_range(i: usize) -> std::ops::Range<usize> {
+        // 2^shift, saturating instead of overflowing on 32 bit targets
+        fn pow2(shift: usize) -> usize {
+            1usize.checked_shl(shift as u32).unwrap_or(usize::MAX)
+        }
+        match i {
+            0 => 0..1,
+            i if i >= HISTOGRAM_BUCKETS - 1 => pow2(HISTOGRAM_BUCKETS - 2)..usize::MAX,
+            i => pow2(i - 1)..pow2(i),
+        }
+    }
+
+    /// The total number of values counted
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Return an upper bound on `p` percent of the counted values, or `None`
+    /// if the histogram is empty.
+    ///
+    /// For example `percentile(99.)` of the returned capacity histogram is a
+    /// reasonable starting point for `max_elt_capacity`.
+    pub fn percentile(&self, p: f64) -> Option<usize> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let target = ((total as f64) * p.clamp(0., 100.) / 100.).ceil() as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target && seen > 0 {
+                return Some(Self::bucket_range(i).end - 1);
+            }
+        }
+        Some(usize::MAX)
+    }
+}
+
+/// Time spent, in nanoseconds, on each path through a pool.
+///
+/// Comparing these tells you whether contention on the pool, the thread local
+/// lookup, or allocation dominates, which is what you need to know before
+/// choosing between the local, global, and cached handle APIs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Finding the thread local global pool, only recorded by the functions
+    /// in [global](crate::global) that do the lookup, e.g. `global::take`
+    pub lookup: LatencyHistogram,
+    /// Takes that found an object in the pool
+    pub take_hit: LatencyHistogram,
+    /// Takes that found the pool empty, including allocation if any
+    pub take_miss: LatencyHistogram,
+    /// Inserts that reset the object and kept it
+    pub insert_pooled: LatencyHistogram,
+    /// Inserts that really dropped the object
+    pub insert_dropped: LatencyHistogram,
+}
+
+/// How often a pool had what was asked of it.
+///
+/// These are the numbers to look at when tuning a pool's size. A low hit
+/// rate with many objects discarded because the pool was full means it is
+/// too small, many discarded because they were oversized means its
+/// `max_elt_capacity` is too small.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageCounts {
+    /// The number of takes, including try_takes that found the pool empty
+    pub takes: u64,
+    /// The number of takes that got a pooled object
+    pub hits: u64,
+    /// The number of takes that found the pool empty
+    pub misses: u64,
+    /// The number of objects given back to the pool
+    pub returns: u64,
+    /// The number of objects taken out of the pool for good by
+    /// [detach](crate::global::GPooled::detach), only global pools count
+    /// them. Each of them is memory the pool handed out and will never see
+    /// again, a pool whose objects are mostly detached isn't recycling
+    /// anything.
+    pub detached: u64,
+    /// Objects thrown away because their capacity was more than
+    /// `max_elt_capacity`
+    pub discarded_oversized: u64,
+    /// Objects thrown away because the pool, or its byte budget, was full
+    pub discarded_full: u64,
+    /// Objects thrown away for any other reason, e.g. a failed reset or a
+    /// prune
+    pub discarded_other: u64,
+}
+
+impl UsageCounts {
+    /// The fraction of takes that got a pooled object, or `None` if nothing
+    /// was taken
+    pub fn hit_rate(&self) -> Option<f64> {
+        if self.takes == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / self.takes as f64)
+        }
+    }
+
+    /// The total number of objects thrown away
+    pub fn discarded(&self) -> u64 {
+        self.discarded_oversized + self.discarded_full + self.discarded_other
+    }
+}
+
+/// How much use pooled objects got before they were thrown away.
+///
+/// Recorded when a pool evicts an object, so objects that are still in use
+/// or in the pool aren't counted yet. A pool where most objects were used
+/// only a few times while a few were used constantly is bigger than it needs
+/// to be. Only [GPooled](crate::global::GPooled) objects are tracked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgeStats {
+    /// The number of times each object was handed out by a take
+    pub uses: Histogram,
+    /// How long each object lived, in nanoseconds
+    pub lifetime: LatencyHistogram,
+}
+
+/// How much one thread used a pool, see
+/// [RawPool::set_track_threads](crate::global::RawPool::set_track_threads)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadUse {
+    /// A process unique number assigned to the thread the first time it
+    /// used a tracked pool
+    pub id: u64,
+    /// The thread's name, if it has one
+    pub name: Option<String>,
+    /// The number of objects the thread took from the pool
+    pub taken: u64,
+    /// The number of objects the thread returned to the pool
+    pub returned: u64,
+}
+
+/// A point in time snapshot of a pool.
+///
+/// The values are read without stopping concurrent users of the pool, so they
+/// may be slightly inconsistent with each other if the pool is busy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The number of objects currently in the pool
+    pub len: usize,
+    /// The maximum number of objects the pool will retain
+    pub capacity: usize,
+    /// Objects with a capacity larger than this are not retained
+    pub max_elt_capacity: usize,
+    /// Estimated heap memory, in bytes, owned by the pooled objects, only
+    /// counted with the `stats` feature
+    pub retained_bytes: usize,
+    /// Takes, hits, misses, returns, and discards
+    pub counts: UsageCounts,
+    /// Capacities of objects handed out by take, including newly allocated ones
+    pub taken: CapacityHistogram,
+    /// Capacities of objects given back to the pool, before reset
+    pub returned: CapacityHistogram,
+    /// Capacities of objects that were really dropped instead of pooled
+    pub evicted: CapacityHistogram,
+    /// Time spent taking and inserting
+    pub latency: LatencyStats,
+    /// The number of objects currently taken from the pool and not yet
+    /// returned or detached
+    pub outstanding: usize,
+    /// The high water mark of `outstanding`, this is the number of objects
+    /// the program actually needed at once, which is what capacity planning
+    /// needs.
+    pub max_outstanding: usize,
+    /// The uses and lifetimes of evicted objects
+    pub ageing: AgeStats,
+    /// Which threads take and return objects, only recorded when enabled
+    /// with [set_track_threads](crate::global::RawPool::set_track_threads)
+    pub threads: Vec<ThreadUse>,
+}
+
+impl PoolStats {
+    /// Return the fraction of returned objects that could have gone back to
+    /// the thread that took them, or `None` if threads aren't tracked.
+    ///
+    /// A pool carrying objects from producers to consumers is close to 0. If
+    /// it is close to 1 objects are mostly returned by the threads that took
+    /// them, and local pools would be faster.
+    pub fn same_thread_ratio(&self) -> Option<f64> {
+        let returned: u64 = self.threads.iter().map(|t| t.returned).sum();
+        if returned == 0 {
+            return None;
+        }
+        let same: u64 = self.threads.iter().map(|t| t.taken.min(t.returned)).sum();
+        Some(same as f64 / returned as f64)
+    }
+}
+
+/// The paths through a pool that are timed, see [LatencyStats]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Path {
+    Lookup,
+    TakeHit,
+    TakeMiss,
+    InsertPooled,
+    InsertDropped,
+}
+
+#[cfg(feature = "stats")]
+mod imp {
+    use super::{
+        AgeStats, Histogram, LatencyStats, Path, PoolStats, ThreadUse, UsageCounts,
+        HISTOGRAM_BUCKETS,
+    };
+    use crate::global::evict::EvictReason;
+    use fxhash::FxHashMap;
+    use std::{
+        cell::RefCell,
+        sync::{
+            atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering},
+            Arc, Mutex, Weak,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+    thread_local! {
+        static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[derive(Debug)]
+    struct AtomicHistogram([AtomicU64; HISTOGRAM_BUCKETS]);
+
+    impl Default for AtomicHistogram {
+        fn default() -> Self {
+            Self(std::array::from_fn(|_| AtomicU64::new(0)))
+        }
+    }
+
+    impl AtomicHistogram {
+        fn snapshot(&self) -> Histogram {
+            Histogram {
+                buckets: std::array::from_fn(|i| self.0[i].load(Ordering::Relaxed)),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Timer(Instant);
+
+    impl Timer {
+        #[inline(always)]
+        pub(crate) fn start() -> Self {
+            Self(Instant::now())
+        }
+
+        fn elapsed_ns(&self) -> usize {
+            self.0.elapsed().as_nanos() as usize
+        }
+    }
+
+    /// The birth time and number of uses of a pooled object
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Age {
+        born: Instant,
+        uses: u64,
+    }
+
+    impl Age {
+        pub(crate) fn new() -> Self {
+            Self { born: Instant::now(), uses: 0 }
+        }
+
+        pub(crate) fn used(&mut self) {
+            self.uses += 1
+        }
+
+        pub(crate) fn get(&self) -> Option<(Duration, u64)> {
+            Some((self.born.elapsed(), self.uses))
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct AtomicLatency {
+        lookup: AtomicHistogram,
+        take_hit: AtomicHistogram,
+        take_miss: AtomicHistogram,
+        insert_pooled: AtomicHistogram,
+        insert_dropped: AtomicHistogram,
+    }
+
+    #[derive(Debug, Default)]
+    struct AtomicCounts {
+        hits: AtomicU64,
+        misses: AtomicU64,
+        returns: AtomicU64,
+        detached: AtomicU64,
+        discarded_oversized: AtomicU64,
+        discarded_full: AtomicU64,
+        discarded_other: AtomicU64,
+    }
+
+    // the counters every thread's pending counts are flushed into
+    #[derive(Debug, Default)]
+    struct Shared {
+        counts: AtomicCounts,
+        taken: AtomicHistogram,
+        returned: AtomicHistogram,
+        evicted: AtomicHistogram,
+        latency: AtomicLatency,
+        uses: AtomicHistogram,
+        lifetime: AtomicHistogram,
+    }
+
+    fn add_histogram(to: &AtomicHistogram, from: &Histogram) {
+        for (to, n) in to.0.iter().zip(from.buckets.iter()) {
+            if *n > 0 {
+                to.fetch_add(*n, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn add_count(to: &AtomicU64, n: u64) {
+        if n > 0 {
+            to.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+
+    impl Shared {
+        fn add(&self, p: &Pending) {
+            let c = &self.counts;
+            add_count(&c.hits, p.counts.hits);
+            add_count(&c.misses, p.counts.misses);
+            add_count(&c.returns, p.counts.returns);
+            add_count(&c.detached, p.counts.detached);
+            add_count(&c.discarded_oversized, p.counts.discarded_oversized);
+            add_count(&c.discarded_full, p.counts.discarded_full);
+            add_count(&c.discarded_other, p.counts.discarded_other);
+            add_histogram(&self.taken, &p.taken);
+            add_histogram(&self.returned, &p.returned);
+            add_histogram(&self.evicted, &p.evicted);
+            add_histogram(&self.uses, &p.uses);
+            add_histogram(&self.lifetime, &p.lifetime);
+            let (l, pl) = (&self.latency, &p.latency);
+            add_histogram(&l.lookup, &pl.lookup);
+            add_histogram(&l.take_hit, &pl.take_hit);
+            add_histogram(&l.take_miss, &pl.take_miss);
+            add_histogram(&l.insert_pooled, &pl.insert_pooled);
+            add_histogram(&l.insert_dropped, &pl.insert_dropped);
+        }
+    }
+
+    // one thread's counts for one pool that haven't been flushed yet
+    #[derive(Debug, Default)]
+    struct Pending {
+        ops: u32,
+        counts: UsageCounts,
+        taken: Histogram,
+        returned: Histogram,
+        evicted: Histogram,
+        latency: LatencyStats,
+        uses: Histogram,
+        lifetime: Histogram,
+    }
+
+    fn record(h: &mut Histogram, value: usize) {
+        h.buckets[Histogram::bucket(value)] += 1
+    }
+
+    // pending counts are flushed after this many operations on a pool
+    const FLUSH_EVERY: u32 = 64;
+
+    // pending counts by pool id, flushed when the thread exits
+    struct Buffers(FxHashMap<u64, (Weak<Shared>, Box<Pending>)>);
+
+    impl Drop for Buffers {
+        fn drop(&mut self) {
+            for (shared, pending) in self.0.values() {
+                if let Some(shared) = shared.upgrade() {
+                    shared.add(pending)
+                }
+            }
+        }
+    }
+
+    thread_local! {
+        static BUFFERS: RefCell<Buffers> = RefCell::new(Buffers(FxHashMap::default()));
+    }
+
+    static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(0);
+
+    /// Flush the calling thread's pending counts for every pool
+    pub(crate) fn flush() {
+        let _ = BUFFERS.try_with(|b| {
+            if let Ok(mut b) = b.try_borrow_mut() {
+                for (shared, pending) in b.0.values_mut() {
+                    if let Some(shared) = shared.upgrade() {
+                        shared.add(pending);
+                    }
+                    **pending = Pending::default()
+                }
+            }
+        });
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct PoolCounters {
+        id: u64,
+        shared: Arc<Shared>,
+        // signed because objects that were never taken from this pool, e.g.
+        // assigned orphans, can be returned to it
+        outstanding: AtomicI64,
+        max_outstanding: AtomicI64,
+        retained_bytes: AtomicUsize,
+        track_threads: AtomicBool,
+        threads: Mutex<FxHashMap<u64, ThreadUse>>,
+    }
+
+    impl PoolCounters {
+        pub(crate) fn new() -> Self {
+            Self {
+                id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+                shared: Arc::default(),
+                outstanding: AtomicI64::new(0),
+                max_outstanding: AtomicI64::new(0),
+                retained_bytes: AtomicUsize::new(0),
+                track_threads: AtomicBool::new(false),
+                threads: Mutex::new(FxHashMap::default()),
+            }
+        }
+
+        // count into this thread's pending counts, so the hot path doesn't
+        // contend on shared atomics, or straight into the shared counters
+        // if the thread local is busy or gone
+        fn record<F: FnOnce(&mut Pending)>(&self, f: F) {
+            let mut f = Some(f);
+            let buffered = BUFFERS
+                .try_with(|b| {
+                    let Ok(mut b) = b.try_borrow_mut() else { return false };
+                    if !b.0.contains_key(&self.id) {
+                        // the entries of dropped pools are no use to anyone
+                        b.0.retain(|_, (shared, _)| shared.strong_count() > 0);
+                        b.0.insert(
+                            self.id,
+                            (Arc::downgrade(&self.shared), Box::default()),
+                        );
+                    }
+                    let pending = &mut b.0.get_mut(&self.id).unwrap().1;
+                    (f.take().unwrap())(pending);
+                    pending.ops += 1;
+                    if pending.ops >= FLUSH_EVERY {
+                        self.shared.add(pending);
+                        **pending = Pending::default()
+                    }
+                    true
+                })
+                .unwrap_or(false);
+            if !buffered {
+                let mut pending = Box::<Pending>::default();
+                (f.take().unwrap())(&mut pending);
+                self.shared.add(&pending)
+            }
+        }
+
+        pub(crate) fn taken(&self, capacity: usize) {
+            self.record(|p| record(&mut p.taken, capacity));
+            let n = self.outstanding.fetch_add(1, Ordering::Relaxed) + 1;
+            self.max_outstanding.fetch_max(n, Ordering::Relaxed);
+            if self.track_threads.load(Ordering::Relaxed) {
+                self.thread_used(|t| t.taken += 1)
+            }
+        }
+
+        pub(crate) fn looked(&self, hit: bool) {
+            self.record(|p| if hit { p.counts.hits += 1 } else { p.counts.misses += 1 })
+        }
+
+        pub(crate) fn returned(&self, capacity: usize) {
+            self.record(|p| {
+                p.counts.returns += 1;
+                record(&mut p.returned, capacity)
+            });
+            self.outstanding.fetch_sub(1, Ordering::Relaxed);
+            if self.track_threads.load(Ordering::Relaxed) {
+                self.thread_used(|t| t.returned += 1)
+            }
+        }
+
+        pub(crate) fn detached(&self) {
+            self.record(|p| p.counts.detached += 1);
+            self.outstanding.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        // the pool now holds `bytes` more, counted before the object is
+        // pushed so a racing pop can't subtract them first and wrap
+        pub(crate) fn retained(&self, bytes: usize) {
+            self.retained_bytes.fetch_add(bytes, Ordering::Relaxed);
+        }
+
+        pub(crate) fn unretained(&self, bytes: usize) {
+            self.retained_bytes.fetch_sub(bytes, Ordering::Relaxed);
+        }
+
+        pub(crate) fn retained_bytes(&self) -> usize {
+            self.retained_bytes.load(Ordering::Relaxed)
+        }
+
+        fn thread_used<F: FnOnce(&mut ThreadUse)>(&self, f: F) {
+            // the thread local may already be gone if an object is dropped
+            // during thread exit
+            if let Ok(id) = THREAD_ID.try_with(|id| *id) {
+                let mut threads = self.threads.lock().unwrap();
+                f(threads.entry(id).or_insert_with(|| ThreadUse {
+                    id,
+                    name: thread::current().name().map(String::from),
+                    taken: 0,
+                    returned: 0,
+                }))
+            }
+        }
+
+        pub(crate) fn track_threads(&self) -> bool {
+            self.track_threads.load(Ordering::Relaxed)
+        }
+
+        pub(crate) fn set_track_threads(&self, track: bool) {
+            self.track_threads.store(track, Ordering::Relaxed);
+            if !track {
+                self.threads.lock().unwrap().clear()
+            }
+        }
+
+        pub(crate) fn evicted(
+            &self,
+            capacity: usize,
+            reason: EvictReason,
+            age: Option<(Duration, u64)>,
+        ) {
+            self.record(|p| {
+                let c = &mut p.counts;
+                match reason {
+                    EvictReason::Oversized => c.discarded_oversized += 1,
+                    EvictReason::Full | EvictReason::OverBudget => c.discarded_full += 1,
+                    _ => c.discarded_other += 1,
+                }
+                record(&mut p.evicted, capacity);
+                if let Some((lifetime, uses)) = age {
+                    record(&mut p.uses, uses as usize);
+                    record(&mut p.lifetime, lifetime.as_nanos() as usize)
+                }
+            })
+        }
+
+        pub(crate) fn timed(&self, path: Path, timer: Timer) {
+            let elapsed = timer.elapsed_ns();
+            self.record(|p| {
+                let l = &mut p.latency;
+                let h = match path {
+                    Path::Lookup => &mut l.lookup,
+                    Path::TakeHit => &mut l.take_hit,
+                    Path::TakeMiss => &mut l.take_miss,
+                    Path::InsertPooled => &mut l.insert_pooled,
+                    Path::InsertDropped => &mut l.insert_dropped,
+                };
+                record(h, elapsed)
+            })
+        }
+
+        // flush this thread's pending counts for this pool
+        fn flush(&self) {
+            let _ = BUFFERS.try_with(|b| {
+                if let Ok(mut b) = b.try_borrow_mut()
+                    && let Some((_, pending)) = b.0.get_mut(&self.id)
+                {
+                    self.shared.add(pending);
+                    **pending = Pending::default()
+                }
+            });
+        }
+
+        pub(crate) fn fill(&self, stats: &mut PoolStats) {
+            self.flush();
+            let s = &self.shared;
+            let c = &s.counts;
+            let (hits, misses) =
+                (c.hits.load(Ordering::Relaxed), c.misses.load(Ordering::Relaxed));
+            stats.counts = UsageCounts {
+                takes: hits + misses,
+                hits,
+                misses,
+                returns: c.returns.load(Ordering::Relaxed),
+                detached: c.detached.load(Ordering::Relaxed),
+                discarded_oversized: c.discarded_oversized.load(Ordering::Relaxed),
+                discarded_full: c.discarded_full.load(Ordering::Relaxed),
+                discarded_other: c.discarded_other.load(Ordering::Relaxed),
+            };
+            stats.taken = s.taken.snapshot();
+            stats.returned = s.returned.snapshot();
+            stats.evicted = s.evicted.snapshot();
+            let l = &s.latency;
+            stats.latency = LatencyStats {
+                lookup: l.lookup.snapshot(),
+                take_hit: l.take_hit.snapshot(),
+                take_miss: l.take_miss.snapshot(),
+                insert_pooled: l.insert_pooled.snapshot(),
+                insert_dropped: l.insert_dropped.snapshot(),
+            };
+            stats.outstanding = self.outstanding.load(Ordering::Relaxed).max(0) as usize;
+            stats.max_outstanding = self.max_outstanding.load(Ordering::Relaxed) as usize;
+            stats.ageing =
+                AgeStats { uses: s.uses.snapshot(), lifetime: s.lifetime.snapshot() };
+            let mut threads: Vec<ThreadUse> =
+                self.threads.lock().unwrap().values().cloned().collect();
+            threads.sort_by_key(|t| t.id);
+            stats.threads = threads;
+        }
+    }
+
+    /// The [UsageCounts] of a thread local pool, which only its thread
+    /// touches, so they don't need to be atomic
+    #[derive(Debug, Default)]
+    pub(crate) struct LocalCounters(UsageCounts);
+
+    impl LocalCounters {
+        pub(crate) fn new() -> Self {
+            Self::default()
+        }
+
+        pub(crate) fn looked(&mut self, hit: bool) {
+            self.0.takes += 1;
+            if hit {
+                self.0.hits += 1
+            } else {
+                self.0.misses += 1
+            }
+        }
+
+        pub(crate) fn returned(&mut self) {
+            self.0.returns += 1
+        }
+
+        pub(crate) fn discarded(&mut self, reason: EvictReason) {
+            match reason {
+                EvictReason::Oversized => self.0.discarded_oversized += 1,
+                EvictReason::Full | EvictReason::OverBudget => self.0.discarded_full += 1,
+                _ => self.0.discarded_other += 1,
+            }
+        }
+
+        pub(crate) fn fill(&self, stats: &mut PoolStats) {
+            stats.counts = self.0
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+mod imp {
+    use super::{Path, PoolStats};
+    use crate::global::evict::EvictReason;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Age;
+
+    impl Age {
+        #[inline(always)]
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn used(&mut self) {}
+
+        #[inline(always)]
+        pub(crate) fn get(&self) -> Option<(Duration, u64)> {
+            None
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Timer;
+
+    impl Timer {
+        #[inline(always)]
+        pub(crate) fn start() -> Self {
+            Self
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn flush() {}
+
+    #[derive(Debug)]
+    pub(crate) struct PoolCounters;
+
+    impl PoolCounters {
+        #[inline(always)]
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn taken(&self, _capacity: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn looked(&self, _hit: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn returned(&self, _capacity: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn detached(&self) {}
+
+        #[inline(always)]
+        pub(crate) fn retained(&self, _bytes: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn unretained(&self, _bytes: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn retained_bytes(&self) -> usize {
+            0
+        }
+
+        pub(crate) fn track_threads(&self) -> bool {
+            false
+        }
+
+        #[inline(always)]
+        pub(crate) fn set_track_threads(&self, _track: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn evicted(
+            &self,
+            _capacity: usize,
+            _reason: EvictReason,
+            _age: Option<(Duration, u64)>,
+        ) {
+        }
+
+        #[inline(always)]
+        pub(crate) fn timed(&self, _path: Path, _timer: Timer) {}
+
+        #[inline(always)]
+        pub(crate) fn fill(&self, _stats: &mut PoolStats) {}
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct LocalCounters;
+
+    impl LocalCounters {
+        #[inline(always)]
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn looked(&mut self, _hit: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn returned(&mut self) {}
+
+        #[inline(always)]
+        pub(crate) fn discarded(&mut self, _reason: EvictReason) {}
+
+        #[inline(always)]
+        pub(crate) fn fill(&self, _stats: &mut PoolStats) {}
+    }
+}
+
+pub(crate) use imp::{Age, LocalCounters, PoolCounters, Timer};
+
+/// Flush the calling thread's pending usage statistics for every global
+/// pool.
+///
+/// Each thread counts takes and inserts privately, and adds them to the
+/// pool's statistics every 64 operations on that pool, when it exits, or
+/// when it asks the pool for its stats. A snapshot can therefore miss up to
+/// 64 recent operations per other thread, e.g. long lived worker threads
+/// that have gone idle. Calling this on those threads makes their counts
+/// visible. It does nothing without the `stats` feature.
+pub fn flush() {
+    imp::flush()
+}
@@ -1,8 +1,13 @@
 use super::global::{
+    self,
     arc::{Arc, TArc},
-    Pool, RawPool,
+    Pool, PoolControl, PruneMode, RawPool,
+};
+use crate::{
+    local::LPooled,
+    pooled::{ErasedVec, FixedVec, IndexMapScratch, IndexSetScratch, NodeList},
+    IsoPoolable,
 };
-use crate::{local::LPooled, IsoPoolable};
 use fxhash::{FxHashMap, FxHashSet};
 use indexmap::{IndexMap, IndexSet};
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -256,6 +261,812 @@ fn normal_pool_indexset() {
     mk_normal_pool_hashset!(IndexSet)
 }
 
+#[test]
+fn local_pool_erased_vec() {
+    use std::fmt::{Debug, Display};
+    let mut v: LPooled<ErasedVec<dyn Display>> = LPooled::take();
+    v.push(Box::new(42));
+    v.push(Box::new("hello"));
+    assert_eq!(v.iter().map(|d| d.to_string()).collect::<Vec<_>>(), ["42", "hello"]);
+    let a = v.as_ptr().addr();
+    drop(v);
+    let v: LPooled<ErasedVec<dyn Debug>> = LPooled::take();
+    assert!(v.is_empty());
+    assert_eq!(v.as_ptr().addr(), a);
+    drop(v);
+    let v: LPooled<Vec<(usize, usize)>> = LPooled::take();
+    assert_ne!(v.as_ptr().addr(), a);
+}
+
+#[test]
+fn local_pool_fixed_vec() {
+    let mut v: LPooled<FixedVec<f32, 4>> = LPooled::take();
+    assert_eq!(v.capacity(), 4);
+    v.extend_from_slice(&[1., 2., 3.]).unwrap();
+    assert_eq!(v.extend_from_slice(&[4., 5.]), Err(1));
+    v.push(4.).unwrap();
+    assert_eq!(v.push(5.), Err(5.));
+    assert_eq!(&**v, &[1., 2., 3., 4.]);
+    let a = v.as_ptr().addr();
+    drop(v);
+    let v: LPooled<FixedVec<f32, 4>> = LPooled::take();
+    assert!(v.is_empty());
+    assert_eq!(v.as_ptr().addr(), a);
+    let w: LPooled<FixedVec<f32, 8>> = LPooled::take();
+    assert_ne!(w.as_ptr().addr(), a);
+}
+
+#[test]
+fn fixed_vec_clone() {
+    let mut v = FixedVec::<u32, 8>::new();
+    v.extend_from_slice(&[1, 2]).unwrap();
+    // filling the clone must not reallocate, the buffer doesn't move
+    let mut c = v.clone();
+    let a = c.as_ptr().addr();
+    c.extend_from_slice(&[3, 4, 5, 6, 7, 8]).unwrap();
+    assert_eq!(c.as_ptr().addr(), a);
+    assert_eq!(&*c, &[1, 2, 3, 4, 5, 6, 7, 8]);
+    let mut d = FixedVec::<u32, 8>::new();
+    let b = d.as_ptr().addr();
+    d.clone_from(&c);
+    assert_eq!(d.as_ptr().addr(), b);
+    d.clone_from(&v);
+    d.resize(8, 0).unwrap();
+    assert_eq!(d.as_ptr().addr(), b);
+    assert_eq!(&*d, &[1, 2, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn map_ext() {
+    use crate::pooled::MapExt;
+    let mut m: LPooled<HashMap<String, LPooled<Vec<u64>>>> = LPooled::take();
+    m.get_or_take("a").push(1);
+    m.get_or_take("a").push(2);
+    m.get_or_insert_pooled("b", || [3].into_iter().collect()).push(4);
+    assert_eq!(&**m["a"], &[1, 2]);
+    assert_eq!(&**m["b"], &[3, 4]);
+    let mut m: IndexMap<String, Vec<u64>> = IndexMap::new();
+    m.get_or_take("z").push(1);
+    m.get_or_take("y").push(2);
+    m.get_or_take("z").push(3);
+    assert_eq!(m.keys().collect::<Vec<_>>(), ["z", "y"]);
+    assert_eq!(m["z"], [1, 3]);
+}
+
+#[test]
+fn local_shared() {
+    use crate::local::shared::LocalShared;
+    let mut a: LocalShared<Vec<u32>> = LocalShared::take();
+    a.get_mut().unwrap().extend([1, 2, 3]);
+    let p = a.as_ptr().addr();
+    let mut b = a.clone();
+    assert_eq!(b.strong_count(), 2);
+    assert!(b.get_mut().is_none());
+    drop(a);
+    assert_eq!(&**b, &[1, 2, 3]);
+    let c = b.clone();
+    b.make_mut().push(4);
+    assert!(!LocalShared::ptr_eq(&b, &c));
+    assert_eq!(&**c, &[1, 2, 3]);
+    drop(b);
+    drop(c);
+    let a: LocalShared<Vec<u32>> = LocalShared::take();
+    let b: LocalShared<Vec<u32>> = LocalShared::take();
+    assert!(a.is_empty() && b.is_empty());
+    assert!(a.as_ptr().addr() == p || b.as_ptr().addr() == p);
+}
+
+#[test]
+fn pooled_buf_io() {
+    use crate::local::io::{PooledBufReader, PooledBufWriter};
+    use std::io::{BufRead, Read, Write};
+    let mut out = Vec::new();
+    let mut w = PooledBufWriter::with_capacity(8, &mut out);
+    w.write_all(b"abc").unwrap();
+    assert_eq!(w.buffer(), b"abc");
+    w.write_all(b"defghij").unwrap();
+    w.write_all(b"0123456789").unwrap();
+    let out = w.into_inner().unwrap();
+    assert_eq!(&out[..], b"abcdefghij0123456789");
+    let mut r = PooledBufReader::with_capacity(4, &out[..]);
+    assert_eq!(r.fill_buf().unwrap(), b"abcd");
+    let a = r.buffer().as_ptr().addr();
+    r.consume(2);
+    let mut s = [0; 3];
+    r.read_exact(&mut s).unwrap();
+    assert_eq!(&s, b"cde");
+    let mut rest = Vec::new();
+    r.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"fghij0123456789");
+    drop(r);
+    let v: LPooled<Vec<u8>> = LPooled::take();
+    assert_eq!(v.as_ptr().addr(), a);
+}
+
+#[test]
+fn pformat() {
+    let s = crate::pformat!("{}-{}", "a", 1);
+    assert_eq!(&*s, "a-1");
+    let a = s.as_ptr().addr();
+    drop(s);
+    let s = crate::pformat!("{:?}", [1, 2]);
+    assert_eq!(&*s, "[1, 2]");
+    assert_eq!(s.as_ptr().addr(), a);
+    let mut out = Vec::new();
+    crate::pwrite!(&mut out, "{s}!").unwrap();
+    assert_eq!(out, b"[1, 2]!");
+}
+
+#[test]
+fn any_pool_default_sizes() {
+    type T = Vec<(u8, u16)>;
+    global::set_size_any::<T>(16, 32);
+    assert_eq!(global::get_size_any::<T>(), (16, 32));
+    let mut p = None;
+    for _ in 0..100 {
+        let mut v = global::take_any_default::<T>();
+        v.reserve(8);
+        let a = v.as_ptr().addr();
+        assert_eq!(*p.get_or_insert(a), a);
+        // too big to be pooled given the configured max_elt_capacity
+        let mut v2 = global::take_any_default::<T>();
+        v2.reserve(64);
+    }
+}
+
+#[test]
+fn shared_any_pool() {
+    type T = Vec<(u16, u8)>;
+    let pool = global::shared_pool_any::<T>();
+    let mut v = pool.take();
+    v.reserve(10);
+    let a = v.as_ptr().addr();
+    drop(v);
+    let v = std::thread::spawn(move || {
+        let v = global::shared_pool_any::<T>().take();
+        assert_eq!(v.as_ptr().addr(), a);
+        v
+    })
+    .join()
+    .unwrap();
+    drop(v);
+    assert!(pool.try_take().is_some());
+}
+
+#[test]
+fn pool_control() {
+    let strings: Pool<String> = Pool::new(1024, 1024);
+    let vecs: Pool<Vec<u64>> = Pool::new(1024, 1024);
+    let pools: Vec<Box<dyn PoolControl>> =
+        vec![Box::new(strings.clone()), Box::new(vecs.clone())];
+    let mut s = strings.take();
+    s.reserve(100);
+    let sb = s.capacity();
+    let mut v = vecs.take();
+    v.reserve(100);
+    let vb = v.capacity() * 8;
+    drop(s);
+    drop(v);
+    assert_eq!(pools[0].len(), 1);
+    assert_eq!(pools[1].stats().capacity, 1024);
+    // retained bytes are only counted with the stats feature
+    let (sb, vb) = if cfg!(feature = "stats") { (sb, vb) } else { (0, 0) };
+    assert_eq!(pools[0].retained_bytes(), sb);
+    assert_eq!(pools[1].retained_bytes(), vb);
+    let v = vecs.take();
+    assert_eq!(pools[1].retained_bytes(), 0);
+    drop(v);
+    for p in &pools {
+        p.clear();
+        assert!(p.is_empty());
+        assert_eq!(p.retained_bytes(), 0);
+    }
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn retained_bytes_never_wraps() {
+    use crate::global::{
+        backend::{MutexStack, PoolBackend},
+        GPooled, WeakPool,
+    };
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    type V = GPooled<Vec<u8>>;
+
+    // a backend that lets another take pop each object the moment it is
+    // stored, before the pushing insert has returned
+    struct Racy {
+        inner: MutexStack<V>,
+        pool: Arc<OnceLock<WeakPool<V>>>,
+        seen: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl PoolBackend<V> for Racy {
+        fn push(&self, t: V) -> Result<(), V> {
+            self.inner.push(t)?;
+            if let Some(pool) = self.pool.get().and_then(|p| p.upgrade())
+                && let Some(t) = pool.try_take()
+            {
+                self.seen.lock().unwrap().push(PoolControl::retained_bytes(&pool));
+                t.detach();
+            }
+            Ok(())
+        }
+
+        fn pop(&self) -> Option<V> {
+            self.inner.pop()
+        }
+
+        fn len(&self) -> usize {
+            self.inner.len()
+        }
+
+        fn capacity(&self) -> usize {
+            self.inner.capacity()
+        }
+    }
+
+    let (weak, seen) = (Arc::new(OnceLock::new()), Arc::new(Mutex::new(Vec::new())));
+    let racy = Racy { inner: MutexStack::new(4), pool: weak.clone(), seen: seen.clone() };
+    let pool: Pool<Vec<u8>> = RawPool::with_backend(racy, 1024);
+    let _ = weak.set(pool.downgrade());
+    let mut v = pool.take();
+    v.reserve(64);
+    drop(v);
+    assert_eq!(*seen.lock().unwrap(), [0]);
+    assert_eq!(PoolControl::retained_bytes(&pool), 0);
+}
+
+crate::static_pool! {
+    static REGISTERED: Vec<u16> = (8, 64);
+    fn registered;
+}
+
+#[test]
+fn static_pool_registry() {
+    use crate::global::registry;
+    let name = concat!(module_path!(), "::REGISTERED");
+    assert!(registry::get(name).is_none());
+    let mut v = registered().take();
+    v.push(1);
+    drop(v);
+    let p = registry::get(name).unwrap();
+    assert_eq!(p.len(), 1);
+    assert_eq!(p.stats().capacity, 8);
+    assert!(registry::pools().iter().any(|(n, _)| *n == name));
+    let tmp: Pool<String> = Pool::new(1, 1);
+    registry::register("tmp", &tmp);
+    assert!(registry::get("tmp").is_some());
+    drop(tmp);
+    assert!(registry::get("tmp").is_none());
+    assert!(!registry::pools().iter().any(|(n, _)| *n == "tmp"));
+}
+
+#[test]
+fn gpooled_map() {
+    let pool: Pool<Vec<usize>> = Pool::new(1024, 1024);
+    let mut v = pool.take();
+    v.extend([3, 1, 2]);
+    let a = v.as_ptr().addr();
+    let v = v.map(|mut v| {
+        v.sort();
+        v
+    });
+    assert_eq!(&*v, &[1, 2, 3]);
+    drop(v);
+    let v = pool.try_take().unwrap();
+    assert_eq!(v.as_ptr().addr(), a);
+    let r = v.try_map(|v| if v.is_empty() { Err(v.capacity()) } else { Ok(v) });
+    assert!(r.unwrap_err() >= 3);
+    assert!(pool.try_take().is_none());
+}
+
+#[test]
+fn prune_largest() {
+    let pool: Pool<Vec<u8>> = Pool::new(10, 1 << 20);
+    assert_eq!(pool.prune_mode(), PruneMode::Oldest);
+    pool.set_prune_mode(PruneMode::Largest);
+    let vs: Vec<_> = [16, 1 << 20, 64, 32]
+        .into_iter()
+        .map(|n| {
+            let mut v = pool.take();
+            v.reserve_exact(n);
+            v
+        })
+        .collect();
+    drop(vs);
+    pool.prune();
+    assert_eq!(pool.len(), 3);
+    assert!(pool.retained_bytes() < 1024);
+}
+
+#[test]
+fn freeze_thaw() {
+    let pool: Pool<String> = Pool::new(16, 1024);
+    let mut s = pool.take();
+    s.push_str("hello");
+    drop(s);
+    assert_eq!(pool.len(), 1);
+    pool.freeze();
+    assert!(pool.is_frozen());
+    assert_eq!(pool.len(), 0);
+    let mut s = pool.take();
+    s.push_str("hello");
+    drop(s);
+    assert_eq!(pool.len(), 0);
+    assert_eq!(pool.retained_bytes(), 0);
+    pool.thaw();
+    let mut s = pool.take();
+    s.push_str("hello");
+    drop(s);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn gpooled_pin_unpin() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let mut v = pool.take();
+    v.extend_from_slice(b"pinned");
+    let a = v.as_ptr().addr();
+    let p = v.pin();
+    let moved = vec![p];
+    assert_eq!(moved[0].as_ptr().addr(), a);
+    drop(moved);
+    let v = pool.try_take().unwrap();
+    assert_eq!(v.as_ptr().addr(), a);
+}
+
+#[test]
+fn gpooled_pin() {
+    use crate::{pooled::Pinned, PinPoolable};
+    use std::{
+        marker::PhantomPinned,
+        pin::Pin,
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    // points at itself once it is set up, so it notices being moved
+    struct SelfRef {
+        this: *const SelfRef,
+        resets: usize,
+        _pin: PhantomPinned,
+    }
+    impl SelfRef {
+        fn set_up(self: Pin<&mut Self>) {
+            let this = unsafe { self.get_unchecked_mut() };
+            this.this = this;
+        }
+        fn unmoved(&self) -> bool {
+            self.this.is_null() || ptr::eq(self.this, self)
+        }
+    }
+    impl Drop for SelfRef {
+        fn drop(&mut self) {
+            assert!(self.unmoved());
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    impl PinPoolable for SelfRef {
+        fn empty() -> Self {
+            SelfRef { this: ptr::null(), resets: 0, _pin: PhantomPinned }
+        }
+        fn reset(self: Pin<&mut Self>) {
+            assert!(self.unmoved());
+            unsafe { self.get_unchecked_mut() }.resets += 1
+        }
+        fn capacity(&self) -> usize {
+            1
+        }
+    }
+    let pool: Pool<Pinned<SelfRef>> = Pool::new(1, 1024);
+    let (mut a, mut b) = (pool.take(), pool.take());
+    a.as_mut().set_up();
+    b.as_mut().set_up();
+    let addr = ptr::from_ref::<SelfRef>(&a).addr();
+    // moving the handles doesn't move the objects
+    let moved = vec![a, b];
+    assert!(moved.iter().all(|t| t.unmoved() && !t.this.is_null()));
+    // one is reset in place, the pool is full so the other is dropped in place
+    drop(moved);
+    assert_eq!((pool.len(), DROPPED.load(Ordering::Relaxed)), (1, 1));
+    let a = pool.take();
+    assert_eq!(ptr::from_ref::<SelfRef>(&a).addr(), addr);
+    assert!(a.unmoved() && !a.this.is_null());
+    assert_eq!(a.resets, 1);
+    drop(a);
+    drop(pool);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn gpooled_reader() {
+    use std::io::{BufRead, Read, Seek, SeekFrom};
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let mut buf = pool.take();
+    buf.extend_from_slice(b"line one\nline two\n");
+    let a = buf.as_ptr().addr();
+    let mut r = buf.reader();
+    let mut line = String::new();
+    r.read_line(&mut line).unwrap();
+    assert_eq!(line, "line one\n");
+    assert_eq!(r.position(), 9);
+    r.seek(SeekFrom::End(-4)).unwrap();
+    let mut rest = Vec::new();
+    r.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"two\n");
+    drop(r);
+    let buf = pool.try_take().unwrap();
+    assert_eq!(buf.as_ptr().addr(), a);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn frame_ring() {
+    use crate::global::ring;
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let (mut tx, mut rx) = ring::channel(2, &pool);
+    tx.push_frame(b"one").unwrap();
+    tx.push_frame(b"two").unwrap();
+    let full = tx.push_frame(b"three").unwrap_err();
+    assert_eq!(&*full, b"three");
+    assert_eq!(rx.len(), 2);
+    let f = rx.pop_frame().unwrap();
+    assert_eq!(&*f, b"one");
+    let a = f.as_ptr().addr();
+    drop(f);
+    assert_eq!(pool.len(), 1);
+    tx.push(full).unwrap();
+    assert_eq!(&*rx.pop_frame().unwrap(), b"two");
+    assert_eq!(&*rx.pop_frame().unwrap(), b"three");
+    assert!(rx.pop_frame().is_none());
+    drop(rx);
+    assert!(tx.is_disconnected());
+    assert_eq!(pool.len(), 3);
+    let f = tx.segment();
+    assert_eq!(f.as_ptr().addr(), a);
+}
+
+#[test]
+fn bypass_oversized() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 64);
+    let mut v = pool.take();
+    v.reserve(32);
+    drop(v);
+    let big = pool.take_with_capacity(1024);
+    assert!(big.capacity() >= 1024);
+    assert_eq!(pool.len(), 0);
+    drop(big);
+    let mut v = pool.take();
+    v.reserve(32);
+    drop(v);
+    pool.set_bypass_oversized(true);
+    let big = pool.take_with_capacity(1024);
+    assert!(big.capacity() >= 1024);
+    assert_eq!(pool.len(), 1);
+    drop(big);
+    assert_eq!(pool.len(), 1);
+    let small = pool.take_with_capacity(16);
+    assert!(small.capacity() >= 32);
+    assert_eq!(pool.len(), 0);
+}
+
+#[test]
+fn clone_config() {
+    use crate::global::{backend::TakeOrder, set::PoolSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let evicted = std::sync::Arc::new(AtomicUsize::new(0));
+    let reported = std::sync::Arc::new(AtomicUsize::new(0));
+    let template: Pool<String> = Pool::new(7, 99);
+    template.set_prune_mode(PruneMode::Largest);
+    template.set_bypass_oversized(true);
+    template.set_trace_spans(true);
+    template.set_deep_size(true);
+    template.set_min_elt_capacity(2);
+    template.set_max_takers(5);
+    template.set_initial_capacity(3);
+    template.set_miss_prefill(4, 2);
+    template.set_track_threads(true);
+    let n = evicted.clone();
+    template.set_eviction_hook(Some(move |_: &crate::global::evict::Eviction| {
+        n.fetch_add(1, Ordering::Relaxed);
+    }));
+    let n = reported.clone();
+    template.set_retention_hook(Some(
+        move |_: &crate::global::report::RetentionReport| {
+            n.fetch_add(1, Ordering::Relaxed);
+        },
+    ));
+    drop(template.take_with_capacity(10));
+    template.freeze();
+    assert_eq!(evicted.load(Ordering::Relaxed), 1);
+    let tenant = template.clone_config();
+    let st = tenant.stats();
+    assert_eq!((st.len, st.capacity, st.max_elt_capacity), (0, 7, 99));
+    assert_eq!(tenant.prune_mode(), PruneMode::Largest);
+    assert!(tenant.bypass_oversized());
+    assert!(!tenant.is_frozen());
+    assert!(tenant.trace_spans() && tenant.deep_size());
+    assert_eq!(tenant.min_elt_capacity(), 2);
+    assert_eq!(tenant.max_takers(), Some(5));
+    assert_eq!(tenant.initial_capacity(), 3);
+    assert_eq!(tenant.miss_prefill(), Some((4, 2)));
+    assert_eq!(tenant.track_threads(), cfg!(feature = "stats"));
+    assert_eq!(tenant.order(), TakeOrder::Fifo);
+    // the hooks carried over, and retention is tracked
+    assert!(tenant.retention_report().is_some());
+    tenant.take().reserve(200);
+    assert_eq!(evicted.load(Ordering::Relaxed), 2);
+    drop(tenant);
+    assert_eq!(reported.load(Ordering::Relaxed), 1);
+    assert_eq!(template.with_same_config().prune_mode(), PruneMode::Largest);
+    // so does a custom backend, and its take order
+    let lifo: Pool<String> = Pool::new_lifo(4, 1024);
+    let tenant = lifo.clone_config();
+    assert_eq!((tenant.order(), tenant.capacity()), (TakeOrder::Lifo, 4));
+    // and the budget is shared
+    let set = PoolSet::new(150);
+    let tenant = set.pool::<Vec<u8>>().clone_config();
+    let mut a = tenant.take();
+    let mut b = set.take::<Vec<u8>>();
+    a.reserve_exact(100);
+    b.reserve_exact(100);
+    drop(a);
+    drop(b);
+    assert_eq!(tenant.len(), 1);
+    assert!(set.pool::<Vec<u8>>().is_empty());
+}
+
+#[test]
+fn pool_set() {
+    use crate::global::set::PoolSet;
+    let set = PoolSet::new(256);
+    let mut a = set.take::<Vec<u8>>();
+    let mut b = set.take::<Vec<u8>>();
+    let mut c = set.take::<String>();
+    a.reserve_exact(100);
+    b.reserve_exact(100);
+    c.reserve_exact(100);
+    let pa = a.as_ptr().addr();
+    drop(a);
+    drop(b);
+    assert_eq!(set.retained_bytes(), 200);
+    drop(c);
+    assert_eq!(set.retained_bytes(), 200);
+    assert!(set.pool::<String>().is_empty());
+    let a = set.take::<Vec<u8>>();
+    assert_eq!(a.as_ptr().addr(), pa);
+    assert_eq!(set.retained_bytes(), 100);
+    let mut n = 0;
+    set.for_each(|p| n += p.len());
+    assert_eq!(n, 1);
+    let pool = set.pool::<Vec<u8>>();
+    drop(set);
+    #[cfg(feature = "stats")]
+    assert_eq!(pool.retained_bytes(), 100);
+    drop(pool);
+    drop(a);
+}
+
+#[test]
+fn initial_capacity() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    pool.set_initial_capacity(100);
+    let v = pool.take();
+    assert!(v.capacity() >= 100);
+    assert_eq!(pool.clone_config().initial_capacity(), 100);
+    crate::local::set_initial_capacity::<VecDeque<[u16; 3]>>(10);
+    let v: LPooled<VecDeque<[u16; 3]>> = LPooled::take();
+    assert!(v.capacity() >= 10);
+    global::set_initial_capacity::<VecDeque<[u16; 5]>>(20);
+    let v = global::take::<VecDeque<[u16; 5]>>();
+    assert!(v.capacity() >= 20);
+}
+
+#[test]
+fn sharded_pool() {
+    use crate::global::{sharded::ShardedPool, GPooled};
+    let pool: ShardedPool<GPooled<Vec<u8>>, 4> = ShardedPool::new(16, 1024);
+    let mut a = pool.take(1);
+    a.reserve(10);
+    let pa = a.as_ptr().addr();
+    drop(a);
+    assert_eq!(pool.shard(1).len(), 1);
+    assert!(pool.try_take(2).is_none());
+    let a = pool.try_take(5).unwrap();
+    assert_eq!(a.as_ptr().addr(), pa);
+    drop(a);
+    assert_eq!(pool.shards().iter().map(|p| p.len()).sum::<usize>(), 1);
+}
+
+#[test]
+fn try_insert() {
+    use crate::RawPoolable;
+    let primary: Pool<Vec<u8>> = Pool::new(1, 64);
+    let secondary: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let mut a = primary.take();
+    let mut b = primary.take();
+    let mut c = primary.take();
+    a.reserve(10);
+    b.reserve(10);
+    c.reserve(100);
+    assert!(primary.try_insert(a).is_ok());
+    for v in [b, c] {
+        match primary.try_insert(v) {
+            Ok(()) => unreachable!(),
+            Err(mut v) => {
+                v.assign(&secondary);
+                drop(v)
+            }
+        }
+    }
+    assert_eq!(primary.len(), 1);
+    assert_eq!(secondary.len(), 2);
+    let a = primary.take();
+    let empty = primary.take();
+    assert_eq!(empty.capacity(), 0);
+    if let Err(v) = primary.try_insert(empty) {
+        v.really_drop()
+    }
+    assert_eq!(primary.len(), 0);
+    drop(a);
+    assert_eq!(primary.len(), 1);
+}
+
+#[test]
+fn global_handle() {
+    let h = global::handle::<Vec<[u8; 7]>>();
+    let mut v = h.take();
+    v.push([0; 7]);
+    let a = v.as_ptr().addr();
+    drop(v);
+    let v = global::take::<Vec<[u8; 7]>>();
+    assert_eq!(v.as_ptr().addr(), a);
+    drop(v);
+    let v = h.try_take().unwrap();
+    assert_eq!(v.as_ptr().addr(), a);
+    assert!(h.try_take().is_none());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn capacity_histogram() {
+    use crate::stats::CapacityHistogram;
+    let pool: Pool<Vec<u8>> = Pool::new(2, 1024);
+    let mut v0 = pool.take();
+    let mut v1 = pool.take();
+    let mut v2 = pool.take();
+    v0.reserve_exact(10);
+    v1.reserve_exact(100);
+    v2.reserve_exact(2000);
+    drop(v0);
+    drop(v1);
+    drop(v2);
+    let st = pool.stats();
+    assert_eq!(st.taken.buckets[0], 3);
+    assert_eq!(st.returned.total(), 3);
+    assert_eq!(st.returned.buckets[CapacityHistogram::bucket(10)], 1);
+    assert_eq!(st.evicted.total(), 1);
+    assert_eq!(st.evicted.buckets[CapacityHistogram::bucket(2000)], 1);
+    assert_eq!(st.returned.percentile(50.), Some(127));
+    PoolControl::clear(&pool);
+    assert_eq!(pool.stats().evicted.total(), 3);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn take_latency() {
+    let pool: Pool<Vec<u8>> = Pool::new(1, 1024);
+    let mut v0 = pool.take();
+    let mut v1 = pool.take();
+    v0.reserve(10);
+    v1.reserve(10);
+    drop(v0);
+    drop(v1);
+    drop(pool.take());
+    let st = pool.stats().latency;
+    assert_eq!(st.take_miss.total(), 2);
+    assert_eq!(st.take_hit.total(), 1);
+    assert_eq!(st.insert_pooled.total(), 2);
+    assert_eq!(st.insert_dropped.total(), 1);
+    assert!(st.take_miss.percentile(50.).is_some());
+    let _v = global::take::<Vec<u128>>();
+    let st = global::pool::<Vec<u128>>().unwrap().stats().latency;
+    assert_eq!(st.lookup.total(), 1);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn max_outstanding() {
+    let pool: Pool<String> = Pool::new(16, 1024);
+    let a: Vec<_> = (0..5).map(|_| pool.take()).collect();
+    let b = pool.take();
+    drop(a);
+    let st = pool.stats();
+    assert_eq!(st.outstanding, 1);
+    assert_eq!(st.max_outstanding, 6);
+    drop(b);
+    let c: Vec<_> = (0..3).map(|_| pool.take()).collect();
+    let st = pool.stats();
+    assert_eq!(st.outstanding, 3);
+    assert_eq!(st.max_outstanding, 6);
+    drop(c);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn usage_counts() {
+    use crate::local;
+    let pool: Pool<Vec<u8>> = Pool::new(1, 16);
+    let mut v0 = pool.take();
+    let mut v1 = pool.take();
+    let mut v2 = pool.take();
+    v0.reserve(8);
+    v1.reserve(8);
+    v2.reserve(32);
+    drop(v2); // oversized
+    drop(v0);
+    drop(v1); // full
+    drop(pool.take());
+    let v = pool.try_take();
+    assert!(v.is_some());
+    assert!(pool.try_take().is_none());
+    let c = pool.stats().counts;
+    assert_eq!((c.takes, c.hits, c.misses), (6, 2, 4));
+    assert_eq!(c.returns, 4);
+    assert_eq!((c.discarded_oversized, c.discarded_full, c.discarded_other), (1, 1, 0));
+    assert_eq!(c.hit_rate(), Some(2. / 6.));
+    // local pools count per thread
+    std::thread::spawn(|| {
+        local::set_size::<Vec<u32>>(1, 16);
+        let mut v0 = LPooled::<Vec<u32>>::take();
+        let mut v1 = LPooled::<Vec<u32>>::take();
+        v0.reserve(8);
+        v1.reserve(32);
+        drop(v0);
+        drop(v1);
+        drop(LPooled::<Vec<u32>>::take());
+        let st = local::stats::<Vec<u32>>().unwrap();
+        assert_eq!(st.len, 1);
+        let c = st.counts;
+        assert_eq!((c.takes, c.hits, c.misses, c.returns), (3, 1, 2, 3));
+        assert_eq!((c.discarded_oversized, c.discarded_full), (1, 0));
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(feature = "deterministic")]
+#[test]
+fn deterministic_queue_order() {
+    let pool: RawPool<Arc<String>> = RawPool::new(2, 1);
+    let v: std::vec::Vec<_> = (0..3).map(|i| Arc::new(&pool, i.to_string())).collect();
+    let addrs: std::vec::Vec<_> = v.iter().map(|a| a.as_ptr().addr()).collect();
+    // the third arc is dropped because the pool is full
+    drop(v);
+    let a0 = pool.try_take().unwrap();
+    let a1 = pool.try_take().unwrap();
+    assert_eq!(a0.as_ptr().addr(), addrs[0]);
+    assert_eq!(a1.as_ptr().addr(), addrs[1]);
+    assert!(pool.try_take().is_none());
+}
+
+#[test]
+fn fallible_take() {
+    let pool: Pool<Vec<u64>> = Pool::new(1024, 1024);
+    let v = pool.try_take_or_alloc(100).unwrap();
+    assert!(v.capacity() >= 100);
+    drop(v);
+    let e = pool.try_take_or_alloc(usize::MAX).unwrap_err();
+    assert_eq!(e.additional, usize::MAX);
+    // the pooled vec went back to the pool
+    assert!(pool.try_take().is_some());
+    let v = LPooled::<Vec<u64>>::try_take_or_alloc(100).unwrap();
+    assert!(v.capacity() >= 100);
+    assert!(LPooled::<Vec<u64>>::try_take_or_alloc(usize::MAX).is_err());
+}
+
 ////////// local pool tests //////////
 
 #[test]
@@ -444,6 +1255,29 @@ fn local_pool_indexset() {
     mk_local_pool_hashset!(IndexSet, FxHashSet)
 }
 
+#[test]
+fn local_pool_index_scratch() {
+    let d0 = <IndexMap<usize, usize> as IsoPoolable>::DISCRIMINANT;
+    let d1 = <IndexMapScratch<usize, usize> as IsoPoolable>::DISCRIMINANT;
+    let d2 = <IndexSetScratch<usize> as IsoPoolable>::DISCRIMINANT;
+    assert!(d0 != d1);
+    assert!(d1 != d2);
+    let mut mp = None;
+    let mut sp = None;
+    for i in 0..1000 {
+        let mut m = LPooled::<IndexMapScratch<usize, usize>>::take();
+        let mut s = LPooled::<IndexSetScratch<usize>>::take();
+        check_ptr(&mut mp, &m);
+        check_ptr(&mut sp, &s);
+        assert_eq!(m.len(), 0);
+        assert_eq!(s.len(), 0);
+        m.extend([(i, 0), (0, i)]);
+        s.extend([i, i + 1]);
+        assert_eq!(m.as_slice().get_index(0), Some((&i, &0)));
+        assert_eq!(s.as_slice().first(), Some(&i));
+    }
+}
+
 #[test]
 fn tarc_pool() {
     for _ in 0..100 {
@@ -503,3 +1337,1972 @@ fn arc_pool() {
         drop(pool)
     }
 }
+
+#[test]
+fn into_shared() {
+    let strings: Pool<String> = Pool::new(8, 1024);
+    let arcs: RawPool<Arc<String>> = RawPool::new(8, 1);
+    // a pooled arc whose string already has capacity
+    drop(Arc::new(&arcs, String::with_capacity(64)));
+    let mut s = strings.take();
+    s.push_str("hello");
+    let addr = s.as_ptr().addr();
+    let shared = s.into_shared(&arcs);
+    assert_eq!(&*shared, "hello");
+    assert_eq!(shared.as_str().as_ptr().addr(), addr);
+    // the arc's old string went back to the string pool
+    let s = strings.try_take().unwrap();
+    assert!(s.is_empty() && s.capacity() >= 64);
+    let a = shared.as_ptr().addr();
+    let b = shared.clone();
+    drop(shared);
+    assert_eq!(arcs.try_take(), None);
+    drop(b);
+    let shared = arcs.try_take().unwrap();
+    assert_eq!(shared.as_ptr().addr(), a);
+    assert!(shared.is_empty());
+}
+
+#[test]
+fn occupancy() {
+    let pool: Pool<Vec<u8>> = Pool::new(4, 256);
+    assert!(pool.is_empty());
+    assert_eq!((pool.capacity(), pool.max_elt_capacity()), (4, 256));
+    let v: Vec<_> = (0..6)
+        .map(|_| {
+            let mut v = pool.take();
+            v.push(0);
+            v
+        })
+        .collect();
+    drop(v);
+    assert_eq!(pool.len(), 4);
+    assert!(!pool.is_empty());
+}
+
+#[test]
+fn deep_size() {
+    use crate::size_of::SizeOf;
+    let pool: Pool<Vec<String>> = Pool::new(16, 1024);
+    let mut v = pool.take();
+    v.push("x".repeat(2048));
+    assert!(v.deep_size_of() >= 2048 + std::mem::size_of::<String>());
+    drop(v);
+    // only the slots count
+    assert_eq!(pool.len(), 1);
+    pool.set_deep_size(true);
+    assert!(pool.clone_config().deep_size());
+    let mut v = pool.take();
+    v.push("x".repeat(2048));
+    drop(v);
+    assert_eq!(pool.len(), 0);
+    let mut v = pool.take();
+    v.push("x".repeat(16));
+    drop(v);
+    assert_eq!(pool.len(), 1);
+    pool.set_deep_size(false);
+    let mut v = pool.take();
+    v.push("x".repeat(2048));
+    drop(v);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn frozen() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let mut v = pool.take();
+    v.extend_from_slice(b"frozen");
+    let a = v.as_ptr().addr();
+    let f = v.freeze();
+    assert_eq!(&*f, b"frozen");
+    let mut set = HashSet::new();
+    set.insert(f);
+    assert!(set.contains(&b"frozen"[..]));
+    drop(set);
+    let v = pool.take();
+    assert!(v.is_empty());
+    assert_eq!(v.as_ptr().addr(), a);
+    let mut s: LPooled<String> = LPooled::take();
+    s.push_str("local");
+    assert_eq!(s.freeze().to_string(), "local");
+}
+
+#[cfg(unix)]
+#[test]
+fn inspect() {
+    use global::{inspect, registry};
+    let pool: Pool<String> = Pool::new(16, 64);
+    registry::register("test::inspect", &pool);
+    let mut s = pool.take();
+    s.push_str("inspect");
+    drop(s);
+    let path =
+        std::env::temp_dir().join(format!("poolshark-test-{}.sock", std::process::id()));
+    inspect::serve(&path).unwrap();
+    let pools = inspect::connect(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let info = pools.iter().find(|p| p.name == "test::inspect").unwrap();
+    assert_eq!(info.len, 1);
+    assert_eq!(info.capacity, 16);
+    assert_eq!(info.type_name, std::any::type_name::<global::GPooled<String>>());
+    assert_eq!(info.to_string().parse::<inspect::PoolInfo>().unwrap(), *info);
+    assert!(inspect::table(&pools).contains("test::inspect"));
+}
+
+#[test]
+fn take_must_hit() {
+    use crate::testing;
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    assert!(testing::try_take_must_hit(&pool).is_err());
+    let mut v = pool.take();
+    v.push(1);
+    drop(v);
+    drop(testing::take_must_hit(&pool));
+    type T = Vec<[u8; 13]>;
+    assert!(testing::try_take_local_must_hit::<T>().is_err());
+    let mut v: LPooled<T> = LPooled::take();
+    v.push([0; 13]);
+    drop(v);
+    assert!(testing::take_local_must_hit::<T>().is_empty());
+}
+
+#[test]
+fn gpooled_with_weak() {
+    let pool: Pool<String> = Pool::new(8, 1024);
+    let weak = pool.downgrade();
+    drop(global::GPooled::with_weak(weak.clone(), String::from("cached")));
+    let s = pool.try_take().unwrap();
+    assert!(s.is_empty());
+    assert!(s.capacity() >= 6);
+    drop(s);
+    drop(pool);
+    assert!(weak.upgrade().is_none());
+    let s = global::GPooled::with_weak(weak, String::from("orphaned"));
+    assert_eq!(&*s, "orphaned");
+    drop(s);
+}
+
+#[test]
+fn reuse_retained() {
+    use crate::{Poolable, ReusePolicy};
+    struct Lookup {
+        table: Vec<u32>,
+        scratch: Vec<u32>,
+    }
+    impl Poolable for Lookup {
+        const REUSE: ReusePolicy = ReusePolicy::Retained;
+        fn empty() -> Self {
+            Lookup { table: (0..256).collect(), scratch: Vec::new() }
+        }
+        fn reset(&mut self) {
+            self.scratch.clear()
+        }
+        fn capacity(&self) -> usize {
+            self.scratch.capacity()
+        }
+    }
+    let pool: Pool<Lookup> = Pool::new(8, 1024);
+    let mut l = pool.take();
+    l.table[0] = 42;
+    l.scratch.push(1);
+    drop(l);
+    let l = pool.try_take().unwrap();
+    assert_eq!(l.table[0], 42);
+    assert!(l.scratch.is_empty());
+}
+
+#[test]
+fn eviction_hook() {
+    use global::evict::{EvictReason, Eviction};
+    let pool: Pool<Vec<u8>> = Pool::new(1, 64);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let s = seen.clone();
+    pool.set_eviction_hook(Some(move |e: &Eviction| s.lock().unwrap().push(e.reason)));
+    let mut a = pool.take();
+    let mut b = pool.take();
+    a.push(1);
+    b.push(1);
+    drop(a);
+    drop(b);
+    let mut c = pool.take();
+    c.reserve(128);
+    drop(c);
+    let mut e = pool.take();
+    e.push(1);
+    drop(e);
+    pool.prune();
+    drop(pool.take());
+    pool.set_eviction_hook(None::<fn(&Eviction)>);
+    let mut d = pool.take();
+    d.push(1);
+    drop(d);
+    pool.prune();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            EvictReason::Full,
+            EvictReason::Oversized,
+            EvictReason::Pruned,
+            EvictReason::Empty
+        ]
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn eviction_stream() {
+    use global::evict::EvictReason;
+    let pool: Pool<Vec<u8>> = Pool::new(8, 64);
+    let mut evictions = pool.evictions();
+    let mut v = pool.take();
+    v.reserve(128);
+    drop(v);
+    let e = evictions.recv().await.unwrap();
+    assert_eq!(e.reason, EvictReason::Oversized);
+    assert!(e.capacity >= 128);
+    drop(pool);
+    assert!(evictions.recv().await.is_none());
+}
+
+#[test]
+fn reserve_pooled() {
+    use crate::OversizedReserve;
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let s = seen.clone();
+    crate::set_oversized_reserve_hook(Some(move |r: &OversizedReserve| {
+        if r.type_name.contains("[u8; 11]") {
+            s.lock().unwrap().push((r.capacity, r.max_elt_capacity))
+        }
+    }));
+    let pool: Pool<Vec<[u8; 11]>> = Pool::new(8, 64);
+    let mut v = pool.take();
+    v.reserve_pooled(32);
+    assert!(seen.lock().unwrap().is_empty());
+    v.reserve_pooled(128);
+    assert!(v.capacity() >= 128);
+    assert_eq!(*seen.lock().unwrap(), [(v.capacity(), 64)]);
+    let mut l: LPooled<Vec<[u8; 11]>> = LPooled::take_sz(8, 16);
+    l.try_reserve_pooled(8).unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    l.try_reserve_pooled(32).unwrap();
+    assert_eq!(seen.lock().unwrap()[1], (l.capacity(), 16));
+    crate::set_oversized_reserve_hook(None::<fn(&OversizedReserve)>);
+}
+
+#[test]
+fn drain_target() {
+    type T = Vec<[u8; 17]>;
+    let reserve: Pool<T> = Pool::new(8, 1024);
+    global::set_drain_target(Some(&reserve));
+    let a = std::thread::spawn(|| {
+        let mut v = global::take::<T>();
+        v.push([0; 17]);
+        drop(v);
+        global::take::<T>().as_ptr().addr()
+    })
+    .join()
+    .unwrap();
+    global::set_drain_target::<T>(None);
+    let v = reserve.try_take().unwrap();
+    assert!(v.is_empty());
+    assert_eq!(v.as_ptr().addr(), a);
+    drop(v);
+    assert!(reserve.try_take().is_some());
+}
+
+#[test]
+fn local_global_conversion() {
+    type T = Vec<[u8; 19]>;
+    let pool: Pool<T> = Pool::new(8, 1024);
+    let mut l: LPooled<T> = LPooled::take();
+    l.push([0; 19]);
+    let a = l.as_ptr().addr();
+    let g = l.into_global(&pool);
+    assert_eq!(g.len(), 1);
+    drop(g);
+    let g = pool.try_take().unwrap();
+    assert_eq!(g.as_ptr().addr(), a);
+    let l = g.into_local();
+    drop(l);
+    assert!(pool.try_take().is_none());
+    let l: LPooled<T> = LPooled::take();
+    assert_eq!(l.as_ptr().addr(), a);
+}
+
+#[test]
+fn alloc_family() {
+    use crate::local;
+    type E = [u8; 23];
+    let mut q: VecDeque<E> = local::take_family();
+    q.push_front([0; 23]);
+    q.push_back([1; 23]);
+    let cap = q.capacity();
+    assert!(local::insert_family(q).is_none());
+    let v: Vec<E> = local::take();
+    assert!(v.is_empty());
+    assert_eq!(v.capacity(), cap);
+    local::insert(v);
+    let q: VecDeque<E> = local::take_family();
+    assert!(q.is_empty());
+    assert_eq!(q.capacity(), cap);
+    let mut s: String = local::take_family();
+    s.push_str("family");
+    local::insert_family(s);
+    let b: Vec<u8> = local::take();
+    assert!(b.is_empty() && b.capacity() >= 6);
+}
+
+#[test]
+fn gpooled_take_from() {
+    let pool: Pool<String> = Pool::new(8, 1024);
+    assert!(global::GPooled::try_take_from(&pool).is_none());
+    let mut s = global::GPooled::take_from(&pool);
+    s.push_str("from");
+    drop(s);
+    let s = global::GPooled::try_take_from(&pool).unwrap();
+    assert!(s.is_empty() && s.capacity() >= 4);
+}
+
+#[test]
+fn recycle_all() {
+    let a: Pool<String> = Pool::new(16, 1024);
+    let b: Pool<String> = Pool::new(16, 1024);
+    let mut batch = Vec::new();
+    for i in 0..12 {
+        let mut s = if i % 3 == 0 { b.take() } else { a.take() };
+        s.push_str("recycle");
+        batch.push(s);
+    }
+    batch.push(global::GPooled::orphan(String::from("orphan")));
+    global::recycle_all(&mut batch);
+    assert!(batch.is_empty());
+    assert_eq!(a.stats().len, 8);
+    assert_eq!(b.stats().len, 4);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn ageing_stats() {
+    let pool: Pool<Vec<u8>> = Pool::new(1, 1024);
+    for _ in 0..5 {
+        let mut v = pool.take();
+        v.push(1);
+    }
+    let mut a = pool.take();
+    let mut b = pool.take();
+    a.push(1);
+    b.push(1);
+    drop(a);
+    drop(b);
+    pool.prune();
+    let st = pool.stats().ageing;
+    assert_eq!(st.uses.total(), 2);
+    assert_eq!(st.lifetime.total(), 2);
+    // a was used 6 times, b once, bucket 3 counts 4..8
+    assert_eq!(st.uses.buckets[1], 1);
+    assert_eq!(st.uses.buckets[3], 1);
+}
+
+#[test]
+fn miss_prefill() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    pool.set_initial_capacity(64);
+    pool.set_miss_prefill(3, 4);
+    assert_eq!(pool.miss_prefill(), Some((3, 4)));
+    let held: Vec<_> = (0..2).map(|_| pool.take()).collect();
+    assert_eq!(pool.stats().len, 0);
+    let third = pool.take();
+    assert_eq!(pool.stats().len, 4);
+    assert!(pool.try_take().unwrap().capacity() >= 64);
+    assert_eq!(pool.clone_config().miss_prefill(), Some((3, 4)));
+    pool.set_miss_prefill(0, 0);
+    assert_eq!(pool.miss_prefill(), None);
+    drop((held, third));
+}
+
+#[test]
+fn no_pool() {
+    type T = Vec<[u8; 29]>;
+    let mut v: LPooled<T> = LPooled::take();
+    v.push([0; 29]);
+    drop(v);
+    crate::set_no_pool::<T>();
+    assert!(crate::is_no_pool::<T>());
+    let v: LPooled<T> = LPooled::take();
+    assert_eq!(v.capacity(), 0);
+    let mut g = global::take::<T>();
+    g.push([0; 29]);
+    drop(g);
+    assert_eq!(global::take::<T>().capacity(), 0);
+    crate::unset_no_pool::<T>();
+    assert!(!crate::is_no_pool::<T>());
+    let w: LPooled<T> = LPooled::take();
+    assert!(w.capacity() > 0);
+    drop((v, w));
+}
+
+#[test]
+fn no_pool_fast() {
+    use crate::local::fast::LPooledFast;
+    type T = Vec<[u8; 31]>;
+    let mut v: LPooledFast<T> = LPooledFast::take();
+    v.push([0; 31]);
+    assert!(v.is_cached());
+    // listed after the take, the cached pool must not get it back
+    crate::set_no_pool::<T>();
+    assert!(!v.is_cached());
+    drop(v);
+    crate::unset_no_pool::<T>();
+    assert_eq!(LPooledFast::<T>::take().capacity(), 0);
+}
+
+crate::assert_poolable!(Vec<u8>, HashMap<u64, String>, FixedVec<u32, 16>, IndexMap<u64, u64>);
+
+#[test]
+fn derive_poolable() {
+    #[derive(crate::Poolable)]
+    struct Scratch {
+        keys: Vec<u64>,
+        names: String,
+    }
+    #[derive(crate::Poolable, Debug, PartialEq)]
+    enum Message<T> {
+        #[poolable(empty)]
+        Heartbeat,
+        Batch(Vec<T>),
+        Update {
+            key: String,
+            values: Vec<u8>,
+        },
+    }
+    let pool: Pool<Scratch> = Pool::new(8, 1024);
+    let mut s = pool.take();
+    s.keys.push(1);
+    s.names.push('a');
+    drop(s);
+    let s = pool.try_take().unwrap();
+    assert!(s.keys.is_empty() && s.keys.capacity() > 0 && s.names.is_empty());
+    assert_eq!(<Scratch as crate::Poolable>::REUSE, crate::ReusePolicy::Empty);
+    // a taken message may be a reset Update or Batch, not the empty variant
+    assert_eq!(<Message<u32> as crate::Poolable>::REUSE, crate::ReusePolicy::Retained);
+    let pool: Pool<Message<u32>> = Pool::new(8, 1024);
+    let mut m = pool.take();
+    assert_eq!(*m, Message::Heartbeat);
+    *m = Message::Update { key: "k".into(), values: vec![1, 2, 3] };
+    drop(m);
+    let m = pool.try_take().unwrap();
+    match &*m {
+        Message::Update { key, values } => {
+            assert!(key.is_empty() && values.is_empty() && values.capacity() >= 3)
+        }
+        m => panic!("unexpected variant {m:?}"),
+    }
+    let mut m = m;
+    *m = Message::Batch(vec![1]);
+    drop(m);
+    assert_eq!(*pool.try_take().unwrap(), Message::Batch(vec![]));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn track_threads() {
+    let pool: Pool<Vec<u8>> = Pool::new(64, 1024);
+    pool.set_track_threads(true);
+    for _ in 0..4 {
+        let mut v = pool.take();
+        v.push(1);
+    }
+    let batch: Vec<_> = (0..4).map(|_| pool.take()).collect();
+    std::thread::spawn(move || drop(batch)).join().unwrap();
+    let st = pool.stats();
+    assert_eq!(st.threads.len(), 2);
+    assert_eq!(st.threads.iter().map(|t| t.taken).sum::<u64>(), 8);
+    assert_eq!(st.threads.iter().map(|t| t.returned).sum::<u64>(), 8);
+    assert_eq!(st.same_thread_ratio(), Some(0.5));
+    pool.set_track_threads(false);
+    assert!(pool.stats().threads.is_empty());
+}
+
+#[test]
+fn borrow_path_and_slice() {
+    use std::path::{Path, PathBuf};
+    let mut paths: HashMap<global::GPooled<PathBuf>, usize> = HashMap::new();
+    let mut p = global::GPooled::<PathBuf>::take();
+    p.push("/tmp/foo");
+    paths.insert(p, 1);
+    assert_eq!(paths.get(Path::new("/tmp/foo")), Some(&1));
+    let mut bytes: HashMap<LPooled<Vec<u8>>, usize> = HashMap::new();
+    let mut b = LPooled::<Vec<u8>>::take();
+    b.extend_from_slice(b"key");
+    bytes.insert(b, 2);
+    assert_eq!(bytes.get(&b"key"[..]), Some(&2));
+}
+
+#[test]
+fn retention_report() {
+    use crate::global::report::RetentionReport;
+    use std::sync::{Arc, Mutex};
+    let pool: Pool<Vec<u8>> = Pool::new(2, 1024);
+    assert!(pool.retention_report().is_none());
+    let last = Arc::new(Mutex::new(None));
+    let l = Arc::clone(&last);
+    pool.set_retention_hook(Some(move |r: &RetentionReport| {
+        *l.lock().unwrap() = Some(*r)
+    }));
+    let mut batch: Vec<_> = (0..3).map(|_| pool.take()).collect();
+    for v in &mut batch {
+        v.push(1)
+    }
+    drop(batch);
+    assert!(pool.try_take().is_some());
+    let r = pool.retention_report().unwrap();
+    assert_eq!((r.takes, r.hits, r.evictions, r.peak_len), (4, 1, 1, 2));
+    assert_eq!(r.hit_rate(), Some(0.25));
+    drop(pool);
+    let r = last.lock().unwrap().unwrap();
+    assert_eq!((r.takes, r.hits, r.evictions, r.capacity), (4, 1, 1, 2));
+}
+
+#[test]
+fn take_or_default_with() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let mut calls = 0;
+    let v = pool.take_or_default_with(|| {
+        calls += 1;
+        Vec::with_capacity(512)
+    });
+    assert!(v.capacity() >= 512);
+    drop(v);
+    assert_eq!(pool.len(), 1);
+    let v = pool.take_or_default_with(|| {
+        calls += 1;
+        Vec::new()
+    });
+    assert!(v.capacity() >= 512);
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn custom_backend() {
+    use crate::global::backend::MutexStack;
+    let pool: Pool<Vec<u8>> = RawPool::with_backend(MutexStack::new(2), 1024);
+    let mut batch: Vec<_> = (0..3).map(|_| pool.take()).collect();
+    for (i, v) in batch.iter_mut().enumerate() {
+        v.reserve(16 << i)
+    }
+    let caps: Vec<usize> = batch.iter().map(|v| v.capacity()).collect();
+    drop(batch);
+    assert_eq!(pool.len(), 2);
+    // last in first out
+    assert_eq!(pool.take().capacity(), caps[1]);
+    assert_eq!(pool.clone_config().stats().capacity, 2);
+}
+
+#[test]
+fn export_import_sizes() {
+    use crate::global::sizes::{self, SizeConfig};
+    struct Unseen;
+    global::set_size::<Vec<i16>>(12, 345);
+    global::set_initial_capacity::<Vec<i16>>(6);
+    let saved = sizes::export();
+    let cfg =
+        saved.iter().find(|c| c.type_name == std::any::type_name::<Vec<i16>>()).unwrap();
+    assert_eq!(
+        (cfg.max_pool_size, cfg.max_element_capacity, cfg.initial_capacity),
+        (12, 345, 6)
+    );
+    let name = std::any::type_name::<Vec<Unseen>>();
+    sizes::import(&[SizeConfig {
+        type_name: name.into(),
+        max_pool_size: 7,
+        max_element_capacity: 89,
+        initial_capacity: 0,
+        max_outstanding: 0,
+    }]);
+    assert_eq!(global::get_size::<Vec<Unseen>>(), Some((7, 89)));
+}
+
+#[test]
+fn orphans() {
+    let o = global::GPooled::<Vec<u8>>::orphan_default();
+    assert!(o.is_empty() && o.is_orphan());
+    let o: global::GPooled<String> = String::from("hello").into();
+    assert!(o.is_orphan());
+    assert_eq!(&*o, "hello");
+    let mut l = LPooled::<Vec<u8>>::take();
+    l.push(1);
+    let o = l.into_gpooled_orphan();
+    assert!(o.is_orphan());
+    assert_eq!(&*o, &[1]);
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1024);
+    let t = pool.take();
+    assert!(!t.is_orphan());
+    drop(pool);
+    assert!(t.is_orphan());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn batch_sender() {
+    use crate::global::batch::BatchSender;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    let pool: Pool<Vec<u32>> = Pool::new(16, 1024);
+    let (tx, mut rx) = mpsc::channel(8);
+    let tx = BatchSender::new(tx, pool.clone(), 4, Duration::from_millis(50));
+    for i in 0..6 {
+        tx.send(i).await.unwrap();
+    }
+    assert_eq!(&**rx.recv().await.unwrap(), &[0, 1, 2, 3]);
+    assert!(rx.try_recv().is_err());
+    // the rest arrives when the deadline passes
+    let b = rx.recv().await.unwrap();
+    assert_eq!(&**b, &[4, 5]);
+    drop(b);
+    assert!(!pool.is_empty());
+    tx.send(6).await.unwrap();
+    tx.flush().await.unwrap();
+    assert_eq!(&**rx.recv().await.unwrap(), &[6]);
+    tx.send(7).await.unwrap();
+    drop(tx);
+    assert_eq!(&**rx.recv().await.unwrap(), &[7]);
+    assert!(rx.recv().await.is_none());
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+#[test]
+fn format_buffer() {
+    use crate::format::FormatBuffer;
+    #[cfg(feature = "chrono")]
+    {
+        let ts = chrono::DateTime::from_timestamp(86400, 0).unwrap();
+        let mut buf = FormatBuffer::new();
+        buf.push_chrono_rfc3339(&ts);
+        assert_eq!(&*buf, "1970-01-02T00:00:00+00:00");
+        assert_eq!(&*crate::format::format_chrono(&ts, "%d"), "02");
+    }
+    #[cfg(feature = "time")]
+    {
+        let ts = time::OffsetDateTime::from_unix_timestamp(86400).unwrap();
+        let mut buf = FormatBuffer::new();
+        buf.push_time(&ts, &time::format_description::well_known::Rfc3339).unwrap();
+        assert_eq!(&*buf, "1970-01-02T00:00:00Z");
+        let rfc2822 = time::format_description::well_known::Rfc2822;
+        let s = crate::format::format_time(&ts, &rfc2822).unwrap();
+        assert_eq!(&*s, "Fri, 02 Jan 1970 00:00:00 +0000");
+    }
+}
+
+#[test]
+fn bench_warm() {
+    use crate::bench;
+    let pool: Pool<Vec<u16>> = Pool::new(8, 1024);
+    bench::warm_pool(&pool, 4, |v| v.reserve(100));
+    assert_eq!(pool.len(), 4);
+    assert!(pool.take().capacity() >= 100);
+    let (n, counts) = bench::count_allocs(|| 42);
+    assert_eq!(n, 42);
+    // no counting allocator is installed in the test binary
+    assert_eq!(counts, bench::AllocCounts::default());
+}
+
+#[test]
+fn decode_scratch() {
+    use crate::decode::{self, DecodeScratch};
+    let mut s = DecodeScratch::<u32>::take();
+    s.bytes.extend_from_slice(b"abc");
+    s.items.push(1);
+    assert_eq!(s.text_from(b"hello").unwrap(), "hello");
+    drop(s);
+    let s = DecodeScratch::<u32>::take();
+    assert!(s.bytes.is_empty() && s.text.is_empty() && s.items.is_empty());
+    assert!(s.bytes.capacity() >= 3 && s.text.capacity() >= 5);
+    assert!(decode::string(b"\xff").is_err());
+    assert_eq!(&*decode::bytes(b"xy"), b"xy");
+    let mut i = 0;
+    let v = decode::vec(3, || {
+        i += 1;
+        Ok::<_, ()>(i)
+    });
+    assert_eq!(&**v.as_ref().unwrap(), &[1, 2, 3]);
+    assert_eq!(decode::vec(3, || Err::<u8, _>("short")), Err("short"));
+}
+
+#[test]
+fn try_reset() {
+    use crate::{
+        global::evict::{EvictReason, Eviction},
+        Poolable, ResetError,
+    };
+    use std::sync::Mutex;
+    #[derive(Debug)]
+    struct Conn {
+        buf: Vec<u8>,
+        poisoned: bool,
+    }
+    impl Poolable for Conn {
+        fn empty() -> Self {
+            Conn { buf: Vec::new(), poisoned: false }
+        }
+        fn reset(&mut self) {
+            self.buf.clear()
+        }
+        fn try_reset(&mut self) -> Result<(), ResetError> {
+            if self.poisoned {
+                return Err(ResetError { reason: "poisoned" });
+            }
+            self.reset();
+            Ok(())
+        }
+        fn capacity(&self) -> usize {
+            self.buf.capacity()
+        }
+    }
+    let pool: Pool<Conn> = Pool::new(8, 1024);
+    let reasons = std::sync::Arc::new(Mutex::new(vec![]));
+    let r = std::sync::Arc::clone(&reasons);
+    pool.set_eviction_hook(Some(move |e: &Eviction| r.lock().unwrap().push(e.reason)));
+    let mut c = pool.take();
+    c.buf.push(1);
+    drop(c);
+    assert_eq!(pool.len(), 1);
+    let mut c = pool.take();
+    c.buf.push(1);
+    c.poisoned = true;
+    drop(c);
+    assert_eq!(pool.len(), 0);
+    assert_eq!(*reasons.lock().unwrap(), [EvictReason::ResetFailed]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn gpooled_seed() {
+    use crate::global::GPooledSeed;
+    use serde::de::{
+        value::{Error, SeqDeserializer},
+        DeserializeSeed,
+    };
+    let seed = GPooledSeed::<Vec<u64>>::new(3, 17);
+    let de = SeqDeserializer::<_, Error>::new([4u64, 5].into_iter());
+    let v = seed.deserialize(de).unwrap();
+    assert_eq!(&*v, &[4, 5]);
+    let stats = global::pool_any_default::<Vec<u64>>().stats();
+    assert_eq!((stats.capacity, stats.max_elt_capacity), (3, 17));
+}
+
+#[cfg(feature = "leaks")]
+#[test]
+fn leaks_by_call_site() {
+    use global::leaks;
+    #[derive(Clone)]
+    struct Hoarded;
+    impl crate::Poolable for Hoarded {
+        fn empty() -> Self {
+            Hoarded
+        }
+        fn reset(&mut self) {}
+        fn capacity(&self) -> usize {
+            1
+        }
+    }
+    let count = || {
+        leaks::outstanding()
+            .into_iter()
+            .filter(|s| s.type_name.ends_with("Hoarded"))
+            .map(|s| (s.location.line(), s.count))
+            .collect::<Vec<_>>()
+    };
+    let pool: Pool<Hoarded> = Pool::new(16, 16);
+    let line = line!() + 1;
+    let mut hoard: Vec<_> = (0..5).map(|_| pool.take()).collect();
+    hoard.push(global::GPooled::take_from(&pool));
+    assert_eq!(count(), vec![(line, 5), (line + 1, 1)]);
+    let copy = hoard[0].clone();
+    assert_eq!(count(), vec![(line, 6), (line + 1, 1)]);
+    drop(copy);
+    hoard.truncate(2);
+    assert_eq!(count(), vec![(line, 2)]);
+    let t = hoard.pop().unwrap().map(|h| h);
+    assert_eq!(count(), vec![(line, 2)]);
+    t.detach();
+    drop(hoard);
+    assert_eq!(count(), vec![]);
+    let again = pool.try_take().unwrap();
+    assert_eq!(count(), vec![(line!() - 1, 1)]);
+    drop(again)
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn return_async() {
+    let pool: Pool<Vec<u32>> = Pool::new(8, 1024);
+    let mut v = pool.take();
+    v.extend(0..100);
+    let ret = v.return_async();
+    assert_eq!(pool.len(), 0);
+    ret.await;
+    assert_eq!(pool.len(), 1);
+    let v = pool.take();
+    assert!(v.is_empty() && v.capacity() >= 100);
+    drop(v.return_async());
+    assert_eq!(pool.len(), 1);
+    pool.take().spawn_return().await.unwrap();
+    assert_eq!(pool.len(), 1);
+}
+
+#[cfg(feature = "equivalent")]
+#[test]
+fn equivalent_keys() {
+    use equivalent::Comparable;
+    use std::cmp::Ordering;
+    // unpooled queries against pooled keys, through Borrow
+    let mut pooled: IndexMap<global::GPooled<String>, u32> = IndexMap::default();
+    let mut k = global::take::<String>();
+    k.push_str("key");
+    pooled.insert(k, 1);
+    assert_eq!(pooled.get("key"), Some(&1));
+    assert_eq!(pooled.get(&String::from("key")), Some(&1));
+    // pooled queries against unpooled keys
+    let mut unpooled: IndexMap<String, u32> = IndexMap::default();
+    unpooled.insert("other".into(), 2);
+    let mut q: LPooled<String> = LPooled::take();
+    q.push_str("other");
+    assert_eq!(unpooled.get(&q), Some(&2));
+    let g = global::GPooled::orphan(q.clone().detach());
+    assert_eq!(unpooled.get(&g), Some(&2));
+    let mut v: LPooled<Vec<u8>> = LPooled::take();
+    v.extend_from_slice(b"c");
+    let set: IndexSet<Vec<u8>> = IndexSet::from_iter([b"c".to_vec()]);
+    assert!(set.contains(&v));
+    assert_eq!(q.compare(&"key".to_string()), Ordering::Greater);
+}
+
+#[test]
+fn request_arena() {
+    use global::arena::RequestArena;
+    let strings: Pool<String> = Pool::new(64, 1024);
+    let vecs: Pool<Vec<u32>> = Pool::new(64, 1024);
+    let mut arena = RequestArena::new();
+    let mut held = Vec::new();
+    for i in 0..20 {
+        let s = arena.take_from(&strings);
+        s.push_str("request");
+        let v = arena.take_from(&vecs);
+        v.push(i);
+        held.push((s, v));
+    }
+    // references from earlier chunks are still valid
+    assert!(held
+        .iter()
+        .enumerate()
+        .all(|(i, (s, v))| *s == "request" && **v == [i as u32]));
+    drop(held);
+    assert_eq!(arena.len(), 40);
+    assert_eq!((strings.len(), vecs.len()), (0, 0));
+    arena.clear();
+    assert!(arena.is_empty());
+    assert_eq!((strings.len(), vecs.len()), (20, 20));
+    arena.hold(strings.take()).push('x');
+    let t = std::thread::spawn(move || arena.len()).join().unwrap();
+    assert_eq!(t, 1);
+    assert_eq!(strings.len(), 20);
+}
+
+#[test]
+fn send_pooled() {
+    use global::exclusive::SendPooled;
+    use std::{cell::Cell, sync::Arc};
+    #[derive(Default)]
+    struct Counted {
+        n: Cell<u32>,
+        buf: Vec<u8>,
+    }
+    impl crate::Poolable for Counted {
+        fn empty() -> Self {
+            Self::default()
+        }
+        fn reset(&mut self) {
+            self.n.set(0);
+            self.buf.clear()
+        }
+        fn capacity(&self) -> usize {
+            self.buf.capacity()
+        }
+    }
+    let pool: Pool<Counted> = Pool::new(8, 1024);
+    let mut t = SendPooled::take_from(&pool);
+    t.get_mut().n.set(3);
+    t.get_mut().buf.push(1);
+    let shared = Arc::new(t);
+    let s = shared.clone();
+    std::thread::spawn(move || drop(s)).join().unwrap();
+    drop(Arc::into_inner(shared));
+    assert_eq!(pool.len(), 1);
+    let t = SendPooled::try_take_from(&pool).unwrap().into_inner();
+    assert_eq!((t.n.get(), t.buf.len()), (0, 0));
+}
+
+#[test]
+fn min_elt_capacity() {
+    use global::{evict::EvictReason, string::PooledString};
+    let pool = global::string::pool(8, 1024, 32);
+    let reasons = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let r = reasons.clone();
+    pool.set_eviction_hook(Some(move |e: &global::evict::Eviction| {
+        r.lock().unwrap().push(e.reason)
+    }));
+    let small = PooledString::from_str_in(&pool, "tiny");
+    assert!(!small.is_pooled());
+    drop(small);
+    let mut s = pool.take();
+    s.push_str("not quite");
+    drop(s);
+    assert_eq!(pool.len(), 0);
+    assert_eq!(*reasons.lock().unwrap(), [EvictReason::Undersized]);
+    let big = PooledString::from_str_in(&pool, &"y".repeat(100));
+    assert!(big.is_pooled() && *big == *"y".repeat(100));
+    drop(big);
+    assert_eq!(pool.len(), 1);
+    assert_eq!(pool.clone_config().min_elt_capacity(), 32);
+}
+
+#[test]
+fn vec_uninit() {
+    use crate::pooled::{vec_from_uninit, vec_into_uninit};
+    use std::mem::MaybeUninit;
+    let mut v = vec![String::from("a"); 3];
+    v.reserve(10);
+    let cap = v.capacity();
+    let u = vec_into_uninit(v);
+    assert_eq!((u.len(), u.capacity()), (0, cap));
+    let v = vec_from_uninit(u);
+    assert_eq!((v.len(), v.capacity()), (0, cap));
+    // the uninit form shares the local pool of Vec<T>
+    let mut v: LPooled<Vec<u64>> = LPooled::take();
+    v.reserve(1000);
+    let ptr = v.as_ptr() as usize;
+    drop(v.into_uninit());
+    let u: LPooled<Vec<MaybeUninit<u64>>> = LPooled::take();
+    assert_eq!(u.as_ptr() as usize, ptr);
+    let v = u.into_init();
+    assert!(v.is_empty() && v.capacity() >= 1000);
+}
+
+#[test]
+fn scratch_helpers() {
+    use crate::scratch::{dedup_pooled, sort_unstable_pooled};
+    let mut v: Vec<u32> = (0..1000).map(|i| (i * 7919) % 251).collect();
+    let mut expected: Vec<u32> = Vec::new();
+    for x in &v {
+        if !expected.contains(x) {
+            expected.push(*x)
+        }
+    }
+    dedup_pooled(&mut v);
+    assert_eq!(v, expected);
+    sort_unstable_pooled(&mut v, |x| std::cmp::Reverse(*x));
+    expected.sort_by_key(|x| std::cmp::Reverse(*x));
+    assert_eq!(v, expected);
+    let mut s: global::GPooled<Vec<String>> = global::take();
+    s.extend(["bb", "a", "ccc", "a"].map(String::from));
+    dedup_pooled(&mut s);
+    sort_unstable_pooled(&mut s, |s| s.len());
+    assert_eq!(*s, ["a", "bb", "ccc"]);
+}
+
+#[test]
+fn max_takers() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    pool.set_max_takers(2);
+    assert_eq!(pool.max_takers(), Some(2));
+    let a = pool.take();
+    let b = pool.take();
+    let c = pool.take();
+    assert!(!a.is_orphan() && !b.is_orphan() && c.is_orphan());
+    assert!(pool.try_take().is_none());
+    assert_eq!(pool.takers(), 2);
+    drop(c);
+    assert_eq!(pool.takers(), 2);
+    drop(a);
+    let _local = b.into_local();
+    assert_eq!(pool.takers(), 0);
+    let held: Vec<_> = (0..2).map(|_| pool.take()).collect();
+    assert!(pool.take().is_orphan());
+    drop(held);
+    pool.set_max_takers(0);
+    assert_eq!(pool.max_takers(), None);
+    let all: Vec<_> = (0..4).map(|_| pool.take()).collect();
+    assert!(all.iter().all(|t| !t.is_orphan()));
+}
+
+#[test]
+fn max_takers_admitted_only() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let early = pool.take();
+    pool.set_max_takers(2);
+    let a = pool.take();
+    // neither objects taken before the limit nor strays give a slot back
+    drop(early);
+    drop(global::GPooled::with_weak(pool.downgrade(), Vec::with_capacity(8)));
+    assert_eq!(pool.takers(), 1);
+    let b = pool.take();
+    assert!(pool.take().is_orphan());
+    // an object try_insert gives back is released once, not again when
+    // it is dropped
+    let mut a = a;
+    a.reserve(4096);
+    let a = pool.try_insert(a).unwrap_err();
+    assert_eq!(pool.takers(), 1);
+    drop(a);
+    assert_eq!(pool.takers(), 1);
+    // map and try_map keep the slot, or give it back if the object is lost
+    let b = b.map(|v| v);
+    assert_eq!(pool.takers(), 1);
+    assert!(b.try_map(|_| Err::<Vec<u8>, _>(())).is_err());
+    assert_eq!(pool.takers(), 0);
+    let c = pool.take();
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        c.map(|_| panic!("lost"))
+    }));
+    assert!(r.is_err());
+    assert_eq!(pool.takers(), 0);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn trace_spans() {
+    use std::sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    };
+    use tracing::{
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+    #[derive(Default)]
+    struct Spans {
+        next: AtomicU64,
+        open: AtomicUsize,
+    }
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            assert_eq!(span.metadata().name(), "pooled");
+            self.open.fetch_add(1, Ordering::Relaxed);
+            Id::from_u64(self.next.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+        fn try_close(&self, _: Id) -> bool {
+            self.open.fetch_sub(1, Ordering::Relaxed);
+            true
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+    let spans = Arc::new(Spans::default());
+    let open = || spans.open.load(Ordering::Relaxed);
+    tracing::subscriber::with_default(spans.clone(), || {
+        let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+        drop(pool.take());
+        assert_eq!(open(), 0);
+        pool.set_trace_spans(true);
+        assert!(pool.clone_config().trace_spans());
+        let a = pool.take();
+        let b = pool.take();
+        assert_eq!(open(), 2);
+        let copy = a.clone();
+        assert_eq!(open(), 2);
+        drop(copy);
+        let a = a.map(|mut v| {
+            v.push(1);
+            v
+        });
+        assert_eq!(open(), 2);
+        drop(a);
+        assert_eq!(open(), 1);
+        b.detach();
+        assert_eq!(open(), 0);
+        let c = pool.try_take().unwrap();
+        assert_eq!(open(), 1);
+        drop(c);
+        assert_eq!(open(), 0);
+    })
+}
+
+#[test]
+fn node_list() {
+    let pool: Pool<NodeList<u64>> = Pool::new(8, 1024);
+    let mut l = pool.take();
+    l.extend(0..4);
+    l.push_front(10);
+    assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![10, 0, 1, 2, 3]);
+    assert_eq!((l.pop_front(), l.pop_back()), (Some(10), Some(3)));
+    assert_eq!((l.len(), l.capacity()), (3, 5));
+    let node = l.back().unwrap() as *const u64;
+    l.pop_back();
+    l.push_back(42);
+    assert_eq!(l.back().unwrap() as *const u64, node);
+    drop(l);
+    let mut l = pool.take();
+    assert!(l.is_empty());
+    assert_eq!(l.capacity(), 5);
+    l.extend(0..5);
+    assert_eq!(l.capacity(), 5);
+    l.push_back(5);
+    assert_eq!(l.capacity(), 6);
+    l.shrink_to_fit();
+    assert_eq!(l.capacity(), 6);
+    l.clear();
+    l.shrink_to_fit();
+    assert_eq!(l.capacity(), 0);
+    let mut lists: Pool<std::collections::LinkedList<u64>> = Pool::new(8, 1024);
+    lists.take().extend(0..3);
+    assert_eq!(lists.len(), 1);
+    assert!(lists.take().is_empty());
+    lists = Pool::new(8, 2);
+    lists.take().extend(0..3);
+    assert_eq!(lists.len(), 0);
+}
+
+#[test]
+fn pooled_ref() {
+    use global::view::PooledRef;
+    fn push<'a>(v: impl Into<PooledRef<'a, Vec<u32>>>, n: u32) -> bool {
+        let mut v = v.into();
+        let mut inner = v.reborrow();
+        inner.reserve_pooled(n as usize);
+        inner.extend(0..n);
+        v.is_pooled()
+    }
+    let pool: Pool<Vec<u32>> = Pool::new(8, 16);
+    let mut pooled = pool.take();
+    let mut plain = Vec::new();
+    assert!(push(&mut pooled, 4));
+    assert!(!push(&mut plain, 4));
+    assert!(push(pooled.downgrade(), 2));
+    assert_eq!(&pooled[..], &[0, 1, 2, 3, 0, 1]);
+    assert_eq!(&plain[..], &[0, 1, 2, 3]);
+    assert!(pooled.downgrade().try_reserve_pooled(4).is_ok());
+    drop(pooled);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn adopt_orphan() {
+    let mine: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let theirs: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let gone: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let mut dangling = gone.take();
+    drop(gone);
+    assert!(dangling.is_orphan());
+    dangling.push(1);
+    let v = vec![
+        mine.adopt_orphan(global::GPooled::orphan(vec![1])),
+        mine.adopt_orphan(dangling),
+        mine.adopt_orphan(theirs.take_with_capacity(4)),
+    ];
+    assert!(v.iter().all(|t| !t.is_orphan()));
+    drop(v);
+    assert_eq!((mine.len(), theirs.len()), (2, 1));
+    let mut t = global::GPooled::orphan(vec![1u8]);
+    t.ensure_attached(&theirs);
+    t.ensure_attached(&mine);
+    drop(t);
+    assert_eq!((mine.len(), theirs.len()), (2, 2));
+}
+
+#[test]
+fn lpooled_fast() {
+    use crate::local::fast::LPooledFast;
+    crate::local::clear_type::<Vec<u16>>();
+    let mut v: LPooledFast<Vec<u16>> = LPooledFast::take();
+    assert!(v.is_cached());
+    v.extend(0..100);
+    let cap = v.capacity();
+    drop(v);
+    let v: LPooled<Vec<u16>> = LPooled::take();
+    assert_eq!(v.capacity(), cap);
+    let v = LPooledFast::from(v.detach());
+    assert!(!v.is_cached());
+    drop(v);
+    let v: LPooledFast<Vec<u16>> = LPooledFast::take();
+    assert_eq!(v.capacity(), cap);
+    // the cached pool is freed, so it must fall back to the lookup
+    crate::local::clear();
+    assert!(!v.is_cached());
+    drop(v);
+    assert_eq!(LPooledFast::<Vec<u16>>::take().capacity(), cap);
+    // dropped on another thread it goes to that thread's pool
+    let mut v: LPooledFast<Vec<u16>> = LPooledFast::take();
+    v.reserve(1000);
+    let cap = v.capacity();
+    std::thread::spawn(move || {
+        assert!(!v.is_cached());
+        drop(v);
+        let v: LPooled<Vec<u16>> = LPooled::take();
+        assert_eq!(v.capacity(), cap);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(LPooledFast::<Vec<u16>>::take().capacity(), 0);
+}
+
+#[test]
+fn location_id_from_key_hash() {
+    use crate::{Discriminant, LocationId, Poolable};
+    const A: LocationId = LocationId::from_key_hash("poolshark::test::KeyedA");
+    assert_eq!(A, LocationId::from_key_hash("poolshark::test::KeyedA"));
+    assert_ne!(A, LocationId::from_key_hash("poolshark::test::KeyedB"));
+    assert_ne!(LocationId::from_key_hash("").0, 0);
+    macro_rules! keyed {
+        ($name:ident, $key:expr) => {
+            struct $name<T>(Vec<T>);
+            impl<T> Poolable for $name<T> {
+                fn empty() -> Self {
+                    Self(Vec::new())
+                }
+                fn reset(&mut self) {
+                    self.0.clear()
+                }
+                fn capacity(&self) -> usize {
+                    self.0.capacity()
+                }
+            }
+            unsafe impl<T> IsoPoolable for $name<T> {
+                const DISCRIMINANT: Option<Discriminant> =
+                    Discriminant::new_p1::<T>(LocationId::from_key_hash($key));
+            }
+        };
+    }
+    keyed!(KeyedA, "poolshark::test::KeyedA");
+    keyed!(KeyedCollides, "poolshark::test::KeyedA");
+    let mut a: LPooled<KeyedA<u32>> = LPooled::take();
+    a.0.reserve(10);
+    drop(a);
+    assert!(LPooled::<KeyedA<f32>>::take().0.capacity() >= 10);
+    // caught in release builds too
+    assert!(std::panic::catch_unwind(LPooled::<KeyedCollides<u32>>::take).is_err());
+}
+
+#[test]
+fn insert_unreset() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static RESETS: AtomicUsize = AtomicUsize::new(0);
+    struct Counted(Vec<u8>);
+    impl crate::Poolable for Counted {
+        fn empty() -> Self {
+            Counted(Vec::new())
+        }
+        fn reset(&mut self) {
+            RESETS.fetch_add(1, Ordering::Relaxed);
+            self.0.clear()
+        }
+        fn capacity(&self) -> usize {
+            self.0.capacity()
+        }
+    }
+    let pool: Pool<Counted> = Pool::new(1, 64);
+    let mut t = pool.take();
+    t.0.reserve(8);
+    drop(t);
+    assert_eq!((pool.len(), RESETS.load(Ordering::Relaxed)), (1, 1));
+    let t = pool.take();
+    unsafe { pool.insert_unreset(t) };
+    assert_eq!((pool.len(), RESETS.load(Ordering::Relaxed)), (1, 1));
+    // the limits still apply
+    let mut t = pool.take();
+    t.0.reserve(128);
+    unsafe { pool.insert_unreset(t) };
+    assert_eq!(pool.len(), 0);
+    unsafe {
+        pool.insert_unreset(global::GPooled::orphan(Counted(Vec::with_capacity(8))))
+    };
+    unsafe {
+        pool.insert_unreset(global::GPooled::orphan(Counted(Vec::with_capacity(8))))
+    };
+    assert_eq!((pool.len(), RESETS.load(Ordering::Relaxed)), (1, 1));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn prune_gradually() {
+    use std::time::{Duration, Instant};
+    let pool: Pool<Vec<u8>> = Pool::new(100, 1024);
+    let fill = |n: usize| {
+        let v: Vec<_> = (0..n).map(|_| pool.take_with_capacity(16)).collect();
+        drop(v)
+    };
+    fill(50);
+    let start = Instant::now();
+    let task = pool.prune_gradually(500);
+    assert_eq!(pool.len(), 50);
+    task.await.unwrap();
+    assert_eq!(pool.len(), 40);
+    assert!(start.elapsed() >= Duration::from_millis(18));
+    // high rates evict several objects per tick
+    fill(40);
+    let start = Instant::now();
+    pool.prune_gradually(1_000_000).await.unwrap();
+    assert_eq!(pool.len(), 30);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    // the task stops when the pool goes away
+    let task = pool.prune_gradually(1);
+    drop(pool);
+    tokio::time::timeout(Duration::from_secs(5), task).await.unwrap().unwrap();
+}
+
+#[test]
+fn record_sink() {
+    use crate::log::RecordSink;
+    use std::io::Write;
+    let sink = RecordSink::new(Vec::new());
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let sink = &sink;
+            s.spawn(move || {
+                for i in 0..100 {
+                    let mut w = sink.writer();
+                    // written in pieces, but emitted as one record
+                    write!(w, "thread {t} ").unwrap();
+                    writeln!(w, "record {i}").unwrap();
+                }
+            });
+        }
+    });
+    let mut w = sink.writer();
+    write!(w, "last").unwrap();
+    w.flush().unwrap();
+    w.flush().unwrap();
+    drop(w);
+    let out = String::from_utf8(sink.into_inner()).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 401);
+    assert!(lines[..400]
+        .iter()
+        .all(|l| l.starts_with("thread ") && l.contains(" record ")));
+    assert_eq!(lines[400], "last");
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn pooled_logger() {
+    use crate::log::PooledLogger;
+    use ::log::{Level, LevelFilter, Log, Record};
+    let logger = PooledLogger::new(Vec::new(), LevelFilter::Info);
+    let record = |level, msg| {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target("app")
+                .args(format_args!("{msg}"))
+                .build(),
+        )
+    };
+    record(Level::Info, "started");
+    record(Level::Debug, "hidden");
+    record(Level::Warn, "slow");
+    let logger = logger.with_format(|w, r| writeln!(w, "[{}] {}", r.level(), r.args()));
+    logger
+        .log(&Record::builder().level(Level::Error).args(format_args!("failed")).build());
+    logger.flush();
+    assert_eq!(
+        String::from_utf8(logger.into_inner()).unwrap(),
+        "INFO  app: started\nWARN  app: slow\n[ERROR] failed\n"
+    );
+}
+
+#[test]
+fn compat_object_pool() {
+    use crate::compat::object_pool::Pool;
+    let pool: Pool<Vec<u8>> = Pool::new(2, || Vec::with_capacity(16));
+    assert_eq!(pool.len(), 2);
+    let mut a = pool.pull(|| Vec::with_capacity(16));
+    let b = pool.try_pull().unwrap();
+    assert!(pool.try_pull().is_none());
+    let c = pool.pull(|| Vec::with_capacity(16));
+    a.push(1);
+    drop(a);
+    assert_eq!(pool.len(), 1);
+    assert!(pool.try_pull().unwrap().is_empty());
+    let (p, v) = b.detach();
+    assert_eq!(pool.len(), 1);
+    p.attach(v);
+    drop(c); // the pool is full
+    assert_eq!(pool.len(), 2);
+    // empty pools still work
+    let pool: Pool<Vec<u8>> = Pool::new(0, Vec::new);
+    assert!(pool.is_empty());
+    drop(pool.pull(|| Vec::with_capacity(16)));
+    assert_eq!(pool.len(), 1);
+    assert!(Pool::<Vec<u8>>::from_vec(vec![]).is_empty());
+}
+
+#[test]
+fn compat_lifeguard() {
+    use crate::compat::lifeguard::Pool;
+    let pool: Pool<String> = Pool::with_size_and_max(2, 4);
+    assert_eq!((pool.size(), pool.max_size()), (2, 4));
+    let a = pool.new_from("cat");
+    let b = pool.new_rc_from(String::from("dog"));
+    assert_eq!((a.as_str(), b.as_str()), ("cat", "dog"));
+    assert_eq!(pool.size(), 0);
+    let s = a.detach();
+    drop(b);
+    assert_eq!(pool.size(), 1);
+    pool.attach(s);
+    assert_eq!(pool.size(), 2);
+    assert!(pool.detach().unwrap().is_empty());
+    assert_eq!(pool.size(), 1);
+    let pool: Pool<String> = Pool::with_size_and_max(0, 0);
+    assert_eq!((pool.size(), pool.max_size()), (0, 1));
+}
+
+#[test]
+fn prefill() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    assert_eq!(pool.prefill(4), 4);
+    assert_eq!(pool.len(), 4);
+    assert_eq!(pool.prefill_with_capacity(8, 1024), 4);
+    assert_eq!(pool.len(), 8);
+    let v: Vec<_> = (0..8).map(|_| pool.take()).collect();
+    assert_eq!(v.iter().filter(|v| v.capacity() >= 1024).count(), 4);
+    std::thread::spawn(|| {
+        use crate::local;
+        local::set_size::<Vec<u16>>(4, 512);
+        assert_eq!(local::prefill_with_capacity::<Vec<u16>>(2, 1024), 0);
+        assert_eq!(local::prefill_with_capacity::<Vec<u16>>(2, 512), 2);
+        assert_eq!(local::prefill::<Vec<u16>>(4), 2);
+        let a = LPooled::<Vec<u16>>::take();
+        let b = LPooled::<Vec<u16>>::take();
+        let c = LPooled::<Vec<u16>>::take();
+        assert_eq!(c.capacity(), 512);
+        drop((a, b, c));
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn take_many() {
+    let pool: Pool<String> = Pool::new(8, 1024);
+    let mut batch: Vec<_> = pool.take_many(4).collect();
+    for s in &mut batch {
+        s.push('x');
+    }
+    drop(batch);
+    assert_eq!(pool.len(), 4);
+    let mut it = pool.take_many(6);
+    assert_eq!(it.len(), 6);
+    let batch: Vec<_> = it.by_ref().take(5).collect();
+    assert_eq!(it.len(), 1);
+    assert_eq!(batch.iter().filter(|s| s.capacity() > 0).count(), 4);
+    assert!(pool.is_empty());
+    std::thread::spawn(|| {
+        use crate::local;
+        for _ in 0..3 {
+            let v = Vec::<i64>::with_capacity(8);
+            unsafe { local::insert_raw(v) };
+        }
+        let mut it = local::take_many::<Vec<i64>>(5);
+        let a = it.next().unwrap();
+        assert!(a.capacity() >= 8);
+        drop(it);
+        // the two pooled objects it didn't hand out went back
+        assert_eq!(
+            local::take_many::<Vec<i64>>(5).filter(|v| v.capacity() > 0).count(),
+            2
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(feature = "faults")]
+#[test]
+fn faults() {
+    use crate::testing::faults::{self, Fault, Schedule};
+    // a type no other test pools, faults are per thread but pools aren't
+    let pool: Pool<Vec<i128>> = Pool::new(8, 1024);
+    pool.take().push(0);
+    {
+        let f = faults::inject::<Vec<i128>>(Fault::Empty, Schedule::Nth(2));
+        assert!(pool.try_take().is_some());
+        assert!(pool.try_take().is_none());
+        assert_eq!(f.calls(), 2);
+    }
+    pool.take().push(0);
+    {
+        let _f = faults::inject::<Vec<i128>>(Fault::Full, Schedule::Every(2));
+        let v: Vec<_> = (0..4).map(|_| pool.take()).collect();
+        let v: Vec<_> = v
+            .into_iter()
+            .map(|mut v| {
+                v.push(0);
+                v
+            })
+            .collect();
+        drop(v);
+        assert_eq!(pool.len(), 2);
+    }
+    {
+        let _f = faults::inject::<Vec<i128>>(Fault::Orphan, Schedule::First(1));
+        assert!(pool.take().is_orphan());
+        assert!(!pool.take().is_orphan());
+    }
+    // dropping the guard removes the fault
+    let _ = faults::inject::<Vec<i128>>(Fault::Orphan, Schedule::Always);
+    assert!(!pool.take().is_orphan());
+    let _f = faults::inject::<Vec<i128>>(Fault::Orphan, Schedule::Always);
+    faults::clear();
+    assert!(!pool.take().is_orphan());
+    std::thread::spawn(|| {
+        use crate::local;
+        let f = faults::inject::<Vec<i128>>(Fault::Full, Schedule::Always);
+        assert!(local::insert(Vec::<i128>::with_capacity(8)).is_some());
+        drop(f);
+        assert!(local::insert(Vec::<i128>::with_capacity(8)).is_none());
+        let _f = faults::inject::<Vec<i128>>(Fault::Empty, Schedule::First(1));
+        assert_eq!(local::take::<Vec<i128>>().capacity(), 0);
+        assert_eq!(local::take::<Vec<i128>>().capacity(), 8);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(feature = "faults")]
+#[test]
+fn faults_max_takers() {
+    use crate::testing::faults::{self, Fault, Schedule};
+    let pool: Pool<Vec<i128>> = Pool::new(8, 1024);
+    pool.set_max_takers(1);
+    {
+        let _f = faults::inject::<Vec<i128>>(Fault::Orphan, Schedule::First(4));
+        for _ in 0..4 {
+            assert!(pool.take().is_orphan());
+        }
+    }
+    // injected orphans never took a slot, so the one slot is still free
+    assert_eq!(pool.takers(), 0);
+    let a = pool.take();
+    assert!(!a.is_orphan());
+    assert_eq!(pool.takers(), 1);
+    assert!(pool.take().is_orphan());
+}
+
+#[test]
+fn insert_many() {
+    let pool: Pool<String> = Pool::new(4, 1024);
+    let mut batch: Vec<_> = (0..6).map(|_| pool.take()).collect();
+    for s in &mut batch {
+        s.push_str("abc");
+    }
+    batch[1].push_str(&"x".repeat(2048));
+    pool.insert_many(batch.drain(..));
+    assert_eq!(pool.len(), 4);
+    assert!(pool.take_many(4).all(|s| s.is_empty() && s.capacity() <= 1024));
+    std::thread::spawn(|| {
+        use crate::local;
+        local::set_size::<Vec<u32>>(2, 1024);
+        local::insert_many((0..3).map(|i| vec![i; 8]));
+        let a = local::take::<Vec<u32>>();
+        let b = local::take::<Vec<u32>>();
+        let c = local::take::<Vec<u32>>();
+        assert!(a.is_empty() && a.capacity() >= 8 && b.capacity() >= 8);
+        assert_eq!(c.capacity(), 0);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn take_order() {
+    use crate::global::backend::TakeOrder;
+    let fifo: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let lifo: Pool<Vec<u8>> = Pool::new_lifo(8, 1024);
+    assert_eq!(fifo.order(), TakeOrder::Fifo);
+    assert_eq!(lifo.order(), TakeOrder::Lifo);
+    for pool in [&fifo, &lifo] {
+        let v: Vec<_> = (1..=3)
+            .map(|i| {
+                let mut v = pool.take();
+                v.reserve_exact(i * 16);
+                v
+            })
+            .collect();
+        drop(v);
+    }
+    assert_eq!(fifo.take().capacity(), 16);
+    assert_eq!(lifo.take().capacity(), 48);
+}
+
+#[cfg(all(feature = "base64", feature = "hex"))]
+#[test]
+fn encode_pooled() {
+    use crate::encode::{base64_encode_pooled, hex_encode_pooled};
+    std::thread::spawn(|| {
+        let s = hex_encode_pooled(b"\x00\x01\xfe\xff");
+        assert_eq!(&*s, "0001feff");
+        let ptr = s.as_ptr();
+        drop(s);
+        let s = base64_encode_pooled("ab");
+        assert_eq!(&*s, "YWI=");
+        // the same buffer, reused
+        assert_eq!(s.as_ptr(), ptr);
+        assert_eq!(&*hex_encode_pooled([]), "");
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn resize() {
+    let pool: Pool<Vec<u8>> = Pool::new_resizable(4, 1024);
+    let take = |n: usize| {
+        let v: Vec<_> = (0..n)
+            .map(|_| {
+                let mut v = pool.take();
+                v.push(0);
+                v
+            })
+            .collect();
+        v
+    };
+    drop(take(4));
+    assert_eq!(pool.len(), 4);
+    assert!(pool.resize(8));
+    assert_eq!((pool.len(), pool.capacity()), (4, 8));
+    drop(take(8));
+    assert_eq!(pool.len(), 8);
+    assert!(pool.resize(2));
+    assert_eq!((pool.len(), pool.capacity()), (2, 2));
+    let handle = pool.clone();
+    drop(take(4));
+    assert_eq!(handle.len(), 2);
+    let lifo: Pool<Vec<u8>> = Pool::new_lifo(4, 1024);
+    assert!(!lifo.resize(8));
+    assert_eq!(lifo.capacity(), 4);
+    let fixed: Pool<Vec<u8>> = Pool::new(4, 1024);
+    assert!(!fixed.resize(8));
+    assert_eq!(fixed.capacity(), 4);
+    // a clone of the configuration is resizable too
+    assert!(pool.clone_config().resize(8));
+}
+
+#[test]
+fn resize_under_load() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    static LIVE: AtomicUsize = AtomicUsize::new(0);
+    struct Counted(Vec<u8>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            LIVE.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+    impl crate::Poolable for Counted {
+        fn empty() -> Self {
+            LIVE.fetch_add(1, Ordering::Relaxed);
+            Counted(Vec::new())
+        }
+        fn reset(&mut self) {
+            self.0.clear()
+        }
+        fn capacity(&self) -> usize {
+            self.0.capacity()
+        }
+    }
+    let pool: Pool<Counted> = Pool::new_resizable(16, 1024);
+    let stop = std::sync::Arc::new(AtomicBool::new(false));
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let (pool, stop) = (pool.clone(), stop.clone());
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let batch: Vec<_> = (0..4)
+                        .map(|_| {
+                            let mut t = pool.take();
+                            t.0.push(0);
+                            t
+                        })
+                        .collect();
+                    drop(batch)
+                }
+            })
+        })
+        .collect();
+    for i in 0..2000 {
+        assert!(pool.resize(1 + i % 32));
+    }
+    stop.store(true, Ordering::Relaxed);
+    for w in workers {
+        w.join().unwrap()
+    }
+    // every object still alive is in the pool, none are stranded in a
+    // queue that was resized away, and the accounting agrees
+    let n = pool.len();
+    assert!(n <= pool.capacity());
+    assert_eq!(LIVE.load(Ordering::Relaxed), n);
+    #[cfg(feature = "stats")]
+    assert_eq!(pool.retained_bytes(), n * 8);
+    drop(pool);
+    assert_eq!(LIVE.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn clear_pool() {
+    let pool: Pool<String> = Pool::new(8, 1024);
+    assert_eq!(pool.prefill_with_capacity(8, 64), 8);
+    #[cfg(feature = "stats")]
+    assert!(pool.retained_bytes() > 0);
+    pool.clear();
+    assert!(pool.is_empty());
+    assert_eq!(pool.retained_bytes(), 0);
+    // still pooling
+    pool.take().push_str("still pooled");
+    assert_eq!(pool.len(), 1);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn thread_local_stats() {
+    use std::sync::mpsc;
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let (to_main, from_worker) = mpsc::channel();
+    let (to_worker, from_main) = mpsc::channel();
+    let worker = {
+        let pool = pool.clone();
+        std::thread::spawn(move || {
+            for _ in 0..10 {
+                drop(pool.take())
+            }
+            to_main.send(()).unwrap();
+            from_main.recv().unwrap();
+            crate::stats::flush();
+            to_main.send(()).unwrap();
+            from_main.recv().unwrap();
+            for _ in 0..5 {
+                drop(pool.take())
+            }
+        })
+    };
+    drop(pool.take());
+    from_worker.recv().unwrap();
+    // the worker's counts are still pending, ours are flushed by stats
+    assert_eq!(pool.stats().counts.takes, 1);
+    to_worker.send(()).unwrap();
+    from_worker.recv().unwrap();
+    assert_eq!(pool.stats().counts.takes, 11);
+    to_worker.send(()).unwrap();
+    worker.join().unwrap();
+    // and the rest are flushed when it exits
+    assert_eq!(pool.stats().counts.takes, 16);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn detach_accounting() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let (a, b, _c) = (pool.take(), pool.take(), pool.take());
+    assert_eq!(pool.stats().outstanding, 3);
+    let v = a.detach();
+    drop(b);
+    let stats = pool.stats();
+    assert_eq!((stats.outstanding, stats.counts.detached), (1, 1));
+    drop(v);
+    assert_eq!(pool.stats().counts.returns, 1);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn map_lost_accounting() {
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let a = pool.take();
+    assert!(a.try_map(|_| Err::<Vec<u8>, _>(())).is_err());
+    let stats = pool.stats();
+    assert_eq!((stats.outstanding, stats.counts.detached), (0, 1));
+}
+
+#[test]
+fn prelude_aliases() {
+    use crate::prelude::*;
+    std::thread::spawn(|| {
+        let mut s = PooledFxSet::<u32>::take();
+        s.reserve(8);
+        drop(s);
+        assert!(LPooled::<fxhash::FxHashSet<u32>>::take().capacity() >= 8);
+        let mut m = PooledMap::<u32, PooledString>::take();
+        m.insert(1, PooledString::take());
+        let mut d = PooledVecDeque::<u8>::take();
+        d.push_back(1);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn take_with_capacity_scan() {
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1 << 20);
+    let small: Vec<_> = (0..3).map(|_| pool.take_with_capacity(8)).collect();
+    let big = pool.take_with_capacity(4096);
+    drop(small);
+    drop(big);
+    let v = pool.take_with_capacity(4096);
+    assert!(v.capacity() >= 4096);
+    assert_eq!(pool.len(), 3);
+    // more small ones than are scanned in front of the big one
+    let pool: Pool<Vec<u8>> = Pool::new(16, 1 << 20);
+    let mut v: Vec<_> = (0..4).map(|_| pool.take_with_capacity(8)).collect();
+    v.push(pool.take_with_capacity(4096));
+    drop(v);
+    let v = pool.take_with_capacity(2048);
+    assert!(v.capacity() >= 2048);
+    assert_eq!(pool.len(), 5);
+    std::thread::spawn(|| {
+        use crate::local;
+        local::insert(Vec::<u16>::with_capacity(1024));
+        for _ in 0..4 {
+            local::insert(Vec::<u16>::with_capacity(4));
+        }
+        // out of reach of the scan, so a new one is allocated
+        let v = LPooled::<Vec<u16>>::take_with_capacity(1024);
+        assert!(v.capacity() >= 1024);
+        let w = local::take_with_capacity::<Vec<u16>>(4);
+        assert_eq!(w.capacity(), 4);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn histogram_bucket_ranges() {
+    use crate::stats::{Histogram, HISTOGRAM_BUCKETS};
+    let mut prev_end = 0;
+    for i in 0..HISTOGRAM_BUCKETS {
+        let r = Histogram::bucket_range(i);
+        // contiguous, and each value is counted in the bucket it falls in
+        assert_eq!(r.start, prev_end);
+        assert_eq!(Histogram::bucket(r.start), i);
+        assert_eq!(Histogram::bucket(r.end - 1), i);
+        prev_end = r.end;
+    }
+    assert_eq!(Histogram::bucket_range(HISTOGRAM_BUCKETS - 1).end, usize::MAX);
+}
+
+// Run `test` alone in a child process, for tests of process wide settings
+// that would leak into the other tests running at the same time. Returns
+// true in the child, which should run the test, and false in the parent,
+// once the child passed.
+fn in_child_process(test: &str) -> bool {
+    if std::env::var_os("POOLSHARK_TEST_CHILD").is_some() {
+        return true;
+    }
+    let out = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", test, "--nocapture"])
+        .env("POOLSHARK_TEST_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    false
+}
+
+#[test]
+fn local_budget() {
+    use crate::local;
+    // the budget would catch the pools of every other test
+    if !in_child_process("test::local_budget") {
+        return;
+    }
+    local::set_budget(Some(1000));
+    let st = local::budget();
+    assert_eq!((st.max_bytes, st.retained_bytes, st.rejected), (Some(1000), 0, 0));
+    let mut a: LPooled<Vec<u8>> = LPooled::take();
+    let mut b: LPooled<Vec<u8>> = LPooled::take();
+    a.reserve_exact(600);
+    b.reserve_exact(600);
+    drop(a);
+    assert_eq!(local::budget().retained_bytes, 600);
+    drop(b);
+    // b didn't fit, so it was really dropped instead of pooled
+    let st = local::budget();
+    assert_eq!((st.retained_bytes, st.rejected), (600, 1));
+    let a: LPooled<Vec<u8>> = LPooled::take();
+    let b: LPooled<Vec<u8>> = LPooled::take();
+    assert_eq!((a.capacity(), b.capacity()), (600, 0));
+    // taking releases the bytes
+    assert_eq!(local::budget().retained_bytes, 0);
+    drop(b);
+    drop(a);
+    assert_eq!(local::budget().retained_bytes, 600);
+    local::clear();
+    assert_eq!(local::budget().retained_bytes, 0);
+    std::thread::spawn(|| {
+        let mut s: LPooled<String> = LPooled::take();
+        s.reserve_exact(100);
+        drop(s);
+        assert_eq!(local::budget().retained_bytes, 100);
+    })
+    .join()
+    .unwrap();
+    // the thread's pools were dropped when it exited
+    assert_eq!(local::budget().retained_bytes, 0);
+    local::set_budget(None);
+    let st = local::budget();
+    assert_eq!((st.max_bytes, st.rejected), (None, 1));
+}
+
+#[cfg(feature = "epoch")]
+#[test]
+fn epoch_bump() {
+    use crate::global::{epoch, GPooled};
+    // a bump would drop the pooled objects of every other test
+    if !in_child_process("test::epoch_bump") {
+        return;
+    }
+    let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+    let (mut pooled, mut outstanding) = (pool.take(), pool.take());
+    pooled.reserve(8);
+    outstanding.reserve(8);
+    drop(pooled);
+    assert_eq!(pool.len(), 1);
+    epoch::bump();
+    // the object pooled before the bump is really dropped by the take
+    assert_eq!(pool.take().capacity(), 0);
+    assert_eq!(pool.len(), 0);
+    drop(outstanding);
+    assert_eq!(pool.len(), 0);
+    let mut fresh = pool.take();
+    fresh.reserve(8);
+    drop(fresh);
+    assert!(pool.take().capacity() >= 8);
+    // the same for this thread's global pools
+    let (mut pooled, mut outstanding) =
+        (GPooled::<Vec<u16>>::take(), GPooled::<Vec<u16>>::take());
+    pooled.reserve(8);
+    outstanding.reserve(8);
+    drop(pooled);
+    epoch::bump();
+    drop(outstanding);
+    assert_eq!(GPooled::<Vec<u16>>::take().capacity(), 0);
+    let mut fresh = GPooled::<Vec<u16>>::take();
+    fresh.reserve(8);
+    drop(fresh);
+    assert!(GPooled::<Vec<u16>>::take().capacity() >= 8);
+}
+
+#[cfg(not(any(
+    feature = "epoch",
+    feature = "stats",
+    feature = "leaks",
+    feature = "tracing"
+)))]
+#[test]
+fn gpooled_size() {
+    // just the pool pointer on top of the object
+    assert_eq!(
+        std::mem::size_of::<global::GPooled<Vec<u8>>>(),
+        std::mem::size_of::<Vec<u8>>() + std::mem::size_of::<usize>()
+    );
+}
@@ -0,0 +1,175 @@
+//! Forcing pools down their rare paths in tests.
+//!
+//! Code that uses pools has branches that ordinary tests never reach,
+//! because in a test the pool almost always has what is asked of it. With
+//! the `faults` feature a test can [inject] a [Fault] for one pooled type,
+//! so that takes find the pool empty, takes get an
+//! [orphan](crate::global::GPooled::orphan) that has no pool, or inserts
+//! find the pool full, on exactly the calls it chooses.
+//!
+//! Faults are per thread, they only affect pool calls made by the thread
+//! that injected them, so tests running in parallel don't see each other's
+//! faults. Each injected fault counts the calls it applies to, takes for
+//! [Empty](Fault::Empty) and [Orphan](Fault::Orphan) and inserts for
+//! [Full](Fault::Full), and its [Schedule] picks which of them fail. It
+//! lasts until the returned [Injected] guard is dropped.
+//!
+//! Both global and local pools of the type are affected. Local objects
+//! have no orphans, a take that gets one allocates without counting a
+//! miss, as if the type had no pool.
+//!
+//! Without the feature the checks compile away.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "faults")] {
+//! use poolshark::{
+//!     global::Pool,
+//!     testing::faults::{self, Fault, Schedule},
+//! };
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+//! pool.take().push(0);
+//! {
+//!     let _f = faults::inject::<Vec<u8>>(Fault::Empty, Schedule::Nth(2));
+//!     assert!(pool.try_take().is_some());
+//!     assert!(pool.try_take().is_none()); // the second take fails
+//! }
+//! let _f = faults::inject::<Vec<u8>>(Fault::Orphan, Schedule::Always);
+//! assert!(pool.take().is_orphan());
+//! # }
+//! ```
+
+/// Which path to force a pool down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// Takes find the pool empty
+    Empty,
+    /// Inserts find the pool full, and drop the object
+    Full,
+    /// Takes return an object that doesn't belong to any pool
+    Orphan,
+}
+
+/// Which of the calls a fault applies to fail, counting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Schedule {
+    /// Every call
+    Always,
+    /// Only the nth call
+    Nth(u64),
+    /// The first n calls
+    First(u64),
+    /// Every nth call
+    Every(u64),
+}
+
+#[cfg(feature = "faults")]
+impl Schedule {
+    fn fails(&self, call: u64) -> bool {
+        match *self {
+            Self::Always => true,
+            Self::Nth(n) => call == n,
+            Self::First(n) => call <= n,
+            Self::Every(n) => n > 0 && call.is_multiple_of(n),
+        }
+    }
+}
+
+#[cfg(feature = "faults")]
+mod imp {
+    use super::{Fault, Schedule};
+    use std::{cell::RefCell, marker::PhantomData};
+
+    struct Rule {
+        id: u64,
+        type_name: &'static str,
+        fault: Fault,
+        schedule: Schedule,
+        calls: u64,
+    }
+
+    thread_local! {
+        static RULES: RefCell<(u64, Vec<Rule>)> = const { RefCell::new((0, Vec::new())) };
+    }
+
+    /// Keeps an injected fault active, see [inject](super::inject)
+    #[derive(Debug)]
+    #[must_use = "the fault is removed when this is dropped"]
+    pub struct Injected {
+        id: u64,
+        // the fault belongs to the thread that injected it
+        _thread: PhantomData<*const ()>,
+    }
+
+    impl Injected {
+        /// Return the number of calls the fault has counted so far
+        pub fn calls(&self) -> u64 {
+            RULES.with_borrow(|(_, rules)| {
+                rules.iter().find(|r| r.id == self.id).map_or(0, |r| r.calls)
+            })
+        }
+    }
+
+    impl Drop for Injected {
+        fn drop(&mut self) {
+            let _ = RULES.try_with(|r| r.borrow_mut().1.retain(|r| r.id != self.id));
+        }
+    }
+
+    /// Force calls to pools of `T` made on this thread down the path of
+    /// `fault`, on the calls chosen by `schedule`, until the returned guard
+    /// is dropped.
+    pub fn inject<T: ?Sized>(fault: Fault, schedule: Schedule) -> Injected {
+        RULES.with_borrow_mut(|(next, rules)| {
+            *next += 1;
+            rules.push(Rule {
+                id: *next,
+                type_name: std::any::type_name::<T>(),
+                fault,
+                schedule,
+                calls: 0,
+            });
+            Injected { id: *next, _thread: PhantomData }
+        })
+    }
+
+    /// Remove every fault injected on this thread, outstanding guards
+    /// become inert
+    pub fn clear() {
+        RULES.with_borrow_mut(|(_, rules)| rules.clear())
+    }
+
+    /// Count a call to a pool of `type_name` that `fault` could apply to,
+    /// and return true if it should fail
+    pub(crate) fn injected(type_name: &'static str, fault: Fault) -> bool {
+        RULES
+            .try_with(|r| {
+                let mut r = r.borrow_mut();
+                let mut fail = false;
+                for rule in r.1.iter_mut() {
+                    if rule.fault == fault && rule.type_name == type_name {
+                        rule.calls += 1;
+                        fail |= rule.schedule.fails(rule.calls)
+                    }
+                }
+                fail
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(not(feature = "faults"))]
+mod imp {
+    use super::Fault;
+
+    #[inline(always)]
+    pub(crate) fn injected(_type_name: &'static str, _fault: Fault) -> bool {
+        false
+    }
+}
+
+pub(crate) use imp::injected;
+#[cfg(feature = "faults")]
+pub use imp::{clear, inject, Injected};
@@ -0,0 +1,76 @@
+//! Helpers for tests and benchmarks of pooled code.
+//!
+//! A benchmark of pooled code that accidentally allocates on every
+//! iteration, because the object was too large to be retained or was never
+//! returned, still runs, it just measures the allocator instead of the pool.
+//! The `must_hit` functions take from a pool like normal, but fail if the
+//! pool was empty, so tests and benchmarks can prove they exercise the
+//! recycled path.
+//!
+//! ```
+//! use poolshark::{global::Pool, testing};
+//!
+//! let pool: Pool<Vec<u8>> = Pool::new(8, 1024);
+//! let mut v = pool.take();
+//! v.extend_from_slice(b"warm up");
+//! drop(v);
+//! for _ in 0..1000 {
+//!     // panics if the vec above was not recycled
+//!     let mut v = testing::take_must_hit(&pool);
+//!     v.extend_from_slice(b"hello");
+//! }
+//! ```
+pub mod faults;
+
+use crate::{
+    global::RawPool,
+    local::{self, LPooled},
+    IsoPoolable, RawPoolable,
+};
+use std::{any::type_name, error::Error, fmt};
+
+/// A must hit take found the pool empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolMiss {
+    /// The type that was being taken
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for PoolMiss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the pool of {} was empty", self.type_name)
+    }
+}
+
+impl Error for PoolMiss {}
+
+/// Take an object from `pool`, or return an error if it is empty
+pub fn try_take_must_hit<T: RawPoolable>(pool: &RawPool<T>) -> Result<T, PoolMiss> {
+    pool.try_take().ok_or(PoolMiss { type_name: type_name::<T>() })
+}
+
+/// Take an object from `pool`, panicking if it is empty
+#[track_caller]
+pub fn take_must_hit<T: RawPoolable>(pool: &RawPool<T>) -> T {
+    match try_take_must_hit(pool) {
+        Ok(t) => t,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Take an object from the thread local pool, or return an error if it is
+/// empty
+pub fn try_take_local_must_hit<T: IsoPoolable>() -> Result<LPooled<T>, PoolMiss> {
+    local::try_take::<T>()
+        .map(LPooled::from)
+        .ok_or(PoolMiss { type_name: type_name::<T>() })
+}
+
+/// Take an object from the thread local pool, panicking if it is empty
+#[track_caller]
+pub fn take_local_must_hit<T: IsoPoolable>() -> LPooled<T> {
+    match try_take_local_must_hit() {
+        Ok(t) => t,
+        Err(e) => panic!("{e}"),
+    }
+}